@@ -1,6 +1,10 @@
-use std::fs::File;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use toml::de::{DeTable, DeValue};
 
 fn main() {
     let out = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
@@ -16,5 +20,445 @@ fn main() {
     f.write_all(rp235x_riscv_x).unwrap();
     println!("cargo:rerun-if-changed=rp235x_riscv.x");
 
+    generate_config(&out);
+
     println!("cargo:rerun-if-changed=build.rs");
 }
+
+// ---------------------------------------------------------------------------
+// dashboard.toml
+//
+// Tailors a build to one car without touching the sources. Every key is optional
+// and falls back to the default below; `dashboard.example.toml` lists them all.
+// The file is read from the package directory, or from the path in
+// `DASHBOARD_CONFIG`, and turned into `thresholds.rs` (included by
+// `config::sensors`) and `dashboard.rs` (included by `config::dashboard`).
+// ---------------------------------------------------------------------------
+
+const CONFIG_FILE: &str = "dashboard.toml";
+
+/// A chain of limits that must stay in ascending order within the range the
+/// settings menu edits it in. Each limit is a constant of `config::sensors` and a
+/// key of `[thresholds]`, in lower case; the range is generated as `{name}_EDIT_RANGE`
+/// for `limits::Chain`.
+struct Chain {
+    name: &'static str,
+    min: f64,
+    max: f64,
+    limits: &'static [(&'static str, f64)],
+}
+
+const CHAINS: &[Chain] = &[
+    Chain {
+        name: "OIL",
+        min: 40.0,
+        max: 160.0,
+        limits: &[
+            ("OIL_LOW_TEMP", 75.0),
+            ("OIL_ELEVATED", 90.0),
+            ("OIL_HIGH", 100.0),
+            ("OIL_CRITICAL", 110.0),
+        ],
+    },
+    Chain {
+        name: "DSG",
+        min: 40.0,
+        max: 160.0,
+        limits: &[("DSG_ELEVATED", 90.0), ("DSG_HIGH", 100.0), ("DSG_CRITICAL", 110.0)],
+    },
+    Chain {
+        name: "COOLANT",
+        min: 40.0,
+        max: 130.0,
+        limits: &[("COOLANT_COLD_MAX", 75.0), ("COOLANT_CRITICAL", 90.0)],
+    },
+    Chain {
+        name: "IAT",
+        min: -40.0,
+        max: 90.0,
+        limits: &[
+            ("IAT_EXTREME_COLD", -20.0),
+            ("IAT_COLD", 0.0),
+            ("IAT_WARM", 25.0),
+            ("IAT_HOT", 45.0),
+            ("IAT_CRITICAL", 60.0),
+        ],
+    },
+    Chain {
+        name: "EGT",
+        min: 100.0,
+        max: 1_250.0,
+        limits: &[
+            ("EGT_COLD_MAX", 300.0),
+            ("EGT_SPIRITED", 600.0),
+            ("EGT_HIGH_LOAD", 850.0),
+            ("EGT_CRITICAL", 950.0),
+            ("EGT_DANGER_MANIFOLD", 1_050.0),
+        ],
+    },
+    Chain {
+        name: "BATT",
+        min: 10.0,
+        max: 15.0,
+        limits: &[("BATT_CRITICAL", 12.0), ("BATT_WARNING", 12.5)],
+    },
+    // Mixture bands are in lambda, so they hold for any fuel; AFR readings scale with its stoichiometric ratio
+    Chain {
+        name: "LAMBDA",
+        min: 0.6,
+        max: 1.3,
+        limits: &[
+            ("LAMBDA_RICH_AF", 0.82),
+            ("LAMBDA_RICH", 0.95),
+            ("LAMBDA_OPTIMAL_MAX", 1.01),
+            ("LAMBDA_LEAN_CRITICAL", 1.05),
+        ],
+    },
+    Chain {
+        name: "BOOST",
+        min: 0.2,
+        max: 3.0,
+        limits: &[("BOOST_HIGH", 1.6), ("BOOST_CRITICAL", 1.9)],
+    },
+    Chain {
+        name: "RPM",
+        min: 1_000.0,
+        max: 9_000.0,
+        limits: &[("RPM_SHIFT", 6_000.0), ("RPM_REDLINE", 6_500.0)],
+    },
+];
+
+/// Title of the loading screen, between two spinners.
+const DEFAULT_TITLE: &str = "Loading";
+const MAX_TITLE_LEN: usize = 24;
+
+/// Init log of the loading screen. A line that is exactly `{model}` or `{gearbox}`
+/// shows that part of the vehicle profile.
+const DEFAULT_BOOT_LINES: &[(&str, u64)] = &[
+    ("Initializing OBD-II interface...", 800),
+    ("Connecting to ECU...", 1200),
+    ("Reading vehicle info...", 1000),
+    ("{model}", 600),
+    ("{gearbox}", 600),
+    ("Loading sensors...", 800),
+    ("Ready.", 500),
+];
+/// Console width of the loading screen, less the cursor prefix.
+const MAX_BOOT_LINE_LEN: usize = 48;
+const MAX_BOOT_LINE_MS: u64 = 10_000;
+
+const DEFAULT_LAYOUT: &str = "FULL";
+
+/// Accepted values of one `[units]` key, in the spelling of the settings menu, with
+/// the variant they select; the first is the default.
+struct UnitChoice {
+    key: &'static str,
+    ty: &'static str,
+    options: &'static [(&'static str, &'static str)],
+}
+
+const UNITS: &[UnitChoice] = &[
+    UnitChoice {
+        key: "temperature",
+        ty: "TempUnit",
+        options: &[("C", "Celsius"), ("F", "Fahrenheit")],
+    },
+    UnitChoice {
+        key: "pressure",
+        ty: "PressureUnit",
+        options: &[("BAR", "Bar"), ("PSI", "Psi"), ("KPA", "Kpa")],
+    },
+    UnitChoice {
+        key: "mixture",
+        ty: "MixtureUnit",
+        options: &[("AFR", "Afr"), ("LAMBDA", "Lambda")],
+    },
+    UnitChoice {
+        key: "fuel",
+        ty: "Fuel",
+        options: &[("E0", "E0"), ("E10", "E10"), ("E85", "E85")],
+    },
+];
+
+fn generate_config(out: &Path) {
+    println!("cargo:rerun-if-env-changed=DASHBOARD_CONFIG");
+    let path = match std::env::var_os("DASHBOARD_CONFIG") {
+        Some(path) => PathBuf::from(path),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join(CONFIG_FILE),
+    };
+
+    let text = if path.exists() {
+        println!("cargo:rerun-if-changed={}", path.display());
+        fs::read_to_string(&path).unwrap_or_else(|e| fail(&path, &e.to_string()))
+    } else {
+        // Watch the directory instead, so creating the file triggers a rebuild
+        println!(
+            "cargo:rerun-if-changed={}",
+            path.parent().unwrap_or(Path::new(".")).display()
+        );
+        String::new()
+    };
+    let mut config = parse(&text).unwrap_or_else(|e| fail(&path, &e));
+
+    let thresholds = thresholds(&mut config).unwrap_or_else(|e| fail(&path, &e));
+    let dashboard = dashboard(&mut config).unwrap_or_else(|e| fail(&path, &e));
+    if let Some((key, entry)) = config.iter().next() {
+        fail(&path, &format!("line {}: unknown key `{key}`", entry.line));
+    }
+
+    fs::write(out.join("thresholds.rs"), thresholds).unwrap();
+    fs::write(out.join("dashboard.rs"), dashboard).unwrap();
+}
+
+/// Report a configuration error and stop the build.
+fn fail(
+    path: &Path,
+    message: &str,
+) -> ! {
+    eprintln!("error: {}: {message}", path.display());
+    std::process::exit(1);
+}
+
+const GENERATED: &str = "// Generated by build.rs from dashboard.toml. Do not edit.\n\n";
+
+/// The limit constants, each chain's edit range, and assertions restating each
+/// chain's order in the generated file itself.
+fn thresholds(config: &mut Config<'_>) -> Result<String, String> {
+    let mut code = String::from(GENERATED);
+    for chain in CHAINS {
+        let _ = writeln!(
+            code,
+            "pub const {}_EDIT_RANGE: (f32, f32) = ({:?}, {:?});",
+            chain.name, chain.min as f32, chain.max as f32
+        );
+        let mut below: Option<(String, f64)> = None;
+        let mut below_name = None;
+        for &(name, default) in chain.limits {
+            let key = format!("thresholds.{}", name.to_lowercase());
+            let value = match config.remove(&key) {
+                Some(entry) => {
+                    let value = entry.number(&key)?;
+                    if !(chain.min..=chain.max).contains(&value) {
+                        return Err(format!(
+                            "line {}: `{key}` = {value} is outside {} to {}",
+                            entry.line, chain.min, chain.max
+                        ));
+                    }
+                    if let Some((below_key, below_value)) = &below
+                        && value <= *below_value
+                    {
+                        return Err(format!(
+                            "line {}: `{key}` = {value} must be above `{below_key}` = {below_value}",
+                            entry.line
+                        ));
+                    }
+                    value
+                }
+                None => {
+                    if let Some((below_key, below_value)) = &below
+                        && default <= *below_value
+                    {
+                        return Err(format!(
+                            "`{below_key}` = {below_value} must be below `{key}`, which defaults to {default}"
+                        ));
+                    }
+                    default
+                }
+            };
+            let _ = writeln!(code, "pub const {name}: f32 = {:?};", value as f32);
+            if let Some(below_name) = below_name {
+                let _ = writeln!(code, "const _: () = assert!({below_name} < {name});");
+            }
+            below = Some((key, value));
+            below_name = Some(name);
+        }
+    }
+    Ok(code)
+}
+
+fn dashboard(config: &mut Config<'_>) -> Result<String, String> {
+    let mut code = String::from(GENERATED);
+
+    let title = match config.remove("title") {
+        Some(entry) => entry.text("title", MAX_TITLE_LEN)?,
+        None => DEFAULT_TITLE.to_string(),
+    };
+    let _ = writeln!(code, "/// Title of the loading screen.");
+    let _ = writeln!(code, "pub const LOADING_TITLE: &str = {title:?};\n");
+
+    let lines = match config.remove("boot.lines") {
+        Some(entry) => boot_lines(&entry)?,
+        None => {
+            DEFAULT_BOOT_LINES
+                .iter()
+                .map(|&(line, ms)| (line.to_string(), ms))
+                .collect()
+        }
+    };
+    let _ = writeln!(code, "/// Init log lines and how long each is shown, in milliseconds.");
+    let _ = writeln!(code, "pub const INIT_MESSAGES: &[(&str, u64)] = &[");
+    for (line, ms) in &lines {
+        let _ = writeln!(code, "    ({line:?}, {ms}),");
+    }
+    let _ = writeln!(code, "];\n");
+
+    // Checked against `LAYOUTS` where the index is resolved
+    let layout = match config.remove("layout.default") {
+        Some(entry) => entry.text("layout.default", usize::MAX)?,
+        None => DEFAULT_LAYOUT.to_string(),
+    };
+    let _ = writeln!(code, "/// Name of the layout a fresh settings record starts with.");
+    let _ = writeln!(code, "pub const DEFAULT_LAYOUT_NAME: &str = {layout:?};\n");
+
+    let _ = writeln!(code, "/// Display units a fresh settings record starts with.");
+    let _ = writeln!(code, "pub const DEFAULT_UNITS: UnitPrefs = UnitPrefs {{");
+    for unit in UNITS {
+        let key = format!("units.{}", unit.key);
+        let variant = match config.remove(&key) {
+            Some(entry) => {
+                let value = entry.text(&key, usize::MAX)?;
+                let Some(&(_, variant)) = unit.options.iter().find(|(name, _)| name.eq_ignore_ascii_case(&value))
+                else {
+                    let names: Vec<_> = unit.options.iter().map(|(name, _)| *name).collect();
+                    return Err(format!(
+                        "line {}: `{key}` = {value:?} is not one of {}",
+                        entry.line,
+                        names.join(", ")
+                    ));
+                };
+                variant
+            }
+            None => unit.options[0].1,
+        };
+        let _ = writeln!(code, "    {}: {}::{variant},", unit.key, unit.ty);
+    }
+    let _ = writeln!(code, "}};");
+
+    Ok(code)
+}
+
+fn boot_lines(entry: &Entry<'_>) -> Result<Vec<(String, u64)>, String> {
+    let invalid = || {
+        format!(
+            "line {}: `boot.lines` must be a list of [\"text\", milliseconds]",
+            entry.line
+        )
+    };
+    let DeValue::Array(items) = &entry.value else {
+        return Err(invalid());
+    };
+    if items.is_empty() {
+        return Err(format!("line {}: `boot.lines` is empty", entry.line));
+    }
+
+    let mut lines = Vec::new();
+    for item in items.iter() {
+        let DeValue::Array(pair) = item.get_ref() else {
+            return Err(invalid());
+        };
+        let [text, ms] = pair.as_ref() else {
+            return Err(invalid());
+        };
+        let (DeValue::String(text), Some(ms)) = (text.get_ref(), number(ms.get_ref())) else {
+            return Err(invalid());
+        };
+        check_text(text, MAX_BOOT_LINE_LEN).map_err(|e| format!("line {}: boot line {text:?} {e}", entry.line))?;
+        if ms.fract() != 0.0 || !(0.0..=MAX_BOOT_LINE_MS as f64).contains(&ms) {
+            return Err(format!(
+                "line {}: boot line {text:?} needs a whole number of milliseconds up to {MAX_BOOT_LINE_MS}",
+                entry.line
+            ));
+        }
+        lines.push((text.to_string(), ms as u64));
+    }
+    Ok(lines)
+}
+
+/// Text the display fonts can draw within `max_len` characters.
+fn check_text(
+    text: &str,
+    max_len: usize,
+) -> Result<(), String> {
+    if text.is_empty() {
+        Err("is empty".to_string())
+    } else if !text.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        Err("has characters outside printable ASCII".to_string())
+    } else if text.len() > max_len {
+        Err(format!("is longer than {max_len} characters"))
+    } else {
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// dashboard.toml is parsed with the `toml` crate, a build dependency only, and
+// flattened to `table.key` entries that keep their line for error messages.
+// ---------------------------------------------------------------------------
+
+/// Values keyed by `table.key`.
+type Config<'i> = BTreeMap<String, Entry<'i>>;
+
+struct Entry<'i> {
+    value: DeValue<'i>,
+    line: usize,
+}
+
+impl Entry<'_> {
+    fn number(
+        &self,
+        key: &str,
+    ) -> Result<f64, String> {
+        number(&self.value).ok_or_else(|| format!("line {}: `{key}` must be a number", self.line))
+    }
+
+    fn text(
+        &self,
+        key: &str,
+        max_len: usize,
+    ) -> Result<String, String> {
+        let DeValue::String(text) = &self.value else {
+            return Err(format!("line {}: `{key}` must be a string", self.line));
+        };
+        check_text(text, max_len).map_err(|e| format!("line {}: `{key}` {e}", self.line))?;
+        Ok(text.to_string())
+    }
+}
+
+/// A finite integer or float.
+fn number(value: &DeValue<'_>) -> Option<f64> {
+    match value {
+        DeValue::Integer(int) => {
+            i64::from_str_radix(int.as_str(), int.radix())
+                .ok()
+                .map(|int| int as f64)
+        }
+        DeValue::Float(float) => float.as_str().parse::<f64>().ok().filter(|float| float.is_finite()),
+        _ => None,
+    }
+}
+
+fn parse(text: &str) -> Result<Config<'_>, String> {
+    let table = DeTable::parse(text).map_err(|e| e.to_string())?;
+    let mut config = Config::new();
+    flatten(text, "", table.into_inner(), &mut config);
+    Ok(config)
+}
+
+/// Add the values of `table` to `config` as `{prefix}key`, descending into sub-tables.
+fn flatten<'i>(
+    text: &str,
+    prefix: &str,
+    table: DeTable<'i>,
+    config: &mut Config<'i>,
+) {
+    for (key, value) in table {
+        let key = format!("{prefix}{}", key.get_ref());
+        let line = text[..value.span().start].matches('\n').count() + 1;
+        match value.into_inner() {
+            DeValue::Table(table) => flatten(text, &format!("{key}."), table, config),
+            value => {
+                config.insert(key, Entry { value, line });
+            }
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! Build-time settings from `dashboard.toml`, generated by `build.rs`: loading screen
+//! text, default layout and default units.
+
+use crate::units::{Fuel, MixtureUnit, PressureUnit, TempUnit, UnitPrefs};
+
+include!(concat!(env!("OUT_DIR"), "/dashboard.rs"));
@@ -2,16 +2,8 @@ pub const SCREEN_WIDTH: u32 = 320;
 
 pub const SCREEN_HEIGHT: u32 = 240;
 
-pub const HISTORY_SIZE: usize = 50;
-
-pub const TREND_THRESHOLD: f32 = 0.5;
-
 pub const HEADER_HEIGHT: u32 = 26;
 
-pub const COL_WIDTH: u32 = SCREEN_WIDTH / 4;
-
-pub const ROW_HEIGHT: u32 = (SCREEN_HEIGHT - HEADER_HEIGHT) / 2;
-
 pub const CENTER_X: i32 = (SCREEN_WIDTH / 2) as i32;
 
 pub const CENTER_Y: i32 = (SCREEN_HEIGHT / 2) as i32;
@@ -1,30 +1,19 @@
+pub mod dashboard;
 pub mod layout;
 pub mod sensors;
 
-pub use layout::{
-    CENTER_X,
-    CENTER_Y,
-    COL_WIDTH,
-    HEADER_HEIGHT,
-    HISTORY_SIZE,
-    ROW_HEIGHT,
-    SCREEN_HEIGHT,
-    SCREEN_WIDTH,
-    TREND_THRESHOLD,
-};
+pub use layout::{CENTER_X, CENTER_Y, HEADER_HEIGHT, SCREEN_HEIGHT, SCREEN_WIDTH};
 pub use sensors::{
-    AFR_LEAN_CRITICAL,
-    AFR_OPTIMAL_MAX,
-    AFR_RICH,
-    AFR_RICH_AF,
-    AFR_STOICH,
-    BAR_TO_PSI,
     BATT_CRITICAL,
     BATT_WARNING,
+    BOOST_CRITICAL,
     BOOST_EASTER_EGG_BAR,
-    BOOST_EASTER_EGG_PSI,
+    BOOST_HIGH,
     COOLANT_COLD_MAX,
     COOLANT_CRITICAL,
+    DSG_CRITICAL,
+    DSG_ELEVATED,
+    DSG_HIGH,
     EGT_COLD_MAX,
     EGT_CRITICAL,
     EGT_DANGER_MANIFOLD,
@@ -35,8 +24,14 @@ pub use sensors::{
     IAT_EXTREME_COLD,
     IAT_HOT,
     IAT_WARM,
-    OIL_DSG_CRITICAL,
-    OIL_DSG_ELEVATED,
-    OIL_DSG_HIGH,
+    LAMBDA_LEAN_CRITICAL,
+    LAMBDA_OPTIMAL_MAX,
+    LAMBDA_RICH,
+    LAMBDA_RICH_AF,
+    OIL_CRITICAL,
+    OIL_ELEVATED,
+    OIL_HIGH,
     OIL_LOW_TEMP,
+    RPM_REDLINE,
+    RPM_SHIFT,
 };
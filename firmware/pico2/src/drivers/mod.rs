@@ -1,5 +1,17 @@
 mod display;
+mod settings_flash;
 mod st7789;
 
 pub use display::{display_spi_config, get_actual_spi_freq};
-pub use st7789::{DoubleBuffer, FRAMEBUFFER_A, FRAMEBUFFER_B, St7789Flusher, St7789Renderer};
+pub use settings_flash::SettingsStore;
+pub use st7789::{
+    DisplayCsPin,
+    DisplayDcPin,
+    DisplayDma,
+    DoubleBuffer,
+    FRAMEBUFFER_A,
+    FRAMEBUFFER_B,
+    St7789Flusher,
+    St7789Renderer,
+    flush_buffer_blocking,
+};
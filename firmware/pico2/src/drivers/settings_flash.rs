@@ -0,0 +1,111 @@
+use embassy_rp::Peri;
+use embassy_rp::flash::{Blocking, ERASE_SIZE, Flash};
+use embassy_rp::peripherals::FLASH;
+
+use crate::limits::{THRESHOLDS_RECORD_LEN, Thresholds};
+use crate::log_info;
+use crate::state::{SESSION_RECORD_LEN, SETTINGS_RECORD_LEN, SessionRecord, Settings};
+
+/// Pico 2 flash size. `memory.x` only gives the image 2 MiB, so the
+/// settings sector at the very end can never overlap the firmware.
+const FLASH_SIZE: usize = 4 * 1024 * 1024;
+
+/// Offset of the settings sector (last 4 KiB erase sector).
+const SETTINGS_OFFSET: u32 = (FLASH_SIZE - ERASE_SIZE) as u32;
+
+/// Offset of the session sector, just below the settings.
+const SESSION_OFFSET: u32 = SETTINGS_OFFSET - ERASE_SIZE as u32;
+
+/// Offset of the thresholds sector, below the session.
+const THRESHOLDS_OFFSET: u32 = SESSION_OFFSET - ERASE_SIZE as u32;
+
+const _: () = assert!(SESSION_RECORD_LEN <= ERASE_SIZE);
+const _: () = assert!(THRESHOLDS_RECORD_LEN <= ERASE_SIZE);
+
+/// Stores [`Settings`] in the last flash sector, the last [`SessionRecord`] in the one
+/// before and the edited [`Thresholds`] below that.
+///
+/// Erase and program are blocking and run from RAM with Core 1 paused, stalling
+/// the render loop for tens of milliseconds, so only save on an explicit change or,
+/// for the session, at the sparse points `SessionAutosave` picks.
+pub struct SettingsStore {
+    flash: Flash<'static, FLASH, Blocking, FLASH_SIZE>,
+}
+
+impl SettingsStore {
+    pub fn new(flash: Peri<'static, FLASH>) -> Self {
+        Self {
+            flash: Flash::new_blocking(flash),
+        }
+    }
+
+    /// Load saved settings, or defaults if the sector is erased or corrupt.
+    pub fn load(&mut self) -> Settings {
+        let mut bytes = [0u8; SETTINGS_RECORD_LEN];
+        if self.flash.blocking_read(SETTINGS_OFFSET, &mut bytes).is_err() {
+            log_info!("Settings read failed");
+            return Settings::new();
+        }
+        Settings::from_bytes(&bytes).unwrap_or_else(|| {
+            log_info!("No saved settings, defaults");
+            Settings::new()
+        })
+    }
+
+    pub fn save(
+        &mut self,
+        settings: &Settings,
+    ) {
+        if self.write_sector(SETTINGS_OFFSET, &settings.to_bytes()).is_err() {
+            log_info!("Settings save failed");
+        }
+    }
+
+    /// Load the last saved session, if there is a valid one. Read at boot, this is the
+    /// last session of the previous power cycle.
+    pub fn load_session(&mut self) -> Option<SessionRecord> {
+        let mut bytes = [0u8; SESSION_RECORD_LEN];
+        self.flash.blocking_read(SESSION_OFFSET, &mut bytes).ok()?;
+        SessionRecord::from_bytes(&bytes)
+    }
+
+    /// Replace the saved session with `record`.
+    pub fn save_session(
+        &mut self,
+        record: &SessionRecord,
+    ) {
+        if self.write_sector(SESSION_OFFSET, &record.to_bytes()).is_err() {
+            log_info!("Session save failed");
+        }
+    }
+
+    /// Load saved limits; `None` if the sector is erased, corrupt or holds a set that
+    /// does not validate, so the caller falls back to the vehicle profile's.
+    pub fn load_thresholds(&mut self) -> Option<Thresholds> {
+        let mut bytes = [0u8; THRESHOLDS_RECORD_LEN];
+        if self.flash.blocking_read(THRESHOLDS_OFFSET, &mut bytes).is_err() {
+            log_info!("Thresholds read failed");
+            return None;
+        }
+        Thresholds::from_bytes(&bytes)
+    }
+
+    pub fn save_thresholds(
+        &mut self,
+        thresholds: &Thresholds,
+    ) {
+        if self.write_sector(THRESHOLDS_OFFSET, &thresholds.to_bytes()).is_err() {
+            log_info!("Thresholds save failed");
+        }
+    }
+
+    fn write_sector(
+        &mut self,
+        offset: u32,
+        bytes: &[u8],
+    ) -> Result<(), embassy_rp::flash::Error> {
+        self.flash
+            .blocking_erase(offset, offset + ERASE_SIZE as u32)
+            .and_then(|()| self.flash.blocking_write(offset, bytes))
+    }
+}
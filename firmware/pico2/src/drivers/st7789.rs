@@ -1,11 +1,19 @@
-use embassy_rp::gpio::Output;
-use embassy_rp::peripherals::SPI0;
+use embassy_rp::dma::Channel;
+use embassy_rp::gpio::{Output, Pin};
+use embassy_rp::peripherals::{DMA_CH0, PIN_16, PIN_17, SPI0};
 use embassy_rp::spi::{Async, Spi};
 use embassy_time::Timer;
 use embedded_graphics::pixelcolor::Rgb565;
 use embedded_graphics::pixelcolor::raw::RawU16;
 use embedded_graphics::prelude::*;
 
+/// Display wiring: the D/C line, the chip select and the DMA channel feeding SPI0.
+/// `main` takes its peripherals as these types and `flush_buffer_blocking` reads the
+/// pin and channel numbers from them, so the two cannot disagree.
+pub type DisplayDcPin = PIN_16;
+pub type DisplayCsPin = PIN_17;
+pub type DisplayDma = DMA_CH0;
+
 pub const WIDTH: usize = 320;
 pub const HEIGHT: usize = 240;
 const BUFFER_SIZE: usize = WIDTH * HEIGHT * 2;
@@ -158,6 +166,61 @@ impl<'d> St7789Flusher<'d> {
     }
 }
 
+// SPI0 is fixed by the `St7789Flusher` type, so only the pins and DMA channel need the consts above
+const SPI0_BASE: u32 = 0x4008_0000;
+const SSPDR: *mut u32 = (SPI0_BASE + 0x08) as *mut u32;
+const SSPSR: *const u32 = (SPI0_BASE + 0x0C) as *const u32;
+const SSPDMACR: *mut u32 = (SPI0_BASE + 0x24) as *mut u32;
+const SSPSR_TNF: u32 = 1 << 1;
+const SSPSR_BSY: u32 = 1 << 4;
+
+const DMA_CHAN_ABORT: *mut u32 = 0x5000_0464 as *mut u32;
+
+const SIO_GPIO_OUT_SET: *mut u32 = 0xD000_0018 as *mut u32;
+const SIO_GPIO_OUT_CLR: *mut u32 = 0xD000_0020 as *mut u32;
+
+/// Push a full framebuffer to the panel by polling the SPI0 and SIO registers directly.
+///
+/// Used from the panic/HardFault path, where the executor, the flush task and the
+/// `St7789Flusher` (owned by that task) can no longer be relied on. Any in-flight
+/// display DMA transfer is aborted first so the FIFO is ours.
+///
+/// # Safety
+/// Must only be called once nothing else will touch SPI0, the display DMA channel or
+/// the DC/CS pins again (interrupts disabled, other core parked or faulted).
+pub unsafe fn flush_buffer_blocking(buffer: &[u8]) {
+    unsafe {
+        use core::ptr::{read_volatile, write_volatile};
+
+        let dc = DisplayDcPin::steal().pin();
+        let cs = DisplayCsPin::steal().pin();
+        let dma = DisplayDma::steal().number();
+
+        write_volatile(DMA_CHAN_ABORT, 1 << dma);
+        while read_volatile(DMA_CHAN_ABORT) & (1 << dma) != 0 {}
+        write_volatile(SSPDMACR, 0);
+        while read_volatile(SSPSR) & SSPSR_BSY != 0 {}
+
+        let write_byte = |byte: u8| {
+            while read_volatile(SSPSR) & SSPSR_TNF == 0 {}
+            write_volatile(SSPDR, u32::from(byte));
+        };
+
+        write_volatile(SIO_GPIO_OUT_CLR, 1 << cs);
+        write_volatile(SIO_GPIO_OUT_CLR, 1 << dc);
+        write_byte(RAMWR);
+        while read_volatile(SSPSR) & SSPSR_BSY != 0 {}
+        write_volatile(SIO_GPIO_OUT_SET, 1 << dc);
+
+        for &byte in buffer {
+            write_byte(byte);
+        }
+
+        while read_volatile(SSPSR) & SSPSR_BSY != 0 {}
+        write_volatile(SIO_GPIO_OUT_SET, 1 << cs);
+    }
+}
+
 pub struct St7789Renderer<'a> {
     framebuffer: &'a mut [u8],
 }
@@ -0,0 +1,183 @@
+//! Panic and HardFault handlers that paint a fault screen before halting.
+//!
+//! Both paths run with interrupts disabled and without the executor, so the
+//! screen is rendered with `St7789Renderer` into a framebuffer and pushed to the
+//! panel with a register-level blocking SPI write instead of the async flush task.
+//! If the watchdog is running it is stretched to its maximum period, so the screen
+//! stays up for ~16 s before the board resets with the fault recorded as the culprit.
+//!
+//! Before drawing, the faulting core rings a SIO doorbell that parks the other core,
+//! so the main loop (core 0) cannot keep rendering into the framebuffer or restart
+//! the display DMA while the fault screen goes out.
+
+use core::fmt::Write;
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use cortex_m_rt::{ExceptionFrame, exception};
+use embassy_rp::interrupt;
+use embassy_rp::interrupt::{InterruptExt, Priority};
+use heapless::String;
+
+use crate::drivers::{FRAMEBUFFER_A, St7789Renderer, flush_buffer_blocking};
+use crate::screens::{FaultReport, draw_fault_screen};
+use crate::tasks::watchdog::{self, CULPRIT_HARDFAULT, CULPRIT_PANIC};
+
+/// Set once a fault handler has claimed the display.
+/// The flush task checks this so it never starts another DMA transfer over the fault screen.
+pub static FAULT_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set by the core that is not drawing the fault screen once it has parked.
+static OTHER_CORE_PARKED: AtomicBool = AtomicBool::new(false);
+
+const FOOTER_HALTED: &str = "Power cycle to restart";
+
+const FOOTER_REBOOTING: &str = "Watchdog reset in ~16 s";
+
+const SCB_CFSR: *const u32 = 0xE000_ED28 as *const u32;
+const SCB_HFSR: *const u32 = 0xE000_ED2C as *const u32;
+const SCB_MMFAR: *const u32 = 0xE000_ED34 as *const u32;
+const SCB_BFAR: *const u32 = 0xE000_ED38 as *const u32;
+
+const SIO_DOORBELL_OUT_SET: *mut u32 = 0xD000_0180 as *mut u32;
+const SIO_DOORBELL_IN_CLR: *mut u32 = 0xD000_018C as *mut u32;
+const FAULT_DOORBELL: u32 = 1 << 0;
+
+/// How long the faulting core waits for the other one to park. A core with interrupts
+/// masked (inside a critical section) parks late; one that has faulted itself never does.
+const PARK_TIMEOUT_SPINS: u32 = 1_000_000;
+
+/// Let a fault on the other core park this one. Call once on each core at startup;
+/// the NVIC is per core.
+pub fn enable_fault_doorbell() {
+    interrupt::SIO_IRQ_BELL.set_priority(Priority::P0);
+    unsafe { interrupt::SIO_IRQ_BELL.enable() };
+}
+
+/// The other core faulted: stop here, before touching the framebuffers or SPI0 again.
+#[interrupt]
+fn SIO_IRQ_BELL() {
+    disable_interrupts();
+    unsafe { core::ptr::write_volatile(SIO_DOORBELL_IN_CLR, FAULT_DOORBELL) };
+    park()
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo<'_>) -> ! {
+    disable_interrupts();
+
+    let mut message: String<256> = String::new();
+    let _ = write!(message, "{}", info.message());
+
+    let mut location: String<64> = String::new();
+    if let Some(loc) = info.location() {
+        let _ = write!(location, "{}:{}", loc.file(), loc.line());
+    }
+
+    defmt::error!("PANIC: {} at {}", message.as_str(), location.as_str());
+
+    show_fault(
+        FaultReport {
+            kind: "PANIC",
+            message: &message,
+            location: &location,
+            uptime_ms: embassy_time::Instant::now().as_millis(),
+            registers: &[],
+            footer: FOOTER_HALTED,
+        },
+        CULPRIT_PANIC,
+    )
+}
+
+#[exception]
+unsafe fn HardFault(ef: &ExceptionFrame) -> ! {
+    disable_interrupts();
+
+    let (cfsr, hfsr, mmfar, bfar) = unsafe {
+        use core::ptr::read_volatile;
+        (
+            read_volatile(SCB_CFSR),
+            read_volatile(SCB_HFSR),
+            read_volatile(SCB_MMFAR),
+            read_volatile(SCB_BFAR),
+        )
+    };
+
+    defmt::error!(
+        "HARDFAULT pc={=u32:#x} lr={=u32:#x} cfsr={=u32:#x} hfsr={=u32:#x}",
+        ef.pc(),
+        ef.lr(),
+        cfsr,
+        hfsr
+    );
+
+    let registers = [
+        ("R0", ef.r0()),
+        ("R1", ef.r1()),
+        ("R2", ef.r2()),
+        ("R3", ef.r3()),
+        ("R12", ef.r12()),
+        ("LR", ef.lr()),
+        ("PC", ef.pc()),
+        ("XPSR", ef.xpsr()),
+        ("CFSR", cfsr),
+        ("HFSR", hfsr),
+        ("MMFAR", mmfar),
+        ("BFAR", bfar),
+    ];
+
+    show_fault(
+        FaultReport {
+            kind: "HARDFAULT",
+            message: "CPU exception - register dump below",
+            location: "",
+            uptime_ms: embassy_time::Instant::now().as_millis(),
+            registers: &registers,
+            footer: FOOTER_HALTED,
+        },
+        CULPRIT_HARDFAULT,
+    )
+}
+
+#[inline(always)]
+fn disable_interrupts() { unsafe { core::arch::asm!("cpsid i", options(nomem, nostack, preserves_flags)) }; }
+
+/// Render and push the fault screen, then halt until the watchdog (if running) resets the board.
+///
+/// Only the first core to fault draws, after parking the other core; a second fault
+/// (other core, or a fault inside this path) just parks so it cannot corrupt the
+/// screen mid-transfer.
+fn show_fault(
+    mut report: FaultReport<'_>,
+    culprit: u32,
+) -> ! {
+    if !FAULT_ACTIVE.swap(true, Ordering::AcqRel) {
+        park_other_core();
+        if watchdog::hold_for_fault(culprit) {
+            report.footer = FOOTER_REBOOTING;
+        }
+        let buffer = unsafe { &mut *core::ptr::addr_of_mut!(FRAMEBUFFER_A) };
+        draw_fault_screen(&mut St7789Renderer::new(buffer), &report);
+        unsafe { flush_buffer_blocking(buffer) };
+    }
+
+    park()
+}
+
+/// Ring the other core's doorbell and wait (bounded) until it reports parked.
+fn park_other_core() {
+    unsafe { core::ptr::write_volatile(SIO_DOORBELL_OUT_SET, FAULT_DOORBELL) };
+    for _ in 0..PARK_TIMEOUT_SPINS {
+        if OTHER_CORE_PARKED.load(Ordering::Acquire) {
+            return;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+fn park() -> ! {
+    OTHER_CORE_PARKED.store(true, Ordering::Release);
+    loop {
+        core::hint::spin_loop();
+    }
+}
@@ -1,4 +1,4 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_precision_loss)]
 #![allow(clippy::cast_possible_wrap)]
@@ -8,6 +8,8 @@ pub mod config;
 
 pub mod render;
 
+pub mod units;
+
 pub use config::sensors as thresholds;
 
 mod profiling {
@@ -16,14 +18,54 @@ mod profiling {
 }
 
 mod state {
+    pub mod alerts;
+    pub mod classifier;
+    pub mod filter;
+    pub mod history;
+    pub mod input;
+    pub mod layouts;
+    pub mod limits;
+    pub mod menu;
+    pub mod overlay;
     pub mod pages;
+    pub mod plausibility;
+    pub mod profiles;
+    pub mod sensor_id;
     pub mod sensor_state;
+    pub mod session;
+    pub mod settings;
+    #[cfg(test)]
+    pub mod test_clock;
+    pub mod trend;
+    pub mod zones;
 }
 
 mod ui {
     pub mod colors;
+    pub mod theme;
 }
 
 pub use profiling::{cpu_cycles, memory};
-pub use state::{pages, sensor_state};
-pub use ui::colors;
+#[cfg(test)]
+use state::test_clock;
+pub use state::{
+    alerts,
+    classifier,
+    filter,
+    history,
+    input,
+    layouts,
+    limits,
+    menu,
+    overlay,
+    pages,
+    plausibility,
+    profiles,
+    sensor_id,
+    sensor_state,
+    session,
+    settings,
+    trend,
+    zones,
+};
+pub use ui::{colors, theme};
@@ -6,6 +6,7 @@
 #![allow(clippy::cast_sign_loss)]
 
 mod drivers;
+mod fault;
 mod peripherals;
 mod profiling;
 mod screens;
@@ -20,81 +21,103 @@ mod config {
 mod render {
     pub use dashboard_pico2::render::*;
 }
+mod limits {
+    pub use dashboard_pico2::limits::*;
+}
 mod thresholds {
     pub use dashboard_pico2::thresholds::*;
 }
+mod units {
+    pub use dashboard_pico2::units::*;
+}
 
 use core::sync::atomic::Ordering;
 
 use defmt_rtt as _;
 use embassy_executor::{Executor, Spawner};
-use embassy_rp::bind_interrupts;
 use embassy_rp::dma::InterruptHandler as DmaInterruptHandler;
 use embassy_rp::gpio::{Input, Level, Output, Pull};
 use embassy_rp::i2c::{self, InterruptHandler as I2cInterruptHandler};
 use embassy_rp::pwm::{Config as PwmConfig, Pwm};
 use embassy_rp::spi::Spi;
+use embassy_rp::watchdog::Watchdog;
+use embassy_rp::{Peri, bind_interrupts};
 use embassy_time::{Duration, Instant};
 use embedded_graphics::prelude::*;
-use panic_probe as _;
 use static_cell::StaticCell;
 
-use crate::config::{COL_WIDTH, HEADER_HEIGHT, ROW_HEIGHT};
-use crate::drivers::{DoubleBuffer, St7789Flusher, St7789Renderer, display_spi_config, get_actual_spi_freq};
-use crate::peripherals::{ENCODER_BUTTON, ENCODER_DELTA, encoder_task};
+use crate::drivers::{
+    DisplayCsPin,
+    DisplayDcPin,
+    DisplayDma,
+    DoubleBuffer,
+    SettingsStore,
+    St7789Flusher,
+    St7789Renderer,
+    display_spi_config,
+    get_actual_spi_freq,
+};
+use crate::peripherals::{ENCODER_DELTA, ENCODER_SWITCH, encoder_task};
 use crate::profiling as cpu_profiling;
-use crate::render::{FpsMode, RenderState, cell_idx};
-use crate::screens::{ProfilingData, clear_framebuffers, draw_logs_page, draw_profiling_page, run_boot_sequence};
-use crate::state::{ButtonState, Page, Popup, SensorState, process_buttons};
+use crate::render::{FpsMode, RenderState};
+use crate::screens::{
+    ProfilingData,
+    clear_framebuffers,
+    draw_focus_page,
+    draw_graph_page,
+    draw_logs_page,
+    draw_profiling_page,
+    draw_settings_page,
+    draw_stats_page,
+    run_boot_sequence,
+};
+use crate::state::{
+    Action,
+    AlertManager,
+    Buttons,
+    DEFAULT_KEYMAP,
+    Fault,
+    GraphView,
+    InputEvent,
+    LAYOUTS,
+    MAX_TRACES,
+    MenuAction,
+    MenuInput,
+    MenuState,
+    OverlayStack,
+    PROFILES,
+    Page,
+    Popup,
+    SensorBank,
+    SensorId,
+    SessionAutosave,
+    Setting,
+    Zoom,
+    descriptor,
+    profiles,
+    round_eta_s,
+};
 use crate::tasks::{
     BUFFER_SWAPS,
     BUFFER_WAITS,
     CORE1_STACK,
     DEMO_VALUES,
+    DEMO_VIN,
     EXECUTOR_CORE1,
     FLUSH_BUFFER_IDX,
     FLUSH_DONE,
     FLUSH_SIGNAL,
     LAST_FLUSH_TIME_US,
+    SupervisedTask,
     demo_values_task,
     display_flush_task,
     fill_core1_stack_sentinel,
+    watchdog,
+    watchdog_task,
 };
-use crate::thresholds::{
-    AFR_LEAN_CRITICAL,
-    AFR_OPTIMAL_MAX,
-    AFR_RICH,
-    AFR_RICH_AF,
-    BATT_CRITICAL,
-    BATT_WARNING,
-    BOOST_EASTER_EGG_BAR,
-    BOOST_EASTER_EGG_PSI,
-    EGT_DANGER_MANIFOLD,
-};
-use crate::ui::{BLACK, BLUE, ColorTransition, DARK_TEAL, GREEN, ORANGE, RED};
-use crate::widgets::{
-    SensorDisplayData,
-    draw_afr_cell,
-    draw_batt_cell,
-    draw_boost_cell,
-    draw_boost_unit_popup,
-    draw_brightness_popup,
-    draw_danger_manifold_popup,
-    draw_dividers,
-    draw_fps_toggle_popup,
-    draw_header,
-    draw_reset_popup,
-    draw_temp_cell,
-    is_critical_egt,
-    is_critical_iat,
-    is_critical_oil_dsg,
-    is_critical_water,
-    is_low_temp_oil,
-    temp_color_egt,
-    temp_color_iat,
-    temp_color_oil_dsg,
-    temp_color_water,
-};
+use crate::ui::{ColorTransition, theme};
+use crate::units::Quantity;
+use crate::widgets::{PopupContext, draw_dividers, draw_header, draw_selection_frame, draw_sensor_cell};
 
 #[unsafe(link_section = ".bi_entries")]
 #[used]
@@ -127,6 +150,20 @@ compile_error!(
      cpu290-spi72-1v30, cpu300-spi75-1v30"
 );
 
+/// How long the encoder cell selection stays on the dashboard after the last rotation.
+const CELL_SELECTION_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// PWM compare value for a brightness percentage.
+/// Remaps 1-100% to 450-1000 compare (LED needs ~45% duty minimum).
+const fn backlight_compare(percent: u32) -> u16 {
+    if percent == 0 {
+        0
+    } else {
+        let compare = 450 + percent * 550 / 100;
+        if compare > 1000 { 1000 } else { compare as u16 }
+    }
+}
+
 fn read_vreg_voltage_mv() -> u32 {
     const VREG: *const u32 = 0x4010_000C as *const u32;
     let vreg_val = unsafe { core::ptr::read_volatile(VREG) };
@@ -197,6 +234,7 @@ fn core1_main(
     stack_base: u32,
 ) -> ! {
     cpu_profiling::init(cpu_freq_hz);
+    fault::enable_fault_doorbell();
     let executor = EXECUTOR_CORE1.init(Executor::new());
     executor.run(|spawner| {
         spawner.spawn(demo_values_task(animation_start, stack_base).unwrap());
@@ -204,7 +242,7 @@ fn core1_main(
 }
 
 bind_interrupts!(struct Irqs {
-    DMA_IRQ_0 => DmaInterruptHandler<embassy_rp::peripherals::DMA_CH0>;
+    DMA_IRQ_0 => DmaInterruptHandler<DisplayDma>;
     I2C0_IRQ => I2cInterruptHandler<embassy_rp::peripherals::I2C0>;
 });
 
@@ -262,6 +300,17 @@ async fn main(spawner: Spawner) {
 
         embassy_rp::init(config)
     };
+    fault::enable_fault_doorbell();
+
+    let mut hw_watchdog = Watchdog::new(p.WATCHDOG);
+    watchdog::log_reset_reason(&mut hw_watchdog);
+
+    let mut settings_store = SettingsStore::new(p.FLASH);
+    let mut settings = settings_store.load();
+    let mut profile = &PROFILES[profiles::resolve(settings.vehicle, Some(DEMO_VIN))];
+    log_info!("Vehicle: {}", profile.name);
+    limits::set_active(&settings_store.load_thresholds().unwrap_or_else(|| profile.thresholds()));
+    log_info!("Layout: {}", LAYOUTS[settings.layout_index()].name);
 
     let cpu_freq_hz = requested_cpu_mhz() * 1_000_000;
 
@@ -280,8 +329,12 @@ async fn main(spawner: Spawner) {
     let mut _led_g = Output::new(p.PIN_27, Level::High);
     let mut led_b = Output::new(p.PIN_28, Level::High);
 
-    let cs = Output::new(p.PIN_17, Level::High);
-    let dc = Output::new(p.PIN_16, Level::Low);
+    // Typed as the wiring the fault path flushes through, so a mismatch does not compile
+    let cs_pin: Peri<'static, DisplayCsPin> = p.PIN_17;
+    let dc_pin: Peri<'static, DisplayDcPin> = p.PIN_16;
+    let display_dma: Peri<'static, DisplayDma> = p.DMA_CH0;
+    let cs = Output::new(cs_pin, Level::High);
+    let dc = Output::new(dc_pin, Level::Low);
 
     // Backlight via PWM for brightness control (GP20 = PWM slice 2, channel A)
     let mut pwm_config = PwmConfig::default();
@@ -289,7 +342,7 @@ async fn main(spawner: Spawner) {
     pwm_config.compare_a = 1000; // 100% duty — fully on during boot
     let mut backlight_pwm = Pwm::new_output_a(p.PWM_SLICE2, p.PIN_20, pwm_config.clone());
 
-    let spi = Spi::new_txonly(p.SPI0, p.PIN_18, p.PIN_19, p.DMA_CH0, Irqs, display_spi_config());
+    let spi = Spi::new_txonly(p.SPI0, p.PIN_18, p.PIN_19, display_dma, Irqs, display_spi_config());
 
     let mut flusher = St7789Flusher::new(spi, dc, cs);
     flusher.init().await;
@@ -300,7 +353,7 @@ async fn main(spawner: Spawner) {
 
     clear_framebuffers(&mut flusher, &mut double_buffer).await;
 
-    run_boot_sequence(&mut flusher, &mut double_buffer).await;
+    run_boot_sequence(&mut flusher, &mut double_buffer, settings.boot_screen, profile).await;
 
     static FLUSHER: StaticCell<St7789Flusher<'static>> = StaticCell::new();
     let flusher: &'static mut St7789Flusher<'static> = FLUSHER.init(flusher);
@@ -322,19 +375,14 @@ async fn main(spawner: Spawner) {
     let btn_x = Input::new(p.PIN_14, Pull::Up);
     let btn_y = Input::new(p.PIN_15, Pull::Up);
 
-    let mut btn_a_state = ButtonState::new();
-    let mut btn_b_state = ButtonState::new();
-    let mut btn_x_state = ButtonState::new();
-    let mut btn_y_state = ButtonState::new();
+    let mut buttons = Buttons::default();
 
     log_info!("Buttons initialized");
 
     let mut current_page = Page::Dashboard;
     let mut clear_frames_remaining: u8 = 2;
     let mut fps_mode = FpsMode::Off;
-    let mut show_boost_psi = false;
-    let mut active_popup: Option<Popup> = None;
-    let mut prev_egt_danger_active = false;
+    let mut overlays: OverlayStack<Popup> = OverlayStack::new();
     let mut reset_requested = false;
 
     let mut render_state = RenderState::new();
@@ -357,117 +405,171 @@ async fn main(spawner: Spawner) {
 
     let mut flush_in_progress = false;
 
-    // Brightness state (PWM backlight, encoder-controlled)
-    let mut brightness_percent: u32 = 100;
-    let mut saved_brightness: u32 = 100; // for toggle on/off
-    let mut backlight_off_pending = false; // deferred off: show popup first, then cut PWM
+    // Brightness state (PWM backlight, encoder-controlled), starting at the saved boot level
+    let mut brightness_percent = u32::from(settings.brightness);
+    let mut saved_brightness = brightness_percent; // for toggle on/off
+    pwm_config.compare_a = backlight_compare(brightness_percent);
+    backlight_pwm.set_config(&pwm_config);
+    let mut lit_percent = brightness_percent; // level the PWM is at
     let mut log_scroll_offset: i32 = 0;
     let mut prev_log_count: usize = 0; // for anchoring scroll when new logs arrive
 
-    let mut boost = 0.5f32;
-    let mut oil_temp = 60.0f32;
-    let mut water_temp = 88.0f32;
-    let mut dsg_temp = 75.0f32;
-    let mut iat_temp = 30.0f32;
-    let mut egt_temp = 200.0f32;
-    let mut batt_voltage = 12.0f32;
-    let mut afr = 14.0f32;
-
-    let mut oil_state = SensorState::new();
-    let mut water_state = SensorState::new();
-    let mut dsg_state = SensorState::new();
-    let mut iat_state = SensorState::new();
-    let mut egt_state = SensorState::new();
-    let mut batt_state = SensorState::new();
-    let mut afr_state = SensorState::new();
-
-    let mut boost_max = 0.0f32;
-    let mut oil_max = 0.0f32;
-    let mut water_max = 0.0f32;
-    let mut dsg_max = 0.0f32;
-    let mut iat_max = f32::MIN;
-    let mut egt_max = 0.0f32;
-    let mut batt_min = f32::MAX;
-    let mut batt_max = 0.0f32;
+    // Encoder cell selection on the dashboard and the sensor shown on the focus page
+    let mut selected_slot: usize = 0;
+    let mut selection_shown_at: Option<Instant> = None;
+    let mut focus_sensor = SensorId::Boost;
+    let mut graph_view = GraphView::new();
+    let mut focus_zoom = Zoom::OneMinute;
+    // Stats page: shown sensor, and whether it shows the last ended session
+    let mut stats_sensor = SensorId::Oil;
+    let mut stats_show_last = false;
+    let mut last_session = settings_store.load_session();
+    let mut session_autosave = SessionAutosave::new();
+    let mut menu = MenuState::new();
+
+    let mut sensors = SensorBank::new();
+    let mut alerts = AlertManager::new();
+    let mut critical_predicted = [false; SensorId::COUNT];
+    let mut sensor_faults: [Option<Fault>; SensorId::COUNT] = [None; SensorId::COUNT];
 
     log_info!("Main loop starting");
 
+    theme::set_active(settings.theme.resolve(brightness_percent, theme::active_palette()));
     let mut color_transitions = ColorTransition::new();
 
     let animation_start = Instant::now();
 
     let mut demo_receiver = DEMO_VALUES.dyn_receiver().unwrap();
 
+    spawner.spawn(watchdog_task(hw_watchdog).unwrap());
+    watchdog::register(SupervisedTask::MainLoop);
+
     loop {
         let frame_start = Instant::now();
+        watchdog::heartbeat(SupervisedTask::MainLoop);
         let frame_cycles_start = cpu_profiling::read();
 
-        let elapsed_ms = animation_start.elapsed().as_millis() as u32;
-        let blink_on = (elapsed_ms / 200).is_multiple_of(2);
+        let now_ms = animation_start.elapsed().as_millis();
+        let elapsed_ms = now_ms as u32;
+        let blink_on = !settings.critical_blink || (elapsed_ms / 200).is_multiple_of(2);
+        let page_before = current_page;
 
-        let input = process_buttons(
-            &mut btn_x_state,
-            &mut btn_y_state,
-            &mut btn_a_state,
-            &mut btn_b_state,
-            btn_x.is_low(),
-            btn_y.is_low(),
+        // Levels in `Button::ALL` order; the PIM715 buttons pull their pin low when pressed
+        let down = [
             btn_a.is_low(),
             btn_b.is_low(),
-            current_page,
-            fps_mode,
-        );
-
-        if let Some(new_mode) = input.new_fps_mode {
-            fps_mode = new_mode;
-            clear_frames_remaining = 2;
-            log_info!("FPS: {}", fps_mode.label());
-        }
-        if let Some(new_page) = input.new_page {
-            current_page = new_page;
-            clear_frames_remaining = 2;
-            // If backlight-off was deferred and popup is being cleared, apply PWM now
-            if backlight_off_pending {
-                pwm_config.compare_a = 0;
-                backlight_pwm.set_config(&pwm_config);
-                backlight_off_pending = false;
-            }
-            active_popup = None;
-            fps_sample_count = 0;
-            fps_sum = 0.0;
-            average_fps = 0.0;
-            log_info!(
-                "Page: {}",
-                match current_page {
-                    Page::Dashboard => "Dashboard",
-                    Page::Debug => "Debug",
-                    Page::Logs => "Logs",
+            btn_x.is_low(),
+            btn_y.is_low(),
+            ENCODER_SWITCH.load(Ordering::Relaxed),
+        ];
+        let events = buttons.update(now_ms, down, |button| {
+            DEFAULT_KEYMAP.binds(current_page, InputEvent::DoublePress(button))
+        });
+        let mut menu_button: Option<MenuInput> = None;
+
+        for event in events {
+            let Some(action) = DEFAULT_KEYMAP.action(current_page, event) else {
+                continue;
+            };
+            // While an alert is shown, a press acknowledges it and a long press every raised alert
+            let alert_shown = current_page == Page::Dashboard && matches!(overlays.top(), Some(Popup::Alert(_)));
+            match action {
+                Action::NextPage => current_page = current_page.toggle(),
+                Action::Home => current_page = Page::Dashboard,
+                Action::CycleFps => {
+                    fps_mode = fps_mode.next();
+                    clear_frames_remaining = 2;
+                    overlays.show(Popup::Fps(fps_mode), now_ms);
+                    log_info!("FPS: {}", fps_mode.label());
                 }
-            );
-        }
-        if input.boost_unit_toggled {
-            show_boost_psi = !show_boost_psi;
-            log_info!("Boost: {}", if show_boost_psi { "PSI" } else { "BAR" });
-        }
-        if input.reset_requested {
-            reset_requested = true;
-            log_info!("Stats reset requested");
-        }
-        if let Some(popup) = input.show_popup {
-            // If a non-brightness popup replaces a pending backlight-off, apply it now
-            if backlight_off_pending && !matches!(popup, Popup::Brightness(_, _)) {
-                pwm_config.compare_a = 0;
-                backlight_pwm.set_config(&pwm_config);
-                backlight_off_pending = false;
+                Action::CycleBoostUnit => {
+                    settings.units.pressure = settings.units.pressure.next();
+                    settings_store.save(&settings);
+                    overlays.show(Popup::BoostUnit, now_ms);
+                    log_info!("Boost: {}", settings.units.symbol(Quantity::Pressure));
+                }
+                Action::ResetStats => {
+                    reset_requested = true;
+                    overlays.show(Popup::Reset, now_ms);
+                    log_info!("Stats reset requested");
+                }
+                Action::HighlightNextTrace => graph_view.highlight_next(),
+                Action::ToggleTrace => {
+                    if !graph_view.toggle_highlighted() {
+                        log_info!("Graph: all {} traces in use", MAX_TRACES);
+                    }
+                }
+                Action::ToggleSession => {
+                    if last_session.is_some() {
+                        stats_show_last = !stats_show_last;
+                    } else {
+                        log_info!("No saved session");
+                    }
+                }
+                Action::Select | Action::OpenSettings if alert_shown => {
+                    let events = if action == Action::OpenSettings {
+                        alerts.acknowledge_all(now_ms)
+                    } else {
+                        alerts.acknowledge_top(now_ms).into_iter().collect()
+                    };
+                    for event in events {
+                        log_info!("Alert {:?}: {}", event.kind, event.rule().message);
+                    }
+                }
+                Action::OpenSettings => {
+                    menu = MenuState::new();
+                    current_page = Page::Settings;
+                }
+                Action::Select if current_page == Page::Settings => menu_button = Some(MenuInput::Press),
+                Action::Back => menu_button = Some(MenuInput::LongPress),
+                // A press on the dashboard opens the selected cell, or without one toggles the backlight
+                Action::Select if selection_shown_at.is_some() => {
+                    focus_sensor = LAYOUTS[settings.layout_index()].slot(selected_slot, profile).sensor;
+                    current_page = Page::Focus;
+                    log_info!("Focus: {}", descriptor(focus_sensor).label);
+                }
+                Action::Select | Action::ToggleBacklight => {
+                    if brightness_percent > 0 {
+                        saved_brightness = brightness_percent;
+                        brightness_percent = 0;
+                    } else {
+                        brightness_percent = saved_brightness.max(5);
+                    }
+                    overlays.show(Popup::Brightness(brightness_percent), now_ms);
+                }
+                Action::CycleZoom if current_page == Page::Graph => {
+                    graph_view.cycle_zoom();
+                    log_info!("Graph zoom: {}", graph_view.zoom.label());
+                }
+                Action::CycleZoom => {
+                    focus_zoom = focus_zoom.next();
+                    log_info!("Focus zoom: {}", focus_zoom.label());
+                }
+                Action::GoLive => graph_view.cursor_age = 0,
             }
-            active_popup = Some(popup);
         }
 
-        // --- Encoder events (rotation + button press) ---
-        let enc_delta = ENCODER_DELTA.swap(0, Ordering::Relaxed);
-        let enc_button = ENCODER_BUTTON.swap(false, Ordering::Relaxed);
+        // A turn in the frame that changes the page is dropped rather than applied to the new one
+        let enc_delta = if current_page == page_before {
+            ENCODER_DELTA.swap(0, Ordering::Relaxed)
+        } else {
+            ENCODER_DELTA.store(0, Ordering::Relaxed);
+            0
+        };
 
         match current_page {
+            Page::Dashboard => {
+                // Rotation moves the cell selection
+                if enc_delta != 0 {
+                    let slot_count = LAYOUTS[settings.layout_index()].slots.len();
+                    selected_slot = if selection_shown_at.is_some() {
+                        (selected_slot as i32 + enc_delta).rem_euclid(slot_count as i32) as usize
+                    } else {
+                        selected_slot.min(slot_count - 1)
+                    };
+                    selection_shown_at = Some(Instant::now());
+                }
+            }
             Page::Logs => {
                 // Anchor scroll position when new log entries arrive while scrolled,
                 // so the visible entries don't shift under the user.
@@ -487,153 +589,177 @@ async fn main(spawner: Spawner) {
                 if enc_delta != 0 {
                     log_scroll_offset = (log_scroll_offset - enc_delta).max(0);
                 }
-                // Button does nothing on Logs page
             }
-            _ => {
-                // Rotation adjusts brightness in 5% steps
+            Page::Graph => {
+                // Rotation moves the cursor (CW = older)
                 if enc_delta != 0 {
-                    let new_brightness = (brightness_percent as i32 + enc_delta * 5).clamp(0, 100) as u32;
-                    if new_brightness != brightness_percent {
-                        brightness_percent = new_brightness;
-                        saved_brightness = brightness_percent;
-                        backlight_off_pending = false; // cancel any pending off
-                        // Remap 1-100% to 450-1000 compare (LED needs ~45% duty minimum)
-                        pwm_config.compare_a = if brightness_percent == 0 {
-                            0
-                        } else {
-                            (450 + brightness_percent as u16 * 550 / 100).min(1000)
-                        };
-                        backlight_pwm.set_config(&pwm_config);
-                        active_popup = Some(Popup::Brightness(Instant::now(), brightness_percent));
-                        clear_frames_remaining = 2;
+                    graph_view.move_cursor(-enc_delta);
+                }
+            }
+            Page::Stats => {
+                // Rotation cycles through all sensors
+                if enc_delta != 0 {
+                    let next = (stats_sensor.index() as i32 + enc_delta).rem_euclid(SensorId::COUNT as i32);
+                    stats_sensor = SensorId::from_index(next as usize).unwrap_or(stats_sensor);
+                }
+            }
+            Page::Settings => {
+                // Rotation moves or edits, press selects or saves, long press goes back
+                let menu_inputs = [(enc_delta != 0).then_some(MenuInput::Rotate(enc_delta)), menu_button];
+                for menu_input in menu_inputs.into_iter().flatten() {
+                    match menu.handle(menu_input, &mut settings) {
+                        MenuAction::None => {}
+                        MenuAction::Changed(setting) => {
+                            if setting == Setting::Vehicle {
+                                // A new car brings its own layout and limits; earlier edits
+                                // to the limits belonged to the old one
+                                profile = &PROFILES[profiles::resolve(settings.vehicle, Some(DEMO_VIN))];
+                                settings.set(Setting::Layout, profile.layout as u8);
+                                let thresholds = profile.thresholds();
+                                limits::set_active(&thresholds);
+                                settings_store.save_thresholds(&thresholds);
+                                log_info!("Vehicle: {}", profile.name);
+                            }
+                            settings_store.save(&settings);
+                            if setting == Setting::Brightness {
+                                brightness_percent = u32::from(settings.brightness);
+                                saved_brightness = brightness_percent;
+                            }
+                            log_info!("Saved {:?}", setting);
+                        }
+                        MenuAction::LimitChanged(limit, value) => {
+                            let mut thresholds = limits::active();
+                            if thresholds.set(limit, value) {
+                                limits::set_active(&thresholds);
+                                settings_store.save_thresholds(&thresholds);
+                                log_info!("Saved {:?}", limit);
+                            }
+                        }
+                        MenuAction::Close => current_page = Page::Dashboard,
                     }
                 }
-                // Button toggles backlight on/off
-                if enc_button {
-                    if brightness_percent > 0 {
-                        // Turning off: defer PWM change until popup expires so user sees it
+            }
+            Page::Focus => {
+                // Rotation cycles through all sensors
+                if enc_delta != 0 {
+                    let next = (focus_sensor.index() as i32 + enc_delta).rem_euclid(SensorId::COUNT as i32);
+                    focus_sensor = SensorId::from_index(next as usize).unwrap_or(focus_sensor);
+                }
+            }
+            Page::Debug => {
+                // Rotation adjusts brightness by the configured step
+                if enc_delta != 0 {
+                    let step = i32::from(settings.brightness_step);
+                    let new_brightness = (brightness_percent as i32 + enc_delta * step).clamp(0, 100) as u32;
+                    if new_brightness != brightness_percent {
+                        brightness_percent = new_brightness;
                         saved_brightness = brightness_percent;
-                        brightness_percent = 0;
-                        backlight_off_pending = true;
-                        // Don't touch PWM yet — keep backlight on for the popup
-                    } else {
-                        // Turning on: apply immediately
-                        brightness_percent = saved_brightness.max(5);
-                        backlight_off_pending = false;
-                        pwm_config.compare_a = (450 + brightness_percent as u16 * 550 / 100).min(1000);
-                        backlight_pwm.set_config(&pwm_config);
+                        overlays.show(Popup::Brightness(brightness_percent), now_ms);
                     }
-                    active_popup = Some(Popup::Brightness(Instant::now(), brightness_percent));
-                    clear_frames_remaining = 2;
                 }
             }
         }
 
-        // Reset log scroll when leaving Logs page
-        if input.new_page.is_some() {
+        if let Some(shown_at) = selection_shown_at
+            && (shown_at.elapsed() >= CELL_SELECTION_TIMEOUT || current_page != Page::Dashboard)
+        {
+            selection_shown_at = None;
+            clear_frames_remaining = 2;
+        }
+
+        if current_page != page_before {
+            clear_frames_remaining = 2;
+            fps_sample_count = 0;
+            fps_sum = 0.0;
+            average_fps = 0.0;
+            // Reset log scroll when leaving Logs page
             log_scroll_offset = 0;
             prev_log_count = 0;
+            log_info!(
+                "Page: {}",
+                match current_page {
+                    Page::Dashboard => "Dashboard",
+                    Page::Graph => "Graph",
+                    Page::Stats => "Stats",
+                    Page::Debug => "Debug",
+                    Page::Logs => "Logs",
+                    Page::Settings => "Settings",
+                    Page::Focus => "Focus",
+                }
+            );
+            overlays.leave_page();
         }
+        overlays.expire(now_ms);
 
-        if let Some(ref popup) = active_popup
-            && popup.is_expired()
-        {
-            // If a backlight-off was deferred and this is the Brightness popup expiring,
-            // apply the PWM change now (don't apply on other popup types)
-            if backlight_off_pending && matches!(popup, Popup::Brightness(_, _)) {
-                pwm_config.compare_a = 0;
-                backlight_pwm.set_config(&pwm_config);
-                backlight_off_pending = false;
+        // Alerts are raised from the previous frame's readings, like the clear below expects
+        match alerts.top().filter(|_| current_page == Page::Dashboard) {
+            Some(rule) => {
+                if !overlays.any(|popup| *popup == Popup::Alert(rule)) {
+                    overlays.show(Popup::Alert(rule), now_ms);
+                }
+            }
+            None => {
+                overlays.dismiss(|popup| matches!(popup, Popup::Alert(_)));
             }
-            active_popup = None;
-            clear_frames_remaining = 2;
         }
+        render_state.update_overlay(overlays.region());
 
-        let popup_kind = if active_popup.is_some() {
-            active_popup.as_ref().map(Popup::kind)
-        } else if prev_egt_danger_active {
-            Some(3u8)
+        // "BL: OFF" keeps the backlight lit while it is shown, so the change can be read
+        let lit = if overlays.any(|popup| *popup == Popup::Brightness(0)) {
+            saved_brightness
         } else {
-            None
+            brightness_percent
         };
-        render_state.update_popup(popup_kind);
+        if lit != lit_percent {
+            lit_percent = lit;
+            pwm_config.compare_a = backlight_compare(lit_percent);
+            backlight_pwm.set_config(&pwm_config);
+        }
 
         if let Some(demo_values) = demo_receiver.try_get() {
-            boost = demo_values.boost;
-            oil_temp = demo_values.oil_temp;
-            water_temp = demo_values.water_temp;
-            dsg_temp = demo_values.dsg_temp;
-            iat_temp = demo_values.iat_temp;
-            egt_temp = demo_values.egt_temp;
-            batt_voltage = demo_values.batt_voltage;
-            afr = demo_values.afr;
+            sensors.ingest(&demo_values, now_ms);
         }
 
-        if reset_requested {
-            oil_state.reset_average();
-            oil_state.reset_graph();
-            oil_state.reset_peak();
-            water_state.reset_average();
-            water_state.reset_graph();
-            water_state.reset_peak();
-            dsg_state.reset_average();
-            dsg_state.reset_graph();
-            dsg_state.reset_peak();
-            iat_state.reset_average();
-            iat_state.reset_graph();
-            iat_state.reset_peak();
-            egt_state.reset_average();
-            egt_state.reset_graph();
-            egt_state.reset_peak();
-            batt_state.reset_average();
-            batt_state.reset_graph();
-            batt_state.reset_peak();
-            afr_state.reset_average();
-            afr_state.reset_graph();
-            afr_state.reset_peak();
-
-            boost_max = boost;
-            oil_max = oil_temp;
-            water_max = water_temp;
-            dsg_max = dsg_temp;
-            iat_max = iat_temp;
-            egt_max = egt_temp;
-            batt_min = batt_voltage;
-            batt_max = batt_voltage;
+        for (desc, channel) in sensors.iter() {
+            let logged = &mut sensor_faults[desc.id.index()];
+            if channel.fault != *logged {
+                match channel.fault {
+                    Some(fault) => log_info!("{} fault: {}", desc.label, fault.label()),
+                    None => log_info!("{} fault cleared", desc.label),
+                }
+                *logged = channel.fault;
+            }
+        }
 
+        if reset_requested {
+            // The reset ends the session: keep its record before clearing it
+            let record = sensors.session_record();
+            settings_store.save_session(&record);
+            session_autosave.restart(now_ms);
+            last_session = Some(record);
+            stats_show_last = false;
+            sensors.reset_stats();
             reset_requested = false;
             log_info!("Stats reset");
         }
 
-        let show_boost_easter_egg = if show_boost_psi {
-            boost * 14.5038 >= BOOST_EASTER_EGG_PSI
-        } else {
-            boost >= BOOST_EASTER_EGG_BAR
-        };
+        sensors.update(now_ms);
+
+        // Keep flash close to the running session so it survives the ignition going off
+        if session_autosave.due(now_ms, sensors.alarm_value(SensorId::Rpm)) {
+            settings_store.save_session(&sensors.session_record());
+        }
 
-        let oil_updated = oil_temp > oil_max;
-        let water_updated = water_temp > water_max;
-        let dsg_updated = dsg_temp > dsg_max;
-        let iat_updated = iat_temp > iat_max;
-        let egt_updated = egt_temp > egt_max;
-        let batt_updated = batt_voltage > batt_max || batt_voltage < batt_min;
-
-        boost_max = boost_max.max(boost);
-        oil_max = oil_max.max(oil_temp);
-        water_max = water_max.max(water_temp);
-        dsg_max = dsg_max.max(dsg_temp);
-        iat_max = iat_max.max(iat_temp);
-        egt_max = egt_max.max(egt_temp);
-        batt_min = batt_min.min(batt_voltage);
-        batt_max = batt_max.max(batt_voltage);
-
-        oil_state.update(oil_temp, oil_updated);
-        water_state.update(water_temp, water_updated);
-        dsg_state.update(dsg_temp, dsg_updated);
-        iat_state.update(iat_temp, iat_updated);
-        egt_state.update(egt_temp, egt_updated);
-        batt_state.update(batt_voltage, batt_updated);
-        afr_state.update(afr, false);
+        // Log a time-to-critical prediction when it first appears, not every frame it holds
+        for (desc, channel) in sensors.iter() {
+            let predicted = &mut critical_predicted[desc.id.index()];
+            if let Some(eta) = channel.critical_in_s
+                && !*predicted
+            {
+                log_info!("{} critical in ~{}s", desc.label, round_eta_s(eta));
+            }
+            *predicted = channel.critical_in_s.is_some();
+        }
 
         fps_frame_count += 1;
         if last_fps_calc.elapsed() >= Duration::from_secs(1) {
@@ -646,41 +772,23 @@ async fn main(spawner: Spawner) {
             average_fps = fps_sum / fps_sample_count as f32;
         }
 
-        let egt_danger_active = egt_temp >= EGT_DANGER_MANIFOLD;
-
-        let afr_target = if afr < AFR_RICH_AF {
-            BLUE
-        } else if afr < AFR_RICH {
-            DARK_TEAL
-        } else if afr < AFR_OPTIMAL_MAX {
-            GREEN
-        } else if afr <= AFR_LEAN_CRITICAL {
-            ORANGE
-        } else {
-            RED
-        };
-        color_transitions.set_target(cell_idx::AFR, afr_target);
-
-        let batt_target = if batt_voltage < BATT_CRITICAL {
-            RED
-        } else if batt_voltage < BATT_WARNING {
-            ORANGE
-        } else {
-            BLACK
-        };
-        color_transitions.set_target(cell_idx::BATTERY, batt_target);
+        // A sensor the car does not have never raises an alert
+        let alarm_value = |id| profile.has_sensor(id).then(|| sensors.alarm_value(id)).flatten();
+        for event in alerts.update(now_ms, &settings, alarm_value) {
+            log_info!("Alert {:?}: {}", event.kind, event.rule().message);
+        }
 
-        let (water_target, _) = temp_color_water(water_temp);
-        let (oil_target, _) = temp_color_oil_dsg(oil_temp);
-        let (dsg_target, _) = temp_color_oil_dsg(dsg_temp);
-        let (iat_target, _) = temp_color_iat(iat_temp);
-        let (egt_target, _) = temp_color_egt(egt_temp);
+        // Auto theme follows the backlight; any palette change repaints both framebuffers
+        let palette = settings.theme.resolve(brightness_percent, theme::active_palette());
+        if theme::set_active(palette) {
+            clear_frames_remaining = 2;
+            log_info!("Theme: {:?}", palette);
+        }
 
-        color_transitions.set_target(cell_idx::COOLANT, water_target);
-        color_transitions.set_target(cell_idx::OIL, oil_target);
-        color_transitions.set_target(cell_idx::DSG, dsg_target);
-        color_transitions.set_target(cell_idx::IAT, iat_target);
-        color_transitions.set_target(cell_idx::EGT, egt_target);
+        for (desc, channel) in sensors.iter() {
+            let target = theme::active().status(channel.status);
+            color_transitions.set_target(desc.id.index(), target);
+        }
 
         color_transitions.update(Instant::now());
 
@@ -689,15 +797,12 @@ async fn main(spawner: Spawner) {
         let buffer = unsafe { double_buffer.render_buffer() };
         let mut display = St7789Renderer::new(buffer);
 
-        if render_state.is_first_frame() || render_state.popup_just_closed() || clear_frames_remaining > 0 {
-            display.clear(BLACK).ok();
+        if render_state.is_first_frame() || clear_frames_remaining > 0 {
+            display.clear(theme::active().background).ok();
             render_state.mark_display_cleared();
-
-            if render_state.popup_just_closed() && clear_frames_remaining == 0 {
-                clear_frames_remaining = 1;
-            } else {
-                clear_frames_remaining = clear_frames_remaining.saturating_sub(1);
-            }
+            clear_frames_remaining = clear_frames_remaining.saturating_sub(1);
+        } else if let Some(region) = render_state.overlay_damage() {
+            display.fill_solid(&region, theme::active().background).ok();
         }
 
         match current_page {
@@ -706,152 +811,65 @@ async fn main(spawner: Spawner) {
                     draw_header(&mut display, fps_mode, current_fps, average_fps);
                 }
 
-                draw_boost_cell(
-                    &mut display,
-                    0,
-                    HEADER_HEIGHT,
-                    COL_WIDTH,
-                    ROW_HEIGHT,
-                    boost,
-                    boost_max,
-                    show_boost_psi,
-                    show_boost_easter_egg,
-                    blink_on,
-                    0,
-                );
-
-                draw_afr_cell(
-                    &mut display,
-                    COL_WIDTH,
-                    HEADER_HEIGHT,
-                    COL_WIDTH,
-                    ROW_HEIGHT,
-                    afr,
-                    &to_display_data(&afr_state),
-                    blink_on,
-                    0,
-                    Some(color_transitions.get_current(cell_idx::AFR)),
-                );
-
-                draw_batt_cell(
-                    &mut display,
-                    COL_WIDTH * 2,
-                    HEADER_HEIGHT,
-                    COL_WIDTH,
-                    ROW_HEIGHT,
-                    batt_voltage,
-                    batt_min,
-                    batt_max,
-                    &to_display_data(&batt_state),
-                    blink_on,
-                    0,
-                    Some(color_transitions.get_current(cell_idx::BATTERY)),
-                );
-
-                draw_temp_cell(
-                    &mut display,
-                    COL_WIDTH * 3,
-                    HEADER_HEIGHT,
-                    COL_WIDTH,
-                    ROW_HEIGHT,
-                    "COOL",
-                    water_temp,
-                    water_max,
-                    &to_display_data(&water_state),
-                    temp_color_water,
-                    is_critical_water,
-                    None::<fn(f32) -> bool>,
-                    blink_on,
-                    0,
-                    Some(color_transitions.get_current(cell_idx::COOLANT)),
-                );
-
-                draw_temp_cell(
-                    &mut display,
-                    0,
-                    HEADER_HEIGHT + ROW_HEIGHT,
-                    COL_WIDTH,
-                    ROW_HEIGHT,
-                    "OIL",
-                    oil_temp,
-                    oil_max,
-                    &to_display_data(&oil_state),
-                    temp_color_oil_dsg,
-                    is_critical_oil_dsg,
-                    Some(is_low_temp_oil),
-                    blink_on,
-                    0,
-                    Some(color_transitions.get_current(cell_idx::OIL)),
-                );
-
-                draw_temp_cell(
-                    &mut display,
-                    COL_WIDTH,
-                    HEADER_HEIGHT + ROW_HEIGHT,
-                    COL_WIDTH,
-                    ROW_HEIGHT,
-                    "DSG",
-                    dsg_temp,
-                    dsg_max,
-                    &to_display_data(&dsg_state),
-                    temp_color_oil_dsg,
-                    is_critical_oil_dsg,
-                    None::<fn(f32) -> bool>,
-                    blink_on,
-                    0,
-                    Some(color_transitions.get_current(cell_idx::DSG)),
-                );
-
-                draw_temp_cell(
-                    &mut display,
-                    COL_WIDTH * 2,
-                    HEADER_HEIGHT + ROW_HEIGHT,
-                    COL_WIDTH,
-                    ROW_HEIGHT,
-                    "IAT",
-                    iat_temp,
-                    iat_max,
-                    &to_display_data(&iat_state),
-                    temp_color_iat,
-                    is_critical_iat,
-                    None::<fn(f32) -> bool>,
-                    blink_on,
-                    0,
-                    Some(color_transitions.get_current(cell_idx::IAT)),
-                );
-
-                draw_temp_cell(
-                    &mut display,
-                    COL_WIDTH * 3,
-                    HEADER_HEIGHT + ROW_HEIGHT,
-                    COL_WIDTH,
-                    ROW_HEIGHT,
-                    "EGT",
-                    egt_temp,
-                    egt_max,
-                    &to_display_data(&egt_state),
-                    temp_color_egt,
-                    is_critical_egt,
-                    None::<fn(f32) -> bool>,
-                    blink_on,
-                    0,
-                    Some(color_transitions.get_current(cell_idx::EGT)),
-                );
+                let layout = &LAYOUTS[settings.layout_index()];
+                for slot_idx in 0..layout.slots.len() {
+                    let slot = layout.slot(slot_idx, profile);
+                    let (x, y) = layout.grid.slot_origin(slot_idx);
+                    let desc = descriptor(slot.sensor);
+                    draw_sensor_cell(
+                        &mut display,
+                        desc,
+                        sensors.get(slot.sensor),
+                        slot.widget,
+                        x,
+                        y,
+                        layout.grid.cell_width(),
+                        layout.grid.cell_height(),
+                        &settings.units,
+                        settings.status_cues,
+                        blink_on || alerts.is_silenced(slot.sensor),
+                        Some(color_transitions.get_current(desc.id.index())),
+                    );
+                }
 
                 if render_state.need_dividers() {
-                    draw_dividers(&mut display);
+                    draw_dividers(&mut display, layout.grid);
                     render_state.mark_dividers_drawn();
                 }
 
-                if let Some(ref popup) = active_popup {
-                    match popup {
-                        Popup::Reset(_) => draw_reset_popup(&mut display),
-                        Popup::Fps(_) => draw_fps_toggle_popup(&mut display, fps_mode),
-                        Popup::BoostUnit(_) => draw_boost_unit_popup(&mut display, show_boost_psi),
-                        Popup::Brightness(_, pct) => draw_brightness_popup(&mut display, *pct),
+                if selection_shown_at.is_some() {
+                    draw_selection_frame(&mut display, layout.grid, selected_slot);
+                }
+            }
+
+            Page::Graph => {
+                draw_graph_page(&mut display, &graph_view, &sensors, &settings.units);
+            }
+
+            Page::Stats => {
+                let desc = descriptor(stats_sensor);
+                match last_session.filter(|_| stats_show_last) {
+                    Some(record) => {
+                        draw_stats_page(
+                            &mut display,
+                            desc,
+                            &record.sensors[stats_sensor.index()].zones,
+                            None,
+                            true,
+                            &settings.units,
+                        )
+                    }
+                    None => {
+                        let zones = &sensors.get(stats_sensor).zones;
+                        draw_stats_page(
+                            &mut display,
+                            desc,
+                            zones.stats(),
+                            zones.current(),
+                            false,
+                            &settings.units,
+                        );
                     }
-                } else if egt_danger_active {
-                    draw_danger_manifold_popup(&mut display, blink_on);
                 }
             }
 
@@ -901,15 +919,32 @@ async fn main(spawner: Spawner) {
             Page::Logs => {
                 draw_logs_page(&mut display, log_scroll_offset);
             }
-        }
 
-        // Brightness popup overlay on Debug page (not Logs — encoder controls scroll there)
-        if matches!(current_page, Page::Debug) {
-            if let Some(Popup::Brightness(_, pct)) = active_popup {
-                draw_brightness_popup(&mut display, pct);
+            Page::Settings => {
+                draw_settings_page(&mut display, &menu, &settings);
+            }
+
+            Page::Focus => {
+                draw_focus_page(
+                    &mut display,
+                    descriptor(focus_sensor),
+                    sensors.get(focus_sensor),
+                    &settings.units,
+                    settings.status_cues,
+                    focus_zoom,
+                );
             }
         }
 
+        overlays.draw(
+            &mut display,
+            &PopupContext {
+                units: &settings.units,
+                alerts: &alerts,
+                blink_on,
+            },
+        );
+
         render_time_us = render_start.elapsed().as_micros() as u32;
         let frame_cycles_end = cpu_profiling::read();
         frame_cycles_used = cpu_profiling::elapsed(frame_cycles_start, frame_cycles_end);
@@ -951,8 +986,6 @@ async fn main(spawner: Spawner) {
             last_profile_log = Instant::now();
         }
 
-        prev_egt_danger_active = egt_danger_active;
-
         render_state.end_frame();
         frame_count = frame_count.wrapping_add(1);
 
@@ -963,18 +996,3 @@ async fn main(spawner: Spawner) {
         }
     }
 }
-
-fn to_display_data(state: &SensorState) -> SensorDisplayData<'_> {
-    let (buffer, start_idx, count, min, max) = state.get_graph_data();
-    SensorDisplayData {
-        trend: state.get_trend(),
-        is_new_peak: state.is_new_peak,
-        graph_buffer: buffer,
-        graph_buffer_size: crate::state::GRAPH_HISTORY_SIZE,
-        graph_start_idx: start_idx,
-        graph_count: count,
-        graph_min: min,
-        graph_max: max,
-        average: state.get_average(),
-    }
-}
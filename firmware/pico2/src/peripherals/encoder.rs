@@ -2,7 +2,8 @@
 //!
 //! Minimal no_std driver that communicates with the seesaw firmware
 //! over I2C to read encoder rotation (delta) and button press state.
-//! Publishes events via atomics for consumption by the main render loop.
+//! Publishes both via atomics for consumption by the main render loop, which turns
+//! the switch level into press events along with the PIM715 buttons.
 
 use core::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 
@@ -12,6 +13,7 @@ use embassy_time::Timer;
 use embedded_hal_async::i2c::I2c as _;
 
 use crate::log_info;
+use crate::tasks::watchdog::{self, SupervisedTask};
 
 // ---------------------------------------------------------------------------
 // Seesaw protocol constants
@@ -50,9 +52,8 @@ const SS_SWITCH_PIN: u8 = 24;
 /// The main loop should `swap(0)` to read-and-reset atomically.
 pub static ENCODER_DELTA: AtomicI32 = AtomicI32::new(0);
 
-/// Set to `true` on a button press edge (high→low transition).
-/// The main loop should `swap(false)` to consume.
-pub static ENCODER_BUTTON: AtomicBool = AtomicBool::new(false);
+/// Whether the button is held, as of the last poll.
+pub static ENCODER_SWITCH: AtomicBool = AtomicBool::new(false);
 
 /// Indicates whether the encoder was successfully initialized.
 pub static ENCODER_CONNECTED: AtomicBool = AtomicBool::new(false);
@@ -173,8 +174,8 @@ pub async fn encoder_task(mut i2c: I2c<'static, I2C0, Async>) {
     }
 
     ENCODER_CONNECTED.store(true, Ordering::Relaxed);
-
-    let mut prev_button_pressed = false;
+    // Only supervised once polling starts; a missing encoder is not a stall
+    watchdog::register(SupervisedTask::Encoder);
 
     loop {
         // Read encoder delta
@@ -184,15 +185,11 @@ pub async fn encoder_task(mut i2c: I2c<'static, I2C0, Async>) {
             }
         }
 
-        // Read button state for edge detection
         if let Ok(pressed) = read_button(&mut i2c).await {
-            // Detect press edge (was not pressed, now pressed)
-            if pressed && !prev_button_pressed {
-                ENCODER_BUTTON.store(true, Ordering::Relaxed);
-            }
-            prev_button_pressed = pressed;
+            ENCODER_SWITCH.store(pressed, Ordering::Relaxed);
         }
 
+        watchdog::heartbeat(SupervisedTask::Encoder);
         Timer::after_millis(20).await;
     }
 }
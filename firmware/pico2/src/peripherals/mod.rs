@@ -1,3 +1,3 @@
 pub mod encoder;
 
-pub use encoder::{ENCODER_BUTTON, ENCODER_DELTA, encoder_task};
+pub use encoder::{ENCODER_DELTA, ENCODER_SWITCH, encoder_task};
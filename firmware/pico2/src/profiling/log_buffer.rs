@@ -1,9 +1,8 @@
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
-use embedded_graphics::pixelcolor::Rgb565;
 use heapless::String;
 
-use crate::ui::GREEN;
+use crate::ui::Status;
 
 pub const LOG_ENTRIES: usize = 128;
 
@@ -17,9 +16,10 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
-    pub const fn color(self) -> Rgb565 {
+    /// Status whose theme colour the level prefix is drawn in.
+    pub const fn status(self) -> Status {
         match self {
-            Self::Info => GREEN,
+            Self::Info => Status::Normal,
         }
     }
 
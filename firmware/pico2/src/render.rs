@@ -40,27 +40,32 @@ impl FpsMode {
     }
 }
 
+// Host test builds link std, whose inherent f32 methods shadow these
+use embedded_graphics::prelude::{Point, Size};
+use embedded_graphics::primitives::Rectangle;
+#[cfg_attr(test, allow(unused_imports))]
 use micromath::F32Ext;
 
-pub const CELL_COUNT: usize = 8;
+use crate::config::{HEADER_HEIGHT, SCREEN_WIDTH};
+use crate::sensor_id::SensorId;
 
-pub mod cell_idx {
-    pub const AFR: usize = 1;
-    pub const BATTERY: usize = 2;
-    pub const COOLANT: usize = 3;
-    pub const OIL: usize = 4;
-    pub const DSG: usize = 5;
-    pub const IAT: usize = 6;
-    pub const EGT: usize = 7;
-}
+/// One cell (and one colour transition slot) per sensor, indexed by `SensorId::index()`.
+pub const CELL_COUNT: usize = SensorId::COUNT;
+
+/// Framebuffers drawn in turn; a repaint has to reach each of them.
+const FRAMEBUFFER_COUNT: u8 = 2;
+
+const HEADER_REGION: Rectangle = Rectangle::new(Point::zero(), Size::new(SCREEN_WIDTH, HEADER_HEIGHT));
 
 pub struct RenderState {
     dividers_drawn: bool,
     prev_fps_mode: FpsMode,
     prev_fps_instant_rounded: u32,
     prev_fps_average_rounded: u32,
-    prev_popup_kind: Option<u8>,
-    popup_just_closed: bool,
+    overlay_region: Option<Rectangle>,
+    /// Area an overlay left, repainted for `damage_frames` more frames.
+    damage: Option<Rectangle>,
+    damage_frames: u8,
     first_frame: bool,
     display_cleared: bool,
 }
@@ -72,15 +77,18 @@ impl RenderState {
             prev_fps_mode: FpsMode::Off,
             prev_fps_instant_rounded: 0,
             prev_fps_average_rounded: 0,
-            prev_popup_kind: None,
-            popup_just_closed: false,
+            overlay_region: None,
+            damage: None,
+            damage_frames: 0,
             first_frame: true,
             display_cleared: false,
         }
     }
 
     #[inline]
-    pub const fn need_dividers(&self) -> bool { !self.dividers_drawn || self.first_frame || self.display_cleared }
+    pub const fn need_dividers(&self) -> bool {
+        !self.dividers_drawn || self.first_frame || self.display_cleared || self.damage.is_some()
+    }
 
     #[inline]
     pub fn mark_dividers_drawn(&mut self) { self.dividers_drawn = true; }
@@ -104,7 +112,9 @@ impl RenderState {
         };
 
         let dirty = self.first_frame
-            || self.popup_just_closed
+            || self
+                .damage
+                .is_some_and(|damage| !damage.intersection(&HEADER_REGION).is_zero_sized())
             || self.display_cleared
             || fps_mode != self.prev_fps_mode
             || fps_changed;
@@ -115,22 +125,30 @@ impl RenderState {
         dirty
     }
 
-    pub fn update_popup(
+    /// Track the area the shown overlay covers. Once an overlay closes or gives way to
+    /// one of another size, the area it covered is damaged until every framebuffer has
+    /// repainted it.
+    pub fn update_overlay(
         &mut self,
-        popup_kind: Option<u8>,
+        region: Option<Rectangle>,
     ) {
-        let changed = popup_kind != self.prev_popup_kind;
-        let was_visible = self.prev_popup_kind.is_some();
-        self.prev_popup_kind = popup_kind;
-
-        if changed && was_visible {
-            self.popup_just_closed = true;
-            self.dividers_drawn = false;
+        if region == self.overlay_region {
+            return;
         }
+        if let Some(previous) = self.overlay_region {
+            self.damage = Some(match self.damage {
+                Some(damage) => envelope(damage, previous),
+                None => previous,
+            });
+            self.damage_frames = FRAMEBUFFER_COUNT;
+        }
+        self.overlay_region = region;
     }
 
+    /// Area to clear to the background this frame, so the page repaints what an
+    /// overlay covered.
     #[inline]
-    pub const fn popup_just_closed(&self) -> bool { self.popup_just_closed }
+    pub const fn overlay_damage(&self) -> Option<Rectangle> { self.damage }
 
     #[inline]
     pub const fn is_first_frame(&self) -> bool { self.first_frame }
@@ -142,11 +160,87 @@ impl RenderState {
 
     pub fn end_frame(&mut self) {
         self.first_frame = false;
-        self.popup_just_closed = false;
         self.display_cleared = false;
+        self.damage_frames = self.damage_frames.saturating_sub(1);
+        if self.damage_frames == 0 {
+            self.damage = None;
+        }
     }
 }
 
 impl Default for RenderState {
     fn default() -> Self { Self::new() }
 }
+
+/// Smallest rectangle that holds both `a` and `b`.
+fn envelope(
+    a: Rectangle,
+    b: Rectangle,
+) -> Rectangle {
+    let (Some(a_end), Some(b_end)) = (a.bottom_right(), b.bottom_right()) else {
+        return if a.is_zero_sized() { b } else { a };
+    };
+    Rectangle::with_corners(
+        Point::new(a.top_left.x.min(b.top_left.x), a.top_left.y.min(b.top_left.y)),
+        Point::new(a_end.x.max(b_end.x), a_end.y.max(b_end.y)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POPUP: Rectangle = Rectangle::new(Point::new(100, 90), Size::new(120, 60));
+    const ALERT: Rectangle = Rectangle::new(Point::new(50, 75), Size::new(216, 90));
+
+    fn finish_first_frame(render_state: &mut RenderState) {
+        render_state.check_header_dirty(FpsMode::Off, 0.0, 0.0);
+        render_state.mark_dividers_drawn();
+        render_state.end_frame();
+    }
+
+    #[test]
+    fn closed_overlay_is_repainted_in_both_framebuffers() {
+        let mut render_state = RenderState::new();
+        finish_first_frame(&mut render_state);
+
+        render_state.update_overlay(Some(POPUP));
+        assert_eq!(
+            render_state.overlay_damage(),
+            None,
+            "opening covers, nothing to repaint"
+        );
+        render_state.end_frame();
+
+        render_state.update_overlay(None);
+        for _ in 0..FRAMEBUFFER_COUNT {
+            assert_eq!(render_state.overlay_damage(), Some(POPUP));
+            assert!(render_state.need_dividers());
+            assert!(!render_state.check_header_dirty(FpsMode::Off, 0.0, 0.0));
+            render_state.mark_dividers_drawn();
+            render_state.end_frame();
+            render_state.update_overlay(None);
+        }
+        assert_eq!(render_state.overlay_damage(), None);
+        assert!(!render_state.need_dividers());
+    }
+
+    #[test]
+    fn overlays_closing_in_turn_damage_their_envelope() {
+        let mut render_state = RenderState::new();
+        finish_first_frame(&mut render_state);
+
+        render_state.update_overlay(Some(POPUP));
+        render_state.end_frame();
+        render_state.update_overlay(Some(ALERT));
+        assert_eq!(render_state.overlay_damage(), Some(POPUP));
+        render_state.end_frame();
+        render_state.update_overlay(None);
+        assert_eq!(render_state.overlay_damage(), Some(ALERT), "alert holds the popup");
+
+        let header_overlay = Rectangle::new(Point::new(250, 0), Size::new(60, 40));
+        render_state.update_overlay(Some(header_overlay));
+        render_state.update_overlay(None);
+        assert!(render_state.check_header_dirty(FpsMode::Off, 0.0, 0.0));
+    }
+}
@@ -1,18 +1,27 @@
 use embassy_time::Instant;
 use embedded_graphics::prelude::*;
 
-use super::{INIT_MESSAGES, MAX_VISIBLE_LINES, draw_loading_frame, draw_welcome_frame};
+use super::{MAX_VISIBLE_LINES, boot_line, draw_loading_frame, draw_welcome_frame};
+use crate::config::dashboard::INIT_MESSAGES;
 use crate::drivers::{DoubleBuffer, St7789Flusher, St7789Renderer};
+use crate::state::{BootScreen, VehicleProfile};
 use crate::ui::BLACK;
 
 const WELCOME_DURATION_MS: u64 = 7000;
 
 const READY_PAUSE_MS: u64 = 500;
 
+/// Init log for `profile` then welcome animation, trimmed according to the `boot_screen` setting.
 pub async fn run_boot_sequence(
     flusher: &mut St7789Flusher<'_>,
     double_buffer: &mut DoubleBuffer,
+    boot_screen: BootScreen,
+    profile: &VehicleProfile,
 ) {
+    if boot_screen == BootScreen::Skip {
+        return;
+    }
+
     {
         let buffer = unsafe { double_buffer.render_buffer() };
         let mut renderer = St7789Renderer::new(buffer);
@@ -21,7 +30,8 @@ pub async fn run_boot_sequence(
         let mut line_count: usize = 0;
         let boot_start = Instant::now();
 
-        for (msg, duration_ms) in &INIT_MESSAGES {
+        for &(line, duration_ms) in INIT_MESSAGES {
+            let msg = boot_line(line, profile);
             if line_count < MAX_VISIBLE_LINES {
                 visible_lines[line_count] = msg;
                 line_count += 1;
@@ -38,7 +48,7 @@ pub async fn run_boot_sequence(
                 draw_loading_frame(&mut renderer, &visible_lines, line_count, elapsed_ms);
                 flusher.flush_buffer(unsafe { double_buffer.get_buffer(0) }).await;
 
-                if msg_start.elapsed().as_millis() >= *duration_ms {
+                if msg_start.elapsed().as_millis() >= duration_ms {
                     break;
                 }
             }
@@ -56,7 +66,7 @@ pub async fn run_boot_sequence(
         }
     }
 
-    {
+    if boot_screen == BootScreen::Full {
         let buffer = unsafe { double_buffer.render_buffer() };
         let mut renderer = St7789Renderer::new(buffer);
 
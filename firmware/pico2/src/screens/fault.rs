@@ -0,0 +1,129 @@
+use core::fmt::Write;
+
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle};
+use embedded_graphics::text::Text;
+use heapless::String;
+
+use crate::ui::{CENTERED, LABEL_FONT, LABEL_STYLE_WHITE, LEFT_ALIGNED, RED, TITLE_STYLE_WHITE, WHITE, YELLOW};
+
+const TITLE_POS: Point = Point::new(160, 22);
+const KIND_POS: Point = Point::new(160, 40);
+const DIVIDER_START: Point = Point::new(4, 46);
+const DIVIDER_END: Point = Point::new(315, 46);
+const TEXT_X: i32 = 4;
+const TEXT_START_Y: i32 = 60;
+const LINE_HEIGHT: i32 = 12;
+const REGISTER_COL2_X: i32 = 164;
+
+/// Characters per line for the 6x10 font with a 4 px margin on each side.
+const CHARS_PER_LINE: usize = 52;
+
+const MAX_MESSAGE_LINES: usize = 6;
+
+const DIVIDER_STYLE: PrimitiveStyle<Rgb565> = PrimitiveStyle::with_stroke(WHITE, 1);
+
+/// Everything the fault screen shows; filled in by the panic or HardFault handler.
+pub struct FaultReport<'a> {
+    /// Short fault class, e.g. `"PANIC"` or `"HARDFAULT"`.
+    pub kind: &'a str,
+    pub message: &'a str,
+    /// `file:line` of the panic, or empty when unknown.
+    pub location: &'a str,
+    pub uptime_ms: u64,
+    /// Name/value pairs dumped in two columns below the message (empty for panics).
+    pub registers: &'a [(&'a str, u32)],
+    pub footer: &'a str,
+}
+
+pub fn draw_fault_screen<D>(
+    display: &mut D,
+    report: &FaultReport<'_>,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let highlight_style = MonoTextStyle::new(LABEL_FONT, YELLOW);
+
+    display.clear(RED).ok();
+
+    Text::with_text_style("SYSTEM FAULT", TITLE_POS, TITLE_STYLE_WHITE, CENTERED)
+        .draw(display)
+        .ok();
+    Text::with_text_style(report.kind, KIND_POS, highlight_style, CENTERED)
+        .draw(display)
+        .ok();
+
+    Line::new(DIVIDER_START, DIVIDER_END)
+        .into_styled(DIVIDER_STYLE)
+        .draw(display)
+        .ok();
+
+    let mut y = TEXT_START_Y;
+    let mut line: String<CHARS_PER_LINE> = String::new();
+    let mut lines_drawn = 0;
+
+    for c in report.message.chars() {
+        if lines_drawn >= MAX_MESSAGE_LINES {
+            break;
+        }
+        let wrap = c == '\n' || line.push(c).is_err();
+        if wrap {
+            Text::with_text_style(&line, Point::new(TEXT_X, y), LABEL_STYLE_WHITE, LEFT_ALIGNED)
+                .draw(display)
+                .ok();
+            y += LINE_HEIGHT;
+            lines_drawn += 1;
+            line.clear();
+            if c != '\n' {
+                line.push(c).ok();
+            }
+        }
+    }
+    if !line.is_empty() && lines_drawn < MAX_MESSAGE_LINES {
+        Text::with_text_style(&line, Point::new(TEXT_X, y), LABEL_STYLE_WHITE, LEFT_ALIGNED)
+            .draw(display)
+            .ok();
+        y += LINE_HEIGHT;
+    }
+    y += 4;
+
+    let mut s: String<64> = String::new();
+    if !report.location.is_empty() {
+        let _ = write!(s, "At: {}", report.location);
+        Text::with_text_style(&s, Point::new(TEXT_X, y), highlight_style, LEFT_ALIGNED)
+            .draw(display)
+            .ok();
+        y += LINE_HEIGHT;
+    }
+
+    let total_s = report.uptime_ms / 1000;
+    s.clear();
+    let _ = write!(
+        s,
+        "Uptime: {:02}:{:02}:{:02}.{:03}",
+        total_s / 3600,
+        (total_s / 60) % 60,
+        total_s % 60,
+        report.uptime_ms % 1000
+    );
+    Text::with_text_style(&s, Point::new(TEXT_X, y), highlight_style, LEFT_ALIGNED)
+        .draw(display)
+        .ok();
+    y += LINE_HEIGHT + 4;
+
+    for (i, (name, value)) in report.registers.iter().enumerate() {
+        let x = if i % 2 == 0 { TEXT_X } else { REGISTER_COL2_X };
+        let row_y = y + (i / 2) as i32 * LINE_HEIGHT;
+        s.clear();
+        let _ = write!(s, "{name:<5} {value:08X}");
+        Text::with_text_style(&s, Point::new(x, row_y), LABEL_STYLE_WHITE, LEFT_ALIGNED)
+            .draw(display)
+            .ok();
+    }
+
+    Text::with_text_style(report.footer, Point::new(160, 232), LABEL_STYLE_WHITE, CENTERED)
+        .draw(display)
+        .ok();
+}
@@ -0,0 +1,364 @@
+use core::fmt::Write;
+
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::Text;
+use heapless::String;
+
+use crate::state::{SensorChannel, SensorDescriptor, ZOOM_POINTS, Zoom, round_eta_s};
+use crate::ui::{
+    CENTERED,
+    LABEL_FONT,
+    LEFT_ALIGNED,
+    RIGHT_ALIGNED,
+    Status,
+    VALUE_FONT,
+    caption_style,
+    label_style,
+    theme,
+    title_style,
+};
+use crate::units::{Quantity, UnitPrefs};
+use crate::widgets::{ICON_SIZE, draw_cue_icon};
+
+const TITLE_POS: Point = Point::new(8, 24);
+const VALUE_POS: Point = Point::new(312, 30);
+/// Commanded value, under the title.
+const CMD_POS: Point = Point::new(8, 38);
+/// Rate of change, under the value.
+const RATE_POS: Point = Point::new(312, 40);
+/// Time-to-critical prediction, top left inside the plot.
+const PREDICTION_POS: Point = Point::new(PLOT_X + 4, PLOT_Y + 10);
+
+const PLOT_X: i32 = 44;
+const PLOT_Y: i32 = 44;
+const PLOT_W: u32 = 268;
+const PLOT_H: u32 = 140;
+const PLOT_BOTTOM: i32 = PLOT_Y + PLOT_H as i32 - 1;
+const PLOT_RIGHT: i32 = PLOT_X + PLOT_W as i32 - 1;
+const CENTER_PLOT_X: i32 = PLOT_X + PLOT_W as i32 / 2;
+
+const Y_LABEL_X: i32 = PLOT_X - 4;
+const X_LABEL_Y: i32 = PLOT_BOTTOM + 12;
+const STATS_CAPTION_Y: i32 = 212;
+const STATS_VALUE_Y: i32 = 234;
+const STATS_COLUMNS: [i32; 3] = [60, 160, 260];
+
+/// Fraction of the data range added above and below the trace.
+const RANGE_PADDING: f32 = 0.15;
+
+/// Horizontal pixels between two plot points; the newest point sits on the right edge.
+const POINT_SPACING: f32 = (PLOT_W - 1) as f32 / (ZOOM_POINTS - 1) as f32;
+
+/// Full-screen view of one sensor: big value, history graph over its colour
+/// bands with axis labels, and avg/min/max. The rate of change is shown under the
+/// value, and a warning in the plot when the trend reaches critical within the
+/// prediction horizon. A faulty sensor shows FAULT and the failed check in place
+/// of its value and rate.
+///
+/// The graph shows the `zoom` window of the long-term history: the min-max
+/// range of each point as a shaded bar, so short spikes stay visible at
+/// coarse zoom, with the average drawn over it. With `cues` set, the status icon is drawn left of the value.
+pub fn draw_focus_page<D>(
+    display: &mut D,
+    desc: &SensorDescriptor,
+    channel: &SensorChannel,
+    units: &UnitPrefs,
+    cues: bool,
+    zoom: Zoom,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let theme = theme::active();
+    display.clear(theme.background).ok();
+
+    let quantity = desc.quantity;
+    let mut s: String<32> = String::new();
+
+    match units.symbol(quantity) {
+        "" => {
+            let _ = write!(s, "{}", desc.label);
+        }
+        symbol => {
+            let _ = write!(s, "{} ({})", desc.label, symbol);
+        }
+    }
+    Text::with_text_style(&s, TITLE_POS, title_style(), LEFT_ALIGNED)
+        .draw(display)
+        .ok();
+
+    // A faulty sensor shows no value: the last good one would pass for a live reading
+    let status = if channel.fault.is_some() {
+        Status::Warning
+    } else {
+        channel.status
+    };
+    let value_color = theme.reading(status);
+    s.clear();
+    match channel.fault {
+        Some(_) => {
+            let _ = s.push_str("FAULT");
+        }
+        None => {
+            let _ = units.write(quantity, channel.value, &mut s);
+        }
+    }
+    let value_text = Text::with_text_style(
+        &s,
+        VALUE_POS,
+        MonoTextStyle::new(VALUE_FONT, value_color),
+        RIGHT_ALIGNED,
+    );
+    value_text.draw(display).ok();
+
+    if cues {
+        let bounds = value_text.bounding_box();
+        let icon_pos = Point::new(
+            bounds.top_left.x - ICON_SIZE as i32 - 6,
+            bounds.center().y - ICON_SIZE as i32 / 2,
+        );
+        draw_cue_icon(display, icon_pos, status.cue().icon, value_color);
+    }
+
+    if let Some(target) = channel.target {
+        s.clear();
+        let _ = s.push_str("CMD ");
+        let _ = units.write(quantity, target, &mut s);
+        Text::with_text_style(&s, CMD_POS, caption_style(), LEFT_ALIGNED)
+            .draw(display)
+            .ok();
+    }
+
+    if let Some(fault) = channel.fault {
+        Text::with_text_style(fault.label(), RATE_POS, label_style(), RIGHT_ALIGNED)
+            .draw(display)
+            .ok();
+    }
+
+    if let Some(slope) = channel.slope_per_s {
+        s.clear();
+        let _ = units.write_rate(quantity, desc.trend.rate(slope), desc.trend.per.label(), &mut s);
+        let color = if channel.trend.is_some() {
+            theme.text
+        } else {
+            theme.label
+        };
+        Text::with_text_style(&s, RATE_POS, MonoTextStyle::new(LABEL_FONT, color), RIGHT_ALIGNED)
+            .draw(display)
+            .ok();
+    }
+
+    let window = zoom.window();
+    let history = &channel.history;
+    let count = history.len(window);
+
+    if count < 2 {
+        Text::with_text_style(
+            "Collecting data...",
+            Point::new(PLOT_X + PLOT_W as i32 / 2, PLOT_Y + PLOT_H as i32 / 2),
+            label_style(),
+            CENTERED,
+        )
+        .draw(display)
+        .ok();
+    } else {
+        let (data_min, data_max) = history.range(window).unwrap_or((channel.value, channel.value));
+        let (lo, hi) = plot_range(data_min.min(channel.value), data_max.max(channel.value), quantity);
+
+        draw_bands(display, desc, units, lo, hi);
+
+        let envelope = PrimitiveStyle::with_stroke(theme.label, 1);
+        let trace = PrimitiveStyle::with_stroke(theme.text, 1);
+        let mut newer: Option<Point> = None;
+        for (age, point) in (0..count).filter_map(|age| Some((age, history.point(window, age)?))) {
+            let x = PLOT_RIGHT - (age as f32 * POINT_SPACING) as i32;
+            if point.max > point.min {
+                Line::new(
+                    Point::new(x, value_to_y(point.max, lo, hi)),
+                    Point::new(x, value_to_y(point.min, lo, hi)),
+                )
+                .into_styled(envelope)
+                .draw(display)
+                .ok();
+            }
+            let avg = Point::new(x, value_to_y(point.avg, lo, hi));
+            if let Some(newer) = newer {
+                Line::new(avg, newer).into_styled(trace).draw(display).ok();
+            }
+            newer = Some(avg);
+        }
+
+        for (value, y) in [
+            (hi, PLOT_Y),
+            ((lo + hi) / 2.0, PLOT_Y + PLOT_H as i32 / 2),
+            (lo, PLOT_BOTTOM),
+        ] {
+            s.clear();
+            let _ = units.write(quantity, value, &mut s);
+            Text::with_text_style(&s, Point::new(Y_LABEL_X, y + 4), label_style(), RIGHT_ALIGNED)
+                .draw(display)
+                .ok();
+        }
+
+        s.clear();
+        write_age(&mut s, window.age_ms(ZOOM_POINTS - 1));
+        Text::with_text_style(&s, Point::new(PLOT_X, X_LABEL_Y), label_style(), LEFT_ALIGNED)
+            .draw(display)
+            .ok();
+        s.clear();
+        let _ = write!(s, "ZOOM {}", zoom.label());
+        Text::with_text_style(&s, Point::new(CENTER_PLOT_X, X_LABEL_Y), caption_style(), CENTERED)
+            .draw(display)
+            .ok();
+        Text::with_text_style("now", Point::new(PLOT_RIGHT, X_LABEL_Y), label_style(), RIGHT_ALIGNED)
+            .draw(display)
+            .ok();
+    }
+
+    if let Some(eta) = channel.critical_in_s {
+        s.clear();
+        let _ = write!(s, "CRITICAL IN ~{}s", round_eta_s(eta));
+        let style = MonoTextStyle::new(LABEL_FONT, theme.status(Status::Critical));
+        Text::with_text_style(&s, PREDICTION_POS, style, LEFT_ALIGNED)
+            .draw(display)
+            .ok();
+    }
+
+    let axis_style = PrimitiveStyle::with_stroke(theme.label, 1);
+    Line::new(Point::new(PLOT_X - 1, PLOT_Y), Point::new(PLOT_X - 1, PLOT_BOTTOM + 1))
+        .into_styled(axis_style)
+        .draw(display)
+        .ok();
+    Line::new(
+        Point::new(PLOT_X - 1, PLOT_BOTTOM + 1),
+        Point::new(PLOT_RIGHT, PLOT_BOTTOM + 1),
+    )
+    .into_styled(axis_style)
+    .draw(display)
+    .ok();
+
+    let caption_style = caption_style();
+    let stats = [
+        ("AVG", channel.state.get_average()),
+        ("MIN", (channel.min <= channel.max).then_some(channel.min)),
+        ("MAX", (channel.min <= channel.max).then_some(channel.max)),
+    ];
+    for ((caption, value), x) in stats.into_iter().zip(STATS_COLUMNS) {
+        Text::with_text_style(caption, Point::new(x, STATS_CAPTION_Y), caption_style, CENTERED)
+            .draw(display)
+            .ok();
+        s.clear();
+        match value {
+            Some(v) => {
+                let _ = units.write(quantity, v, &mut s);
+            }
+            None => {
+                let _ = write!(s, "--");
+            }
+        }
+        Text::with_text_style(&s, Point::new(x, STATS_VALUE_Y), title_style(), CENTERED)
+            .draw(display)
+            .ok();
+    }
+}
+
+/// Write an age back from now as `-45s`, `-10m` or `-4h`, rounded to the unit.
+pub(super) fn write_age<const N: usize>(
+    s: &mut String<N>,
+    age_ms: u32,
+) {
+    let _ = match age_ms {
+        0..120_000 => write!(s, "-{}s", age_ms / 1_000),
+        120_000..7_200_000 => write!(s, "-{}m", (age_ms + 30_000) / 60_000),
+        _ => write!(s, "-{}h", (age_ms + 1_800_000) / 3_600_000),
+    };
+}
+
+/// Padded value range for the y axis, never narrower than a few steps of the base unit.
+pub(super) fn plot_range(
+    data_min: f32,
+    data_max: f32,
+    quantity: Quantity,
+) -> (f32, f32) {
+    // History is stored in base units, so the span follows their resolution
+    let min_span = match UnitPrefs::BASE.precision(quantity) {
+        0 => 4.0,
+        1 => 0.4,
+        _ => 0.04,
+    };
+    let span = (data_max - data_min).max(min_span);
+    let mid = (data_min + data_max) / 2.0;
+    let half = span * (0.5 + RANGE_PADDING);
+    (mid - half, mid + half)
+}
+
+#[inline]
+fn value_to_y(
+    value: f32,
+    lo: f32,
+    hi: f32,
+) -> i32 {
+    let frac = ((value - lo) / (hi - lo)).clamp(0.0, 1.0);
+    PLOT_BOTTOM - (frac * (PLOT_H - 1) as f32) as i32
+}
+
+/// Fill each colour band of the sensor (between consecutive thresholds) that falls
+/// inside the plotted range with a dimmed version of its colour, and label its edges.
+fn draw_bands<D>(
+    display: &mut D,
+    desc: &SensorDescriptor,
+    units: &UnitPrefs,
+    lo: f32,
+    hi: f32,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let theme = theme::active();
+    let edges = desc.thresholds();
+    let mut s: String<16> = String::new();
+
+    for band in 0..=edges.len() {
+        let lower = if band == 0 { lo } else { edges[band - 1].value.max(lo) };
+        let upper = if band == edges.len() {
+            hi
+        } else {
+            edges[band].value.min(hi)
+        };
+        if upper <= lower {
+            continue;
+        }
+
+        // Sample the middle of the visible part so `>` vs `>=` edges don't matter
+        let status = (desc.status_fn)((lower + upper) / 2.0);
+        let color = theme.reading(status);
+        if status != Status::Neutral {
+            let top = value_to_y(upper, lo, hi);
+            let bottom = value_to_y(lower, lo, hi);
+            Rectangle::new(Point::new(PLOT_X, top), Size::new(PLOT_W, (bottom - top + 1) as u32))
+                .into_styled(PrimitiveStyle::with_fill(dim(color)))
+                .draw(display)
+                .ok();
+        }
+
+        if band > 0 && edges[band - 1].value > lo {
+            let edge = edges[band - 1].value;
+            s.clear();
+            let _ = units.write(desc.quantity, edge, &mut s);
+            let label_color = if status == Status::Neutral { theme.label } else { color };
+            Text::with_text_style(
+                &s,
+                Point::new(PLOT_RIGHT - 2, value_to_y(edge, lo, hi) - 2),
+                MonoTextStyle::new(LABEL_FONT, label_color),
+                RIGHT_ALIGNED,
+            )
+            .draw(display)
+            .ok();
+        }
+    }
+}
+
+/// Darken a band colour so the trace stays readable on top of it.
+#[inline]
+fn dim(color: Rgb565) -> Rgb565 { Rgb565::new(color.r() / 3, color.g() / 3, color.b() / 3) }
@@ -0,0 +1,213 @@
+use core::fmt::Write;
+
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, Line, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::Text;
+use heapless::String;
+
+use super::focus::{plot_range, write_age};
+use crate::state::{GraphView, MAX_TRACES, SensorBank, ZOOM_POINTS, descriptor};
+use crate::ui::{CENTERED, LABEL_FONT, LEFT_ALIGNED, RIGHT_ALIGNED, caption_style, label_style, theme};
+use crate::units::UnitPrefs;
+
+// The theme has one colour per trace slot
+const _: () = assert!(MAX_TRACES == theme::DAY.traces.len());
+
+const LEGEND_COLUMNS: [i32; 2] = [2, 162];
+const LEGEND_ROWS: [i32; 2] = [10, 24];
+const SWATCH_SIZE: Size = Size::new(6, 8);
+
+const PLOT_X: i32 = 4;
+const PLOT_Y: i32 = 32;
+const PLOT_W: u32 = 312;
+const PLOT_H: u32 = 172;
+const PLOT_BOTTOM: i32 = PLOT_Y + PLOT_H as i32 - 1;
+const PLOT_RIGHT: i32 = PLOT_X + PLOT_W as i32 - 1;
+
+const X_LABEL_Y: i32 = PLOT_BOTTOM + 12;
+const PICKER_Y: i32 = 234;
+
+/// Horizontal pixels between two plot points; the newest point sits on the right edge.
+const SAMPLE_SPACING: f32 = (PLOT_W - 1) as f32 / (ZOOM_POINTS - 1) as f32;
+
+/// Overlay of several sensors on one time axis.
+///
+/// Every trace is normalised to its own padded min/max so differently scaled
+/// sensors share the plot height; the legend shows each trace's range and its
+/// value under the cursor. The traces show the average of each point of the
+/// view's zoom window from the long-term history.
+pub fn draw_graph_page<D>(
+    display: &mut D,
+    view: &GraphView,
+    sensors: &SensorBank,
+    units: &UnitPrefs,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let theme = theme::active();
+    display.clear(theme.background).ok();
+
+    Rectangle::new(Point::new(PLOT_X - 1, PLOT_Y - 1), Size::new(PLOT_W + 2, PLOT_H + 2))
+        .into_styled(PrimitiveStyle::with_stroke(theme.label, 1))
+        .draw(display)
+        .ok();
+    draw_grid(display);
+
+    let cursor_x = age_to_x(view.cursor_age);
+    draw_dotted_vline(display, cursor_x, theme.text);
+
+    let mut s: String<40> = String::new();
+    let window = view.zoom.window();
+
+    for (slot, (trace, color)) in view.traces().iter().zip(theme.traces).enumerate() {
+        let legend_pos = Point::new(LEGEND_COLUMNS[slot % 2], LEGEND_ROWS[slot / 2]);
+        let Some(id) = *trace else {
+            continue;
+        };
+        let desc = descriptor(id);
+        let history = &sensors.get(id).history;
+        let count = history.len(window);
+        let sample = |age: usize| history.point(window, age).map_or(0.0, |point| point.avg);
+
+        let range = history
+            .range(window)
+            .map(|(min, max)| plot_range(min, max, desc.quantity));
+        if let Some((lo, hi)) = range {
+            let style = PrimitiveStyle::with_stroke(color, 1);
+            for age in 1..count {
+                Line::new(
+                    Point::new(age_to_x(age), value_to_y(sample(age), lo, hi)),
+                    Point::new(age_to_x(age - 1), value_to_y(sample(age - 1), lo, hi)),
+                )
+                .into_styled(style)
+                .draw(display)
+                .ok();
+            }
+        }
+
+        Rectangle::new(legend_pos + Point::new(0, -7), SWATCH_SIZE)
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(display)
+            .ok();
+
+        s.clear();
+        let _ = write!(s, "{}", desc.label);
+        match range {
+            Some((lo, hi)) if view.cursor_age < count => {
+                let value = sample(view.cursor_age);
+                let _ = s.push(' ');
+                let _ = units.write_padded(desc.quantity, value, &mut s);
+                let _ = s.push(' ');
+                let _ = units.write(desc.quantity, lo, &mut s);
+                let _ = s.push('-');
+                let _ = units.write(desc.quantity, hi, &mut s);
+                Circle::with_center(Point::new(cursor_x, value_to_y(value, lo, hi)), 5)
+                    .into_styled(PrimitiveStyle::with_fill(color))
+                    .draw(display)
+                    .ok();
+            }
+            _ => {
+                let _ = write!(s, " --");
+            }
+        }
+        Text::with_text_style(
+            &s,
+            legend_pos + Point::new(SWATCH_SIZE.width as i32 + 3, 0),
+            MonoTextStyle::new(LABEL_FONT, color),
+            LEFT_ALIGNED,
+        )
+        .draw(display)
+        .ok();
+    }
+
+    let axis_style = caption_style();
+    s.clear();
+    write_age(&mut s, window.age_ms(ZOOM_POINTS - 1));
+    Text::with_text_style(&s, Point::new(PLOT_X, X_LABEL_Y), axis_style, LEFT_ALIGNED)
+        .draw(display)
+        .ok();
+    Text::with_text_style("now", Point::new(PLOT_RIGHT, X_LABEL_Y), axis_style, RIGHT_ALIGNED)
+        .draw(display)
+        .ok();
+
+    // Cursor time, kept clear of the axis end labels
+    s.clear();
+    if view.cursor_age == 0 {
+        let _ = write!(s, "LIVE");
+    } else {
+        write_age(&mut s, window.age_ms(view.cursor_age));
+    }
+    let cursor_label_x = cursor_x.clamp(PLOT_X + 48, PLOT_RIGHT - 48);
+    Text::with_text_style(&s, Point::new(cursor_label_x, X_LABEL_Y), label_style(), CENTERED)
+        .draw(display)
+        .ok();
+
+    let highlighted = view.highlighted;
+    let shown = view.is_shown(highlighted);
+    s.clear();
+    let _ = write!(
+        s,
+        "X:next A:show/hide  [{}] {}",
+        if shown { '*' } else { ' ' },
+        descriptor(highlighted).label
+    );
+    Text::with_text_style(
+        &s,
+        Point::new(PLOT_X, PICKER_Y),
+        if shown { label_style() } else { caption_style() },
+        LEFT_ALIGNED,
+    )
+    .draw(display)
+    .ok();
+
+    s.clear();
+    let _ = write!(s, "ZOOM {}", view.zoom.label());
+    Text::with_text_style(&s, Point::new(PLOT_RIGHT, PICKER_Y), caption_style(), RIGHT_ALIGNED)
+        .draw(display)
+        .ok();
+}
+
+#[inline]
+fn age_to_x(age: usize) -> i32 { PLOT_RIGHT - (age as f32 * SAMPLE_SPACING) as i32 }
+
+#[inline]
+fn value_to_y(
+    value: f32,
+    lo: f32,
+    hi: f32,
+) -> i32 {
+    let frac = ((value - lo) / (hi - lo)).clamp(0.0, 1.0);
+    PLOT_BOTTOM - (frac * (PLOT_H - 1) as f32) as i32
+}
+
+/// Quarter lines of the normalised scale.
+fn draw_grid<D>(display: &mut D)
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let color = theme::active().label;
+    for quarter in 1..4 {
+        let y = PLOT_Y + (PLOT_H as i32 * quarter) / 4;
+        display
+            .draw_iter((PLOT_X..=PLOT_RIGHT).step_by(4).map(|x| Pixel(Point::new(x, y), color)))
+            .ok();
+    }
+}
+
+fn draw_dotted_vline<D>(
+    display: &mut D,
+    x: i32,
+    color: Rgb565,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    display
+        .draw_iter(
+            (PLOT_Y..=PLOT_BOTTOM)
+                .step_by(2)
+                .map(|y| Pixel(Point::new(x, y), color)),
+        )
+        .ok();
+}
@@ -7,6 +7,8 @@ use embedded_graphics::primitives::{Line, PrimitiveStyle};
 use embedded_graphics::text::Text;
 use heapless::String;
 
+use crate::config::dashboard::LOADING_TITLE;
+use crate::state::VehicleProfile;
 use crate::ui::{BLACK, CENTERED, LEFT_ALIGNED, RED, WHITE};
 
 const TITLE_POS: Point = Point::new(160, 25);
@@ -24,15 +26,18 @@ const CONSOLE_STYLE: MonoTextStyle<'static, Rgb565> =
     MonoTextStyle::new(&embedded_graphics::mono_font::ascii::FONT_6X10, BLACK);
 const DIVIDER_STYLE: PrimitiveStyle<Rgb565> = PrimitiveStyle::with_stroke(RED, 1);
 
-pub const INIT_MESSAGES: [(&str, u64); 7] = [
-    ("Initializing OBD-II interface...", 800),
-    ("Connecting to ECU...", 1200),
-    ("Reading vehicle info...", 1000),
-    ("Leon Cupra 5F FL | 2.0 TSI 300HP", 600),
-    ("DQ381-7F DSG MQB-EVO", 600),
-    ("Loading sensors...", 800),
-    ("Ready.", 500),
-];
+/// Init log line as shown for `profile`: the `{model}` and `{gearbox}` lines of
+/// `INIT_MESSAGES` name the car.
+pub const fn boot_line(
+    line: &'static str,
+    profile: &VehicleProfile,
+) -> &'static str {
+    match line.as_bytes() {
+        b"{model}" => profile.model,
+        b"{gearbox}" => profile.gearbox.label(),
+        _ => line,
+    }
+}
 
 const SPINNER_CHARS: [char; 4] = ['|', '/', '-', '\\'];
 
@@ -51,7 +56,7 @@ pub fn draw_loading_frame<D>(
     let right_spinner = SPINNER_CHARS[(spinner_idx + 2) % SPINNER_CHARS.len()];
 
     let mut loading_text: String<32> = String::new();
-    let _ = write!(loading_text, "{left_spinner}  Loading shit  {right_spinner}");
+    let _ = write!(loading_text, "{left_spinner}  {LOADING_TITLE}  {right_spinner}");
     Text::with_text_style(&loading_text, TITLE_POS, TITLE_STYLE, CENTERED)
         .draw(display)
         .ok();
@@ -6,7 +6,7 @@ use embedded_graphics::text::Text;
 use heapless::String;
 
 use crate::profiling::{LOG_BUFFER, LogEntry};
-use crate::ui::{BLACK, GREEN, LABEL_FONT, WHITE, YELLOW};
+use crate::ui::{LABEL_FONT, Status, label_style, theme};
 
 /// Maximum number of log lines visible on screen at once.
 const VISIBLE_LINES: usize = 13;
@@ -21,11 +21,12 @@ pub fn draw_logs_page<D>(
 ) where
     D: DrawTarget<Color = embedded_graphics::pixelcolor::Rgb565>,
 {
-    let header_style = MonoTextStyle::new(LABEL_FONT, GREEN);
-    let footer_style = MonoTextStyle::new(LABEL_FONT, GREEN);
-    let scroll_style = MonoTextStyle::new(LABEL_FONT, YELLOW);
+    let theme = theme::active();
+    let header_style = MonoTextStyle::new(LABEL_FONT, theme.reading(Status::Normal));
+    let footer_style = header_style;
+    let scroll_style = MonoTextStyle::new(LABEL_FONT, theme.highlight);
 
-    display.clear(BLACK).ok();
+    display.clear(theme.background).ok();
 
     Text::new("LOGS", Point::new(4, 12), header_style).draw(display).ok();
 
@@ -33,7 +34,7 @@ pub fn draw_logs_page<D>(
         let total = buffer.count();
 
         if total == 0 {
-            let empty_style = MonoTextStyle::new(LABEL_FONT, WHITE);
+            let empty_style = label_style();
             Text::new("No log entries", Point::new(4, 120), empty_style)
                 .draw(display)
                 .ok();
@@ -75,7 +76,7 @@ pub fn draw_logs_page<D>(
             }
         }
     } else {
-        let busy_style = MonoTextStyle::new(LABEL_FONT, WHITE);
+        let busy_style = label_style();
         Text::new("Log buffer busy...", Point::new(4, 120), busy_style)
             .draw(display)
             .ok();
@@ -93,9 +94,9 @@ fn draw_log_entry<D>(
 ) where
     D: DrawTarget<Color = embedded_graphics::pixelcolor::Rgb565>,
 {
-    let level_color = entry.level.color();
+    let level_color = theme::active().reading(entry.level.status());
     let level_style = MonoTextStyle::new(LABEL_FONT, level_color);
-    let msg_style = MonoTextStyle::new(LABEL_FONT, WHITE);
+    let msg_style = label_style();
 
     let mut prefix: String<16> = String::new();
     let _ = write!(prefix, "[{}] {:05}", entry.level.prefix(), entry.timestamp_ms % 100_000);
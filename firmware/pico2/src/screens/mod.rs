@@ -1,11 +1,21 @@
 mod boot;
+mod fault;
+mod focus;
+mod graph;
 mod loading;
 mod logs;
 mod profiling;
+mod settings;
+mod stats;
 mod welcome;
 
 pub use boot::{clear_framebuffers, run_boot_sequence};
-pub use loading::{INIT_MESSAGES, MAX_VISIBLE_LINES, draw_loading_frame};
+pub use fault::{FaultReport, draw_fault_screen};
+pub use focus::draw_focus_page;
+pub use graph::draw_graph_page;
+pub use loading::{MAX_VISIBLE_LINES, boot_line, draw_loading_frame};
 pub use logs::draw_logs_page;
 pub use profiling::{ProfilingData, draw_profiling_page};
+pub use settings::draw_settings_page;
+pub use stats::draw_stats_page;
 pub use welcome::draw_welcome_frame;
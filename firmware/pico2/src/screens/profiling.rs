@@ -5,7 +5,7 @@ use embedded_graphics::prelude::*;
 use embedded_graphics::text::Text;
 use heapless::String;
 
-use crate::ui::{BLACK, GREEN, LABEL_FONT, WHITE, YELLOW};
+use crate::ui::{LABEL_FONT, Status, label_style, theme};
 
 #[derive(Clone, Copy, Default)]
 pub struct ProfilingData {
@@ -50,11 +50,12 @@ pub fn draw_profiling_page<D>(
 ) where
     D: DrawTarget<Color = embedded_graphics::pixelcolor::Rgb565>,
 {
-    let header_style = MonoTextStyle::new(LABEL_FONT, GREEN);
-    let value_style = MonoTextStyle::new(LABEL_FONT, WHITE);
-    let highlight_style = MonoTextStyle::new(LABEL_FONT, YELLOW);
+    let theme = theme::active();
+    let header_style = MonoTextStyle::new(LABEL_FONT, theme.reading(Status::Normal));
+    let value_style = label_style();
+    let highlight_style = MonoTextStyle::new(LABEL_FONT, theme.highlight);
 
-    display.clear(BLACK).ok();
+    display.clear(theme.background).ok();
 
     let col1 = 4;
     let col2 = 164;
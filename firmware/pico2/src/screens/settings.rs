@@ -0,0 +1,121 @@
+use core::fmt::Write;
+
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::Text;
+use heapless::String;
+
+use crate::config::SCREEN_WIDTH;
+use crate::state::{ItemKind, MenuState, Settings};
+use crate::ui::{LEFT_ALIGNED, RIGHT_ALIGNED, TITLE_FONT, caption_style, label_style, theme, title_style};
+
+const TITLE_POS: Point = Point::new(8, 22);
+const TITLE_RULE_Y: i32 = 30;
+
+const LIST_TOP: i32 = 34;
+const ROW_HEIGHT: i32 = 22;
+const VISIBLE_ROWS: usize = 8;
+/// Baseline offset of 10x20 text within a row.
+const ROW_TEXT_OFFSET: i32 = 16;
+
+const LABEL_X: i32 = 10;
+const VALUE_X: i32 = SCREEN_WIDTH as i32 - 10;
+
+const HINT_POS: Point = Point::new(8, 234);
+
+/// Settings menu: current level as a scrolling list with values on the right.
+///
+/// The row under the cursor is highlighted; while it is being edited its value
+/// is shown in the highlight colour between arrows and the hint line switches to edit controls.
+pub fn draw_settings_page<D>(
+    display: &mut D,
+    menu: &MenuState,
+    settings: &Settings,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let theme = theme::active();
+    display.clear(theme.background).ok();
+
+    let items = menu.items();
+    let cursor = menu.cursor();
+    let pending = menu.pending();
+    let mut s: String<32> = String::new();
+
+    Text::with_text_style(menu.title(), TITLE_POS, title_style(), LEFT_ALIGNED)
+        .draw(display)
+        .ok();
+    if items.len() > VISIBLE_ROWS {
+        let _ = write!(s, "{}/{}", cursor + 1, items.len());
+        Text::with_text_style(&s, Point::new(VALUE_X, TITLE_POS.y), caption_style(), RIGHT_ALIGNED)
+            .draw(display)
+            .ok();
+    }
+    Line::new(
+        Point::new(0, TITLE_RULE_Y),
+        Point::new(SCREEN_WIDTH as i32 - 1, TITLE_RULE_Y),
+    )
+    .into_styled(PrimitiveStyle::with_stroke(theme.label, 1))
+    .draw(display)
+    .ok();
+
+    // Keep the cursor row visible, scrolling one row at a time
+    let first = cursor.saturating_sub(VISIBLE_ROWS - 1);
+
+    for (row, (index, item)) in items.iter().enumerate().skip(first).take(VISIBLE_ROWS).enumerate() {
+        let top = LIST_TOP + row as i32 * ROW_HEIGHT;
+        let selected = index == cursor;
+        let editing = selected && pending.is_some();
+
+        if selected {
+            Rectangle::new(Point::new(0, top), Size::new(SCREEN_WIDTH, ROW_HEIGHT as u32))
+                .into_styled(PrimitiveStyle::with_fill(theme.selection))
+                .draw(display)
+                .ok();
+        }
+
+        let read_only = matches!(item.kind, ItemKind::Value { .. } | ItemKind::Text(_));
+        let label_color = if read_only && !selected {
+            theme.label
+        } else {
+            theme.text
+        };
+        let baseline = top + ROW_TEXT_OFFSET;
+        Text::with_text_style(
+            item.label,
+            Point::new(LABEL_X, baseline),
+            MonoTextStyle::new(TITLE_FONT, label_color),
+            LEFT_ALIGNED,
+        )
+        .draw(display)
+        .ok();
+
+        s.clear();
+        if editing {
+            let _ = s.push_str("< ");
+        }
+        let _ = item.write_value(settings, if selected { pending } else { None }, &mut s);
+        if editing {
+            let _ = s.push_str(" >");
+        }
+        Text::with_text_style(
+            &s,
+            Point::new(VALUE_X, baseline),
+            MonoTextStyle::new(TITLE_FONT, if editing { theme.highlight } else { theme.text }),
+            RIGHT_ALIGNED,
+        )
+        .draw(display)
+        .ok();
+    }
+
+    let hint = match (pending.is_some(), menu.is_top_level()) {
+        (true, _) => "Turn: change  Press: save  Hold: cancel",
+        (false, true) => "Turn: move  Press: select  Hold: exit",
+        (false, false) => "Turn: move  Press: select  Hold: back",
+    };
+    Text::with_text_style(hint, HINT_POS, label_style(), LEFT_ALIGNED)
+        .draw(display)
+        .ok();
+}
@@ -0,0 +1,182 @@
+use core::fmt::Write;
+
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::Text;
+use heapless::String;
+
+use crate::state::{SensorDescriptor, ZoneStats};
+use crate::ui::{CENTERED, LABEL_FONT, LEFT_ALIGNED, RIGHT_ALIGNED, caption_style, label_style, theme, title_style};
+use crate::units::UnitPrefs;
+
+const TITLE_POS: Point = Point::new(8, 24);
+const SESSION_POS: Point = Point::new(312, 14);
+const DURATION_POS: Point = Point::new(312, 28);
+
+const ROWS_TOP: i32 = 44;
+const ROWS_BOTTOM: i32 = 216;
+const MAX_ROW_H: i32 = 34;
+
+const RANGE_X: i32 = 8;
+const BAR_X: i32 = 84;
+const BAR_MAX_W: u32 = 140;
+const FIGURES_X: i32 = 312;
+const HINT_POS: Point = Point::new(160, 234);
+
+/// Time-in-zone bar chart of one sensor: a row per threshold band, lowest first,
+/// with its value range, a bar in the band's colour scaled to its share of the
+/// session, the time spent there and how often the reading entered it.
+///
+/// `current` marks the zone the reading is in while the live session is shown;
+/// `last` switches the caption to the last ended session.
+pub fn draw_stats_page<D>(
+    display: &mut D,
+    desc: &SensorDescriptor,
+    zones: &ZoneStats,
+    current: Option<usize>,
+    last: bool,
+    units: &UnitPrefs,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let theme = theme::active();
+    display.clear(theme.background).ok();
+
+    let mut s: String<32> = String::new();
+    let _ = write!(s, "{} ZONES", desc.label);
+    Text::with_text_style(&s, TITLE_POS, title_style(), LEFT_ALIGNED)
+        .draw(display)
+        .ok();
+
+    let caption = if last { "LAST SESSION" } else { "THIS SESSION" };
+    Text::with_text_style(caption, SESSION_POS, caption_style(), RIGHT_ALIGNED)
+        .draw(display)
+        .ok();
+    s.clear();
+    write_duration(&mut s, zones.total_ms());
+    Text::with_text_style(&s, DURATION_POS, label_style(), RIGHT_ALIGNED)
+        .draw(display)
+        .ok();
+
+    let edges = desc.thresholds();
+    let bands = edges.len() + 1;
+    let row_h = ((ROWS_BOTTOM - ROWS_TOP) / bands as i32).min(MAX_ROW_H);
+    let bar_h = (row_h - 8) as u32;
+
+    for band in 0..bands {
+        let top = ROWS_TOP + band as i32 * row_h;
+        let text_y = top + bar_h as i32 / 2 + 4;
+
+        s.clear();
+        write_band_range(&mut s, desc, units, band);
+        let range_color = if current == Some(band) { theme.text } else { theme.label };
+        Text::with_text_style(
+            &s,
+            Point::new(RANGE_X, text_y),
+            MonoTextStyle::new(LABEL_FONT, range_color),
+            LEFT_ALIGNED,
+        )
+        .draw(display)
+        .ok();
+
+        let color = theme.reading((desc.status_fn)(band_probe(desc, band)));
+        Rectangle::new(Point::new(BAR_X, top), Size::new(BAR_MAX_W, bar_h))
+            .into_styled(PrimitiveStyle::with_stroke(theme.label, 1))
+            .draw(display)
+            .ok();
+        let width = (zones.share(band) * BAR_MAX_W as f32) as u32;
+        if width > 0 {
+            Rectangle::new(Point::new(BAR_X, top), Size::new(width, bar_h))
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(display)
+                .ok();
+        }
+
+        s.clear();
+        write_duration(&mut s, zones.time_ms[band]);
+        let _ = write!(
+            s,
+            " {:>3}% x{}",
+            (zones.share(band) * 100.0 + 0.5) as u32,
+            zones.excursions[band]
+        );
+        Text::with_text_style(&s, Point::new(FIGURES_X, text_y), label_style(), RIGHT_ALIGNED)
+            .draw(display)
+            .ok();
+    }
+
+    Text::with_text_style(
+        "ENC: SENSOR  A: THIS/LAST  B: RESET",
+        HINT_POS,
+        caption_style(),
+        CENTERED,
+    )
+    .draw(display)
+    .ok();
+}
+
+/// Value range of `band` in display units: "<90", "90-100", ">110".
+fn write_band_range<const N: usize>(
+    s: &mut String<N>,
+    desc: &SensorDescriptor,
+    units: &UnitPrefs,
+    band: usize,
+) {
+    let edges = desc.thresholds();
+    let quantity = desc.quantity;
+    match (
+        band.checked_sub(1).map(|i| edges[i].value),
+        edges.get(band).map(|edge| edge.value),
+    ) {
+        (None, Some(upper)) => {
+            let _ = s.push('<');
+            let _ = units.write(quantity, upper, s);
+        }
+        (Some(lower), None) => {
+            let _ = s.push('>');
+            let _ = units.write(quantity, lower, s);
+        }
+        (Some(lower), Some(upper)) => {
+            let _ = units.write(quantity, lower, s);
+            let _ = s.push('-');
+            let _ = units.write(quantity, upper, s);
+        }
+        (None, None) => {
+            let _ = s.push_str("ALL");
+        }
+    }
+}
+
+/// A value inside `band`, to look up its colour: the middle of an inner band, or
+/// just past the edge of an outer one.
+fn band_probe(
+    desc: &SensorDescriptor,
+    band: usize,
+) -> f32 {
+    let edges = desc.thresholds();
+    let step = |value: f32| value.abs().max(1.0) * 0.01;
+    match (
+        band.checked_sub(1).map(|i| edges[i].value),
+        edges.get(band).map(|edge| edge.value),
+    ) {
+        (Some(lower), Some(upper)) => (lower + upper) / 2.0,
+        (None, Some(upper)) => upper - step(upper),
+        (Some(lower), None) => lower + step(lower),
+        (None, None) => desc.initial,
+    }
+}
+
+/// Session time as "45s", "12m05s" or "1h05m".
+fn write_duration<const N: usize>(
+    s: &mut String<N>,
+    ms: u32,
+) {
+    let seconds = ms / 1_000;
+    let _ = match seconds {
+        0..60 => write!(s, "{}s", seconds),
+        60..3_600 => write!(s, "{}m{:02}s", seconds / 60, seconds % 60),
+        _ => write!(s, "{}h{:02}m", seconds / 3_600, seconds / 60 % 60),
+    };
+}
@@ -0,0 +1,489 @@
+//! Alert rules and the manager that raises, queues and acknowledges them.
+//!
+//! Each rule watches one sensor against a limit. A breach has to last
+//! `hold_ms` before the alert is raised, and the alert only clears once the
+//! reading is back past the limit by `hysteresis`, so a value hovering at the
+//! limit raises it once instead of flickering. Raised alerts are queued by
+//! severity; the dashboard shows the head of the queue and the encoder
+//! acknowledges it. An acknowledged alert stays silent until its condition
+//! clears (re-arming it) or `SILENCE_MS` passes with the condition still present.
+
+use heapless::Vec;
+
+use crate::limits::{self, Limit};
+use crate::sensor_id::SensorId;
+use crate::settings::{Setting, Settings};
+use crate::theme::Status;
+
+/// How long an acknowledged alert stays silent while its condition persists.
+pub const SILENCE_MS: u64 = 60_000;
+
+/// Ordered from least to most urgent.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Warning => "WARNING",
+            Self::Critical => "CRITICAL",
+        }
+    }
+
+    /// Status whose theme colour and cue the alert is drawn with.
+    pub const fn status(self) -> Status {
+        match self {
+            Self::Warning => Status::Warning,
+            Self::Critical => Status::Critical,
+        }
+    }
+}
+
+/// Which side of the limit is a breach.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Above,
+    Below,
+}
+
+pub struct AlertRule {
+    pub sensor: SensorId,
+    /// Shown in the alert popup and logged, at most 18 characters.
+    pub message: &'static str,
+    pub severity: Severity,
+    pub direction: Direction,
+    /// Limit whose active value, in the sensor's base unit, counts as a breach when reached.
+    pub limit: Limit,
+    /// Distance back past the limit before the alert clears.
+    pub hysteresis: f32,
+    /// How long the breach has to last before the alert is raised.
+    pub hold_ms: u32,
+    /// On/off setting that enables the rule, if it can be switched off.
+    pub enabled_by: Option<Setting>,
+}
+
+impl AlertRule {
+    fn breached(
+        &self,
+        value: f32,
+    ) -> bool {
+        let limit = limits::get(self.limit);
+        match self.direction {
+            Direction::Above => value >= limit,
+            Direction::Below => value <= limit,
+        }
+    }
+
+    fn cleared(
+        &self,
+        value: f32,
+    ) -> bool {
+        let limit = limits::get(self.limit);
+        match self.direction {
+            Direction::Above => value < limit - self.hysteresis,
+            Direction::Below => value > limit + self.hysteresis,
+        }
+    }
+
+    fn enabled(
+        &self,
+        settings: &Settings,
+    ) -> bool {
+        self.enabled_by.is_none_or(|setting| settings.get(setting) != 0)
+    }
+}
+
+pub const RULES: &[AlertRule] = &[
+    AlertRule {
+        sensor: SensorId::Egt,
+        message: "DANGER TO MANIFOLD",
+        severity: Severity::Critical,
+        direction: Direction::Above,
+        limit: Limit::EgtDangerManifold,
+        hysteresis: 25.0,
+        hold_ms: 0,
+        enabled_by: Some(Setting::EgtAlarm),
+    },
+    AlertRule {
+        sensor: SensorId::Coolant,
+        message: "COOLANT OVERHEAT",
+        severity: Severity::Critical,
+        direction: Direction::Above,
+        limit: Limit::CoolantCritical,
+        hysteresis: 3.0,
+        hold_ms: 2_000,
+        enabled_by: None,
+    },
+    AlertRule {
+        sensor: SensorId::Oil,
+        message: "OIL OVERHEAT",
+        severity: Severity::Critical,
+        direction: Direction::Above,
+        limit: Limit::OilCritical,
+        hysteresis: 3.0,
+        hold_ms: 2_000,
+        enabled_by: None,
+    },
+    AlertRule {
+        sensor: SensorId::Dsg,
+        message: "DSG OVERHEAT",
+        severity: Severity::Critical,
+        direction: Direction::Above,
+        limit: Limit::DsgCritical,
+        hysteresis: 3.0,
+        hold_ms: 2_000,
+        enabled_by: None,
+    },
+    AlertRule {
+        sensor: SensorId::Afr,
+        message: "LEAN MIXTURE",
+        severity: Severity::Critical,
+        direction: Direction::Above,
+        limit: Limit::LambdaLeanCritical,
+        hysteresis: 0.02,
+        hold_ms: 1_000,
+        enabled_by: None,
+    },
+    AlertRule {
+        sensor: SensorId::Boost,
+        message: "OVERBOOST",
+        severity: Severity::Warning,
+        direction: Direction::Above,
+        limit: Limit::BoostCritical,
+        hysteresis: 0.1,
+        hold_ms: 500,
+        enabled_by: None,
+    },
+    AlertRule {
+        sensor: SensorId::Iat,
+        message: "INTAKE AIR HOT",
+        severity: Severity::Warning,
+        direction: Direction::Above,
+        limit: Limit::IatCritical,
+        hysteresis: 3.0,
+        hold_ms: 5_000,
+        enabled_by: None,
+    },
+    AlertRule {
+        sensor: SensorId::Battery,
+        message: "BATTERY LOW",
+        severity: Severity::Warning,
+        direction: Direction::Below,
+        limit: Limit::BattCritical,
+        hysteresis: 0.3,
+        hold_ms: 5_000,
+        enabled_by: None,
+    },
+];
+
+/// Upper bound on the number of rules, for fixed-size queues.
+pub const MAX_RULES: usize = 8;
+
+const _: () = assert!(RULES.len() <= MAX_RULES);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum AlertState {
+    Idle,
+    /// Breached since `since`, not yet for `hold_ms`.
+    Pending {
+        since: u64,
+    },
+    /// Raised and shown.
+    Active {
+        since: u64,
+    },
+    /// Raised, then acknowledged at `at`.
+    Silenced {
+        at: u64,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlertEventKind {
+    Raised,
+    Acknowledged,
+    /// Silence period ran out with the condition still present.
+    Reraised,
+    /// Condition cleared; the rule is armed again.
+    Cleared,
+}
+
+/// Something that happened to an alert, for the log.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AlertEvent {
+    /// Index into `RULES`.
+    pub rule: usize,
+    pub kind: AlertEventKind,
+}
+
+impl AlertEvent {
+    pub fn rule(&self) -> &'static AlertRule { &RULES[self.rule] }
+}
+
+pub type AlertEvents = Vec<AlertEvent, MAX_RULES>;
+
+pub struct AlertManager {
+    states: [AlertState; MAX_RULES],
+}
+
+impl AlertManager {
+    pub const fn new() -> Self {
+        Self {
+            states: [AlertState::Idle; MAX_RULES],
+        }
+    }
+
+    /// Advance every rule to `now_ms` with the latest readings. Disabled rules drop
+    /// their alert without an event, as if it had never been raised. A sensor reading
+    /// `None` is faulty: its rules see no breach and its raised alerts clear.
+    pub fn update(
+        &mut self,
+        now_ms: u64,
+        settings: &Settings,
+        value: impl Fn(SensorId) -> Option<f32>,
+    ) -> AlertEvents {
+        let mut events = AlertEvents::new();
+
+        for (index, (rule, state)) in RULES.iter().zip(self.states.iter_mut()).enumerate() {
+            if !rule.enabled(settings) {
+                *state = AlertState::Idle;
+                continue;
+            }
+
+            let value = value(rule.sensor);
+            let breached = value.is_some_and(|value| rule.breached(value));
+            let cleared = value.is_none_or(|value| rule.cleared(value));
+            let (next, kind) = match *state {
+                AlertState::Idle if breached => {
+                    if rule.hold_ms == 0 {
+                        (AlertState::Active { since: now_ms }, Some(AlertEventKind::Raised))
+                    } else {
+                        (AlertState::Pending { since: now_ms }, None)
+                    }
+                }
+                AlertState::Idle => (AlertState::Idle, None),
+                AlertState::Pending { .. } if !breached => (AlertState::Idle, None),
+                AlertState::Pending { since } if now_ms - since >= u64::from(rule.hold_ms) => {
+                    (AlertState::Active { since: now_ms }, Some(AlertEventKind::Raised))
+                }
+                AlertState::Pending { since } => (AlertState::Pending { since }, None),
+                AlertState::Active { .. } | AlertState::Silenced { .. } if cleared => {
+                    (AlertState::Idle, Some(AlertEventKind::Cleared))
+                }
+                AlertState::Silenced { at } if now_ms - at >= SILENCE_MS => {
+                    (AlertState::Active { since: now_ms }, Some(AlertEventKind::Reraised))
+                }
+                current => (current, None),
+            };
+
+            *state = next;
+            if let Some(kind) = kind {
+                let _ = events.push(AlertEvent { rule: index, kind });
+            }
+        }
+
+        events
+    }
+
+    /// Raised, unacknowledged alerts as `RULES` indices, most urgent first:
+    /// higher severity, then the longest-standing.
+    pub fn queue(&self) -> Vec<usize, MAX_RULES> {
+        let mut queue: Vec<(Severity, u64, usize), MAX_RULES> = Vec::new();
+        for (index, state) in self.states.iter().enumerate().take(RULES.len()) {
+            if let AlertState::Active { since } = *state {
+                let _ = queue.push((RULES[index].severity, since, index));
+            }
+        }
+        queue.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+        queue.iter().map(|&(_, _, index)| index).collect()
+    }
+
+    /// The alert to show, if any.
+    pub fn top(&self) -> Option<usize> { self.queue().first().copied() }
+
+    /// Acknowledge the alert at the head of the queue.
+    pub fn acknowledge_top(
+        &mut self,
+        now_ms: u64,
+    ) -> Option<AlertEvent> {
+        let rule = self.top()?;
+        self.states[rule] = AlertState::Silenced { at: now_ms };
+        Some(AlertEvent {
+            rule,
+            kind: AlertEventKind::Acknowledged,
+        })
+    }
+
+    /// Acknowledge every raised alert.
+    pub fn acknowledge_all(
+        &mut self,
+        now_ms: u64,
+    ) -> AlertEvents {
+        let mut events = AlertEvents::new();
+        while let Some(event) = self.acknowledge_top(now_ms) {
+            let _ = events.push(event);
+        }
+        events
+    }
+
+    /// Whether `sensor` has an alert that was acknowledged and is still silenced.
+    pub fn is_silenced(
+        &self,
+        sensor: SensorId,
+    ) -> bool {
+        RULES
+            .iter()
+            .zip(self.states)
+            .any(|(rule, state)| rule.sensor == sensor && matches!(state, AlertState::Silenced { .. }))
+    }
+}
+
+impl Default for AlertManager {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BOOST_CRITICAL, COOLANT_CRITICAL, EGT_DANGER_MANIFOLD};
+
+    fn index_of(sensor: SensorId) -> usize { RULES.iter().position(|rule| rule.sensor == sensor).unwrap() }
+
+    /// Every sensor at a harmless value except `sensor`, which reads `value`.
+    fn readings(
+        sensor: SensorId,
+        value: f32,
+    ) -> impl Fn(SensorId) -> Option<f32> {
+        move |id| {
+            Some(if id == sensor {
+                value
+            } else if id == SensorId::Battery {
+                14.0
+            } else if id == SensorId::Afr {
+                1.0
+            } else {
+                0.0
+            })
+        }
+    }
+
+    #[test]
+    fn breach_must_last_hold_time_before_raising() {
+        let mut alerts = AlertManager::new();
+        let settings = Settings::new();
+        let coolant = index_of(SensorId::Coolant);
+        let hot = readings(SensorId::Coolant, COOLANT_CRITICAL + 1.0);
+
+        assert!(alerts.update(0, &settings, &hot).is_empty());
+        assert!(alerts.update(1_999, &settings, &hot).is_empty());
+        assert_eq!(alerts.top(), None);
+
+        let events = alerts.update(2_000, &settings, &hot);
+        assert_eq!(
+            events.as_slice(),
+            &[AlertEvent {
+                rule: coolant,
+                kind: AlertEventKind::Raised,
+            }]
+        );
+        assert_eq!(alerts.top(), Some(coolant));
+    }
+
+    #[test]
+    fn short_spike_does_not_raise() {
+        let mut alerts = AlertManager::new();
+        let settings = Settings::new();
+        alerts.update(0, &settings, readings(SensorId::Coolant, COOLANT_CRITICAL + 1.0));
+        alerts.update(1_000, &settings, readings(SensorId::Coolant, COOLANT_CRITICAL - 1.0));
+        alerts.update(3_000, &settings, readings(SensorId::Coolant, COOLANT_CRITICAL + 1.0));
+        assert_eq!(alerts.top(), None, "hold timer restarts after the dip");
+    }
+
+    #[test]
+    fn alert_clears_only_past_hysteresis() {
+        let mut alerts = AlertManager::new();
+        let settings = Settings::new();
+        let egt = index_of(SensorId::Egt);
+        alerts.update(0, &settings, readings(SensorId::Egt, EGT_DANGER_MANIFOLD));
+        assert_eq!(alerts.top(), Some(egt));
+
+        alerts.update(100, &settings, readings(SensorId::Egt, EGT_DANGER_MANIFOLD - 10.0));
+        assert_eq!(alerts.top(), Some(egt), "inside the hysteresis band");
+
+        let events = alerts.update(200, &settings, readings(SensorId::Egt, EGT_DANGER_MANIFOLD - 30.0));
+        assert_eq!(events[0].kind, AlertEventKind::Cleared);
+        assert_eq!(alerts.top(), None);
+    }
+
+    #[test]
+    fn queue_orders_by_severity_then_age() {
+        let mut alerts = AlertManager::new();
+        let settings = Settings::new();
+        let values = |id| {
+            Some(match id {
+                SensorId::Boost => BOOST_CRITICAL + 0.1,
+                SensorId::Egt => EGT_DANGER_MANIFOLD + 10.0,
+                SensorId::Battery => 14.0,
+                SensorId::Afr => 1.0,
+                _ => 0.0,
+            })
+        };
+        alerts.update(0, &settings, values);
+        alerts.update(600, &settings, values);
+
+        assert_eq!(
+            alerts.queue().as_slice(),
+            &[index_of(SensorId::Egt), index_of(SensorId::Boost)]
+        );
+    }
+
+    #[test]
+    fn acknowledged_alert_stays_silent_until_rearmed_or_silence_expires() {
+        let mut alerts = AlertManager::new();
+        let settings = Settings::new();
+        let egt = index_of(SensorId::Egt);
+        let hot = readings(SensorId::Egt, EGT_DANGER_MANIFOLD + 10.0);
+
+        alerts.update(0, &settings, &hot);
+        assert_eq!(alerts.acknowledge_top(10).map(|e| e.rule), Some(egt));
+        assert_eq!(alerts.top(), None);
+        assert!(alerts.is_silenced(SensorId::Egt));
+
+        let events = alerts.update(10 + SILENCE_MS, &settings, &hot);
+        assert_eq!(events[0].kind, AlertEventKind::Reraised);
+        assert_eq!(alerts.top(), Some(egt));
+
+        // Clearing re-arms: the next breach raises again straight away
+        alerts.acknowledge_top(20 + SILENCE_MS);
+        alerts.update(30 + SILENCE_MS, &settings, readings(SensorId::Egt, 0.0));
+        let events = alerts.update(40 + SILENCE_MS, &settings, &hot);
+        assert_eq!(events[0].kind, AlertEventKind::Raised);
+    }
+
+    #[test]
+    fn faulty_sensor_clears_its_alert() {
+        let mut alerts = AlertManager::new();
+        let settings = Settings::new();
+        let hot = readings(SensorId::Egt, EGT_DANGER_MANIFOLD + 10.0);
+        alerts.update(0, &settings, &hot);
+        assert!(alerts.top().is_some());
+
+        let faulty = |id| if id == SensorId::Egt { None } else { hot(id) };
+        let events = alerts.update(100, &settings, faulty);
+        assert_eq!(events[0].kind, AlertEventKind::Cleared);
+        alerts.update(5_000, &settings, faulty);
+        assert_eq!(alerts.top(), None, "no breach without a reading");
+    }
+
+    #[test]
+    fn disabled_rule_never_raises() {
+        let mut alerts = AlertManager::new();
+        let mut settings = Settings::new();
+        settings.set(Setting::EgtAlarm, 0);
+        alerts.update(0, &settings, readings(SensorId::Egt, EGT_DANGER_MANIFOLD + 10.0));
+        assert_eq!(alerts.top(), None);
+    }
+}
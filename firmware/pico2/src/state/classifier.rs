@@ -0,0 +1,202 @@
+//! Debounced band classification of a sensor reading.
+//!
+//! The status functions map a raw value straight to a band, so a reading sitting
+//! on a threshold flips between two bands every sample. `BandClassifier` keeps the
+//! last band until the value is more than the nearest threshold's hysteresis away
+//! from it, and until the new band has held for that threshold's dwell time.
+
+/// A band edge with the tuning used when a reading crosses it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Threshold {
+    /// Edge in the sensor's base unit.
+    pub value: f32,
+    /// How far past the edge a reading has to go before the band changes.
+    pub hysteresis: f32,
+    /// How long the new band has to hold before it is reported.
+    pub dwell_ms: u32,
+}
+
+impl Threshold {
+    pub const fn new(
+        value: f32,
+        hysteresis: f32,
+        dwell_ms: u32,
+    ) -> Self {
+        Self {
+            value,
+            hysteresis,
+            dwell_ms,
+        }
+    }
+
+    /// The threshold closest to `value`; its tuning applies to the band change in progress.
+    fn nearest(
+        thresholds: &[Self],
+        value: f32,
+    ) -> Option<&Self> {
+        thresholds
+            .iter()
+            .min_by(|a, b| (a.value - value).abs().total_cmp(&(b.value - value).abs()))
+    }
+}
+
+/// Holds the reported band of one reading. Generic over the band type so the same
+/// logic debounces a `Status` and a plain critical flag.
+#[derive(Clone, Copy, Debug)]
+pub struct BandClassifier<B> {
+    band: Option<B>,
+    /// Band the value has moved to, and when it first did.
+    candidate: Option<(B, u64)>,
+}
+
+impl<B: Copy + PartialEq> BandClassifier<B> {
+    pub const fn new() -> Self {
+        Self {
+            band: None,
+            candidate: None,
+        }
+    }
+
+    /// Classify `value` at `now_ms` and return the band to report. The first value is
+    /// taken as is; after that a change needs to clear hysteresis and dwell.
+    pub fn classify(
+        &mut self,
+        value: f32,
+        now_ms: u64,
+        thresholds: &[Threshold],
+        band_of: impl Fn(f32) -> B,
+    ) -> B {
+        let raw = band_of(value);
+        let Some(current) = self.band else {
+            self.band = Some(raw);
+            return raw;
+        };
+
+        let (hysteresis, dwell_ms) =
+            Threshold::nearest(thresholds, value).map_or((0.0, 0), |t| (t.hysteresis, t.dwell_ms));
+        // Still within hysteresis of the current band: stay
+        if raw == current || band_of(value - hysteresis) == current || band_of(value + hysteresis) == current {
+            self.candidate = None;
+            return current;
+        }
+
+        let since = match self.candidate {
+            Some((band, since)) if band == raw => since,
+            _ => now_ms,
+        };
+        if now_ms - since >= u64::from(dwell_ms) {
+            self.band = Some(raw);
+            self.candidate = None;
+            raw
+        } else {
+            self.candidate = Some((raw, since));
+            current
+        }
+    }
+
+    /// Last reported band, if anything has been classified yet.
+    pub const fn band(&self) -> Option<B> { self.band }
+
+    /// Forget the reported band, so the next value is taken as is.
+    pub fn reset(&mut self) { *self = Self::new(); }
+}
+
+impl<B: Copy + PartialEq> Default for BandClassifier<B> {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum Band {
+        Low,
+        Mid,
+        High,
+    }
+
+    const EDGES: &[Threshold] = &[Threshold::new(50.0, 2.0, 0), Threshold::new(90.0, 1.0, 500)];
+
+    fn band(value: f32) -> Band {
+        if value > 90.0 {
+            Band::High
+        } else if value >= 50.0 {
+            Band::Mid
+        } else {
+            Band::Low
+        }
+    }
+
+    #[test]
+    fn first_value_is_taken_as_is() {
+        let mut classifier = BandClassifier::new();
+        assert_eq!(classifier.band(), None);
+        assert_eq!(classifier.classify(90.5, 0, EDGES, band), Band::High);
+    }
+
+    #[test]
+    fn value_hovering_at_threshold_does_not_flip() {
+        let mut classifier = BandClassifier::new();
+        classifier.classify(40.0, 0, EDGES, band);
+        for (i, value) in [49.9, 50.0, 50.5, 49.5, 51.9, 48.5].into_iter().enumerate() {
+            assert_eq!(classifier.classify(value, i as u64 * 100, EDGES, band), Band::Low);
+        }
+        assert_eq!(classifier.classify(52.5, 1_000, EDGES, band), Band::Mid);
+        assert_eq!(
+            classifier.classify(48.5, 1_100, EDGES, band),
+            Band::Mid,
+            "back inside hysteresis"
+        );
+        assert_eq!(classifier.classify(47.5, 1_200, EDGES, band), Band::Low);
+    }
+
+    #[test]
+    fn band_change_waits_for_dwell_time() {
+        let mut classifier = BandClassifier::new();
+        classifier.classify(80.0, 0, EDGES, band);
+        assert_eq!(classifier.classify(92.0, 1_000, EDGES, band), Band::Mid);
+        assert_eq!(classifier.classify(92.0, 1_499, EDGES, band), Band::Mid);
+        assert_eq!(classifier.classify(92.0, 1_500, EDGES, band), Band::High);
+    }
+
+    #[test]
+    fn dwell_restarts_when_value_falls_back() {
+        let mut classifier = BandClassifier::new();
+        classifier.classify(80.0, 0, EDGES, band);
+        classifier.classify(92.0, 100, EDGES, band);
+        classifier.classify(80.0, 400, EDGES, band);
+        assert_eq!(classifier.classify(92.0, 700, EDGES, band), Band::Mid);
+        assert_eq!(classifier.classify(92.0, 1_199, EDGES, band), Band::Mid);
+        assert_eq!(classifier.classify(92.0, 1_200, EDGES, band), Band::High);
+    }
+
+    #[test]
+    fn large_jump_skips_intermediate_bands() {
+        let mut classifier = BandClassifier::new();
+        classifier.classify(20.0, 0, EDGES, band);
+        assert_eq!(classifier.classify(120.0, 10, EDGES, band), Band::Low);
+        assert_eq!(classifier.classify(120.0, 510, EDGES, band), Band::High);
+    }
+
+    #[test]
+    fn debounces_a_flag() {
+        let mut classifier = BandClassifier::new();
+        let critical = |value: f32| value > 90.0;
+        classifier.classify(85.0, 0, EDGES, critical);
+        assert!(!classifier.classify(90.5, 100, EDGES, critical), "inside hysteresis");
+        assert!(!classifier.classify(91.5, 200, EDGES, critical), "dwelling");
+        assert!(classifier.classify(91.5, 700, EDGES, critical));
+        assert!(classifier.classify(90.0, 800, EDGES, critical), "inside hysteresis");
+        assert!(classifier.classify(88.5, 900, EDGES, critical), "dwelling");
+        assert!(!classifier.classify(88.5, 1_400, EDGES, critical));
+    }
+
+    #[test]
+    fn reset_forgets_band() {
+        let mut classifier = BandClassifier::new();
+        classifier.classify(95.0, 0, EDGES, band);
+        classifier.reset();
+        assert_eq!(classifier.classify(40.0, 10, EDGES, band), Band::Low);
+    }
+}
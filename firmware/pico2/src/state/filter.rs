@@ -0,0 +1,211 @@
+//! Smoothing between a sensor's source and its displayed value.
+//!
+//! Each sensor picks one `Filter`; `SensorFilter` holds its running state and is
+//! fed every new sample with its timestamp, so the time-based filters behave the
+//! same at any sample rate.
+
+/// Longest median window.
+pub const MAX_MEDIAN: usize = 7;
+
+/// How a sensor's samples are smoothed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+    /// Show samples as they arrive.
+    None,
+    /// Exponential moving average with time constant `tau_ms`: a step reaches ~63 % after `tau_ms`.
+    Ema { tau_ms: u32 },
+    /// Median of the last `n` samples; removes single-sample spikes without lag on steps
+    /// longer than `n / 2` samples. `n` is odd and at most `MAX_MEDIAN`.
+    Median { n: u8 },
+    /// Follow the samples, moving by at most `per_s` base units per second.
+    SlewLimit { per_s: f32 },
+}
+
+impl Filter {
+    pub const fn is_valid(&self) -> bool {
+        match *self {
+            Self::None => true,
+            Self::Ema { tau_ms } => tau_ms > 0,
+            Self::Median { n } => n % 2 == 1 && n as usize <= MAX_MEDIAN,
+            Self::SlewLimit { per_s } => per_s > 0.0,
+        }
+    }
+}
+
+/// A sensor's filter, and which value its alarms look at.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FilterSpec {
+    pub filter: Filter,
+    /// Critical flag, alerts and max/min tracking use the unfiltered sample, so a short
+    /// transient the filter smooths away is still caught.
+    pub raw_alarms: bool,
+}
+
+impl FilterSpec {
+    pub const NONE: Self = Self::new(Filter::None, false);
+
+    pub const fn new(
+        filter: Filter,
+        raw_alarms: bool,
+    ) -> Self {
+        Self { filter, raw_alarms }
+    }
+}
+
+/// Running state of one sensor's `Filter`.
+#[derive(Clone, Copy, Debug)]
+pub struct SensorFilter {
+    /// Last output and the time of the sample it came from.
+    last: Option<(f32, u64)>,
+    window: [f32; MAX_MEDIAN],
+    index: usize,
+    count: usize,
+}
+
+impl SensorFilter {
+    pub const fn new() -> Self {
+        Self {
+            last: None,
+            window: [0.0; MAX_MEDIAN],
+            index: 0,
+            count: 0,
+        }
+    }
+
+    /// Feed the sample `raw` taken at `now_ms` and return the filtered value. The
+    /// first sample after a reset passes through unchanged.
+    pub fn apply(
+        &mut self,
+        filter: &Filter,
+        raw: f32,
+        now_ms: u64,
+    ) -> f32 {
+        let out = match (*filter, self.last) {
+            (Filter::None, _) | (Filter::Ema { .. } | Filter::SlewLimit { .. }, None) => raw,
+            (Filter::Ema { tau_ms }, Some((last, since))) => {
+                // dt / (tau + dt) is the discrete EMA weight; it needs no exp() and
+                // stays stable for any frame time
+                let dt = now_ms.saturating_sub(since) as f32;
+                last + (raw - last) * dt / (tau_ms as f32 + dt)
+            }
+            (Filter::SlewLimit { per_s }, Some((last, since))) => {
+                let step = per_s * now_ms.saturating_sub(since) as f32 / 1_000.0;
+                raw.clamp(last - step, last + step)
+            }
+            (Filter::Median { n }, _) => self.median(raw, n as usize),
+        };
+        self.last = Some((out, now_ms));
+        out
+    }
+
+    fn median(
+        &mut self,
+        raw: f32,
+        n: usize,
+    ) -> f32 {
+        let n = n.clamp(1, MAX_MEDIAN);
+        self.window[self.index % n] = raw;
+        self.index = (self.index + 1) % n;
+        self.count = (self.count + 1).min(n);
+
+        let mut sorted = [0.0; MAX_MEDIAN];
+        let sorted = &mut sorted[..self.count];
+        sorted.copy_from_slice(&self.window[..self.count]);
+        sorted.sort_unstable_by(f32::total_cmp);
+        sorted[self.count / 2]
+    }
+
+    /// Forget all samples, e.g. after the source restarted.
+    pub const fn reset(&mut self) { *self = Self::new(); }
+}
+
+impl Default for SensorFilter {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_clock::FakeClock;
+
+    #[test]
+    fn none_passes_samples_through() {
+        let mut state = SensorFilter::new();
+        assert_eq!(state.apply(&Filter::None, 1.5, 0), 1.5);
+        assert_eq!(state.apply(&Filter::None, -3.0, 10), -3.0);
+    }
+
+    #[test]
+    fn ema_step_response_follows_time_constant() {
+        let filter = Filter::Ema { tau_ms: 500 };
+        for step_ms in [5, 20, 50] {
+            let mut state = SensorFilter::new();
+            let mut clock = FakeClock::new();
+            let mut out = 0.0;
+            // One frame at 0 to settle on the starting value
+            clock.run(step_ms, step_ms, |now| out = state.apply(&filter, 0.0, now));
+            clock.run(step_ms, 500, |now| out = state.apply(&filter, 1.0, now));
+            assert!((0.55..0.68).contains(&out), "{step_ms} ms: {out}");
+            clock.run(step_ms, 5_000, |now| out = state.apply(&filter, 1.0, now));
+            assert!(out > 0.999, "{step_ms} ms: {out}");
+        }
+    }
+
+    #[test]
+    fn median_removes_single_spikes() {
+        let filter = Filter::Median { n: 5 };
+        let mut state = SensorFilter::new();
+        let outputs: [f32; 8] = core::array::from_fn(|i| {
+            let raw = match i {
+                3 => 3.0,
+                5 | 6 => -1.0,
+                _ => 1.0,
+            };
+            state.apply(&filter, raw, i as u64 * 10)
+        });
+        assert_eq!(outputs[3], 1.0, "spike");
+        assert_eq!(outputs[6], 1.0, "two-sample dip is under half the window");
+        assert_eq!(outputs, [1.0; 8]);
+    }
+
+    #[test]
+    fn median_follows_a_step_after_half_the_window() {
+        let filter = Filter::Median { n: 3 };
+        let mut state = SensorFilter::new();
+        for (i, (raw, expected)) in [(0.0, 0.0), (0.0, 0.0), (5.0, 0.0), (5.0, 5.0)].into_iter().enumerate() {
+            assert_eq!(state.apply(&filter, raw, i as u64), expected, "sample {i}");
+        }
+    }
+
+    #[test]
+    fn slew_limit_caps_rate_of_change() {
+        let filter = Filter::SlewLimit { per_s: 100.0 };
+        let mut state = SensorFilter::new();
+        let mut clock = FakeClock::new();
+        let mut out = 0.0;
+        clock.run(10, 10, |now| out = state.apply(&filter, 200.0, now));
+        clock.run(10, 1_000, |now| out = state.apply(&filter, 500.0, now));
+        assert_eq!(out, 300.0);
+        clock.run(10, 1_000, |now| out = state.apply(&filter, 250.0, now));
+        assert_eq!(out, 250.0, "reaches the target");
+    }
+
+    #[test]
+    fn reset_passes_next_sample_through() {
+        let filter = Filter::Ema { tau_ms: 1_000 };
+        let mut state = SensorFilter::new();
+        state.apply(&filter, 10.0, 0);
+        state.apply(&filter, 20.0, 100);
+        state.reset();
+        assert_eq!(state.apply(&filter, 20.0, 200), 20.0);
+    }
+
+    #[test]
+    fn validates_parameters() {
+        assert!(Filter::Median { n: 5 }.is_valid());
+        assert!(!Filter::Median { n: 4 }.is_valid());
+        assert!(!Filter::Median { n: 9 }.is_valid());
+        assert!(!Filter::Ema { tau_ms: 0 }.is_valid());
+        assert!(!Filter::SlewLimit { per_s: 0.0 }.is_valid());
+    }
+}
@@ -0,0 +1,84 @@
+use super::{SensorId, ZOOM_POINTS, Zoom};
+
+/// Maximum number of sensors overlaid on the graph page at once.
+pub const MAX_TRACES: usize = 4;
+
+/// Selection and cursor of the overlay graph page.
+///
+/// Traces live in fixed slots so a sensor keeps its colour when another one is
+/// hidden. The cursor is the age of the point it points at, in points of the
+/// zoom window (0 = newest).
+pub struct GraphView {
+    traces: [Option<SensorId>; MAX_TRACES],
+    /// Sensor that X steps through and A shows or hides.
+    pub highlighted: SensorId,
+    pub cursor_age: usize,
+    pub zoom: Zoom,
+}
+
+impl GraphView {
+    /// Starts with the heat-soak set: intake, exhaust, boost and coolant.
+    pub const fn new() -> Self {
+        Self {
+            traces: [
+                Some(SensorId::Iat),
+                Some(SensorId::Egt),
+                Some(SensorId::Boost),
+                Some(SensorId::Coolant),
+            ],
+            highlighted: SensorId::Iat,
+            cursor_age: 0,
+            zoom: Zoom::TenMinutes,
+        }
+    }
+
+    /// Trace slots in order; the slot index picks the trace colour.
+    #[inline]
+    pub const fn traces(&self) -> &[Option<SensorId>; MAX_TRACES] { &self.traces }
+
+    #[inline]
+    pub fn is_shown(
+        &self,
+        id: SensorId,
+    ) -> bool {
+        self.traces.contains(&Some(id))
+    }
+
+    pub fn highlight_next(&mut self) {
+        let next = (self.highlighted.index() + 1) % SensorId::COUNT;
+        self.highlighted = SensorId::from_index(next).unwrap_or(self.highlighted);
+    }
+
+    /// Hide the highlighted sensor, or show it in the first free slot.
+    /// Returns `false` if it could not be shown because all slots are taken.
+    pub fn toggle_highlighted(&mut self) -> bool {
+        let id = Some(self.highlighted);
+        if let Some(slot) = self.traces.iter_mut().find(|slot| **slot == id) {
+            *slot = None;
+            return true;
+        }
+        match self.traces.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = id;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Step to the next zoom window, wrapping back to the narrowest; the cursor keeps its point index.
+    pub const fn cycle_zoom(&mut self) { self.zoom = self.zoom.next(); }
+
+    /// Move the cursor by `steps` points towards older data (negative = newer).
+    #[inline]
+    pub fn move_cursor(
+        &mut self,
+        steps: i32,
+    ) {
+        self.cursor_age = (self.cursor_age as i32 + steps).clamp(0, ZOOM_POINTS as i32 - 1) as usize;
+    }
+}
+
+impl Default for GraphView {
+    fn default() -> Self { Self::new() }
+}
@@ -0,0 +1,544 @@
+//! Long-term, multi-resolution history of one sensor.
+//!
+//! Readings are folded into 1 s buckets for the last minute, 10 s buckets for the
+//! last hour and 1 min buckets for the session (the last four hours). Every bucket
+//! keeps min, max and average, so a short spike still shows in a coarse bucket.
+//! Buckets are stored as `i16` steps of a per-sensor resolution to keep the three
+//! tiers of every sensor within a few KB.
+
+/// Number of plot points a `Zoom` window is split into.
+pub const ZOOM_POINTS: usize = 60;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bucket {
+    pub min: f32,
+    pub max: f32,
+    pub avg: f32,
+}
+
+impl Bucket {
+    /// Combine equally long buckets.
+    fn merge(buckets: impl Iterator<Item = Self>) -> Option<Self> {
+        let mut merged: Option<Self> = None;
+        let mut count = 0u32;
+        let mut sum = 0.0f32;
+        for bucket in buckets {
+            count += 1;
+            sum += bucket.avg;
+            merged = Some(match merged {
+                None => bucket,
+                Some(m) => {
+                    Self {
+                        min: m.min.min(bucket.min),
+                        max: m.max.max(bucket.max),
+                        avg: 0.0,
+                    }
+                }
+            });
+        }
+        merged.map(|m| {
+            Self {
+                avg: sum / count as f32,
+                ..m
+            }
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tier {
+    Minute,
+    Hour,
+    Session,
+}
+
+impl Tier {
+    pub const ALL: [Self; 3] = [Self::Minute, Self::Hour, Self::Session];
+
+    /// Time one bucket of the tier covers.
+    pub const fn bucket_ms(self) -> u32 {
+        match self {
+            Self::Minute => 1_000,
+            Self::Hour => 10_000,
+            Self::Session => 60_000,
+        }
+    }
+
+    pub const fn capacity(self) -> usize {
+        match self {
+            Self::Minute => 60,
+            Self::Hour => 360,
+            Self::Session => 240,
+        }
+    }
+
+    /// Time the tier covers when full.
+    pub const fn span_ms(self) -> u32 { self.bucket_ms() * self.capacity() as u32 }
+
+    /// Buckets of the tier below that make one bucket of this tier.
+    const fn fold(self) -> u32 {
+        match self {
+            Self::Minute => 1,
+            Self::Hour => Self::Hour.bucket_ms() / Self::Minute.bucket_ms(),
+            Self::Session => Self::Session.bucket_ms() / Self::Hour.bucket_ms(),
+        }
+    }
+}
+
+/// A slice of history to plot: `points` points of `per_point` buckets each from `tier`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Window {
+    pub tier: Tier,
+    pub per_point: usize,
+    pub points: usize,
+}
+
+impl Window {
+    /// The finest window that covers `span_ms` in `points` points.
+    pub fn covering(
+        span_ms: u32,
+        points: usize,
+    ) -> Self {
+        let tier = Tier::ALL
+            .into_iter()
+            .find(|tier| tier.span_ms() >= span_ms)
+            .unwrap_or(Tier::Session);
+        let bucket_ms = tier.bucket_ms() as usize;
+        let per_point = (span_ms as usize).div_ceil(points * bucket_ms).max(1);
+        let points = points.min(tier.capacity() / per_point);
+        Self {
+            tier,
+            per_point,
+            points,
+        }
+    }
+
+    /// Time one point covers.
+    pub const fn point_ms(&self) -> u32 { self.tier.bucket_ms() * self.per_point as u32 }
+
+    /// Age of point `age`, counted back from the newest.
+    pub const fn age_ms(
+        &self,
+        age: usize,
+    ) -> u32 {
+        self.point_ms() * age as u32
+    }
+}
+
+/// Preset windows the focus and graph pages step through.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Zoom {
+    OneMinute,
+    TenMinutes,
+    OneHour,
+    Session,
+}
+
+impl Zoom {
+    pub const fn span_ms(self) -> u32 {
+        match self {
+            Self::OneMinute => 60_000,
+            Self::TenMinutes => 600_000,
+            Self::OneHour => 3_600_000,
+            Self::Session => Tier::Session.span_ms(),
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::OneMinute => "1 MIN",
+            Self::TenMinutes => "10 MIN",
+            Self::OneHour => "1 H",
+            Self::Session => "4 H",
+        }
+    }
+
+    /// Next wider window, wrapping back to the narrowest.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::OneMinute => Self::TenMinutes,
+            Self::TenMinutes => Self::OneHour,
+            Self::OneHour => Self::Session,
+            Self::Session => Self::OneMinute,
+        }
+    }
+
+    pub fn window(self) -> Window { Window::covering(self.span_ms(), ZOOM_POINTS) }
+}
+
+/// Bucket in resolution steps.
+#[derive(Clone, Copy, Debug)]
+struct Packed {
+    min: i16,
+    max: i16,
+    avg: i16,
+}
+
+impl Packed {
+    const ZERO: Self = Self { min: 0, max: 0, avg: 0 };
+}
+
+/// Ring of the newest `N` buckets of one tier.
+struct Ring<const N: usize> {
+    buckets: [Packed; N],
+    /// Slot the next bucket goes into.
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> Ring<N> {
+    const fn new() -> Self {
+        Self {
+            buckets: [Packed::ZERO; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn push(
+        &mut self,
+        bucket: Packed,
+    ) {
+        self.buckets[self.head] = bucket;
+        self.head = (self.head + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    /// Bucket `age` steps back from the newest.
+    fn get(
+        &self,
+        age: usize,
+    ) -> Option<Packed> {
+        (age < self.len).then(|| self.buckets[(self.head + N - 1 - age) % N])
+    }
+}
+
+/// Bucket being filled.
+#[derive(Clone, Copy, Debug)]
+struct Accumulator {
+    min: f32,
+    max: f32,
+    sum: f32,
+    count: u32,
+}
+
+impl Accumulator {
+    const EMPTY: Self = Self {
+        min: f32::MAX,
+        max: f32::MIN,
+        sum: 0.0,
+        count: 0,
+    };
+
+    fn add(
+        &mut self,
+        min: f32,
+        max: f32,
+        avg: f32,
+    ) {
+        self.min = self.min.min(min);
+        self.max = self.max.max(max);
+        self.sum += avg;
+        self.count += 1;
+    }
+
+    fn take(&mut self) -> Bucket {
+        let bucket = Bucket {
+            min: self.min,
+            max: self.max,
+            avg: self.sum / self.count.max(1) as f32,
+        };
+        *self = Self::EMPTY;
+        bucket
+    }
+}
+
+pub struct TieredHistory {
+    /// Value of one stored step.
+    resolution: f32,
+    minute: Ring<{ Tier::Minute.capacity() }>,
+    hour: Ring<{ Tier::Hour.capacity() }>,
+    session: Ring<{ Tier::Session.capacity() }>,
+    /// Open bucket of each tier, in `Tier::ALL` order.
+    open: [Accumulator; 3],
+    /// Start of the open minute-tier bucket.
+    bucket_start_ms: Option<u64>,
+}
+
+impl TieredHistory {
+    pub const fn new(resolution: f32) -> Self {
+        Self {
+            resolution,
+            minute: Ring::new(),
+            hour: Ring::new(),
+            session: Ring::new(),
+            open: [Accumulator::EMPTY; 3],
+            bucket_start_ms: None,
+        }
+    }
+
+    /// Fold in a reading taken at `now_ms`. A bucket closes on the first reading
+    /// after its time is up; longer gaps (a stalled frame) are not filled in.
+    pub fn update(
+        &mut self,
+        value: f32,
+        now_ms: u64,
+    ) {
+        let start = *self.bucket_start_ms.get_or_insert(now_ms);
+        if now_ms - start >= u64::from(Tier::Minute.bucket_ms()) {
+            self.close_minute_bucket();
+            self.bucket_start_ms = Some(now_ms);
+        }
+        self.open[0].add(value, value, value);
+    }
+
+    fn close_minute_bucket(&mut self) {
+        for (index, tier) in Tier::ALL.into_iter().enumerate() {
+            let bucket = self.open[index].take();
+            let packed = self.pack(bucket);
+            match tier {
+                Tier::Minute => self.minute.push(packed),
+                Tier::Hour => self.hour.push(packed),
+                Tier::Session => self.session.push(packed),
+            }
+
+            // Fold the bucket upwards as stored, so every tier rounds the same way
+            let bucket = self.unpack(packed);
+            let Some(above) = Tier::ALL.get(index + 1) else {
+                break;
+            };
+            self.open[index + 1].add(bucket.min, bucket.max, bucket.avg);
+            if self.open[index + 1].count < above.fold() {
+                break;
+            }
+        }
+    }
+
+    /// Point `age` of `window`, counted back from the newest; `None` past the recorded history.
+    pub fn point(
+        &self,
+        window: Window,
+        age: usize,
+    ) -> Option<Bucket> {
+        if age >= window.points {
+            return None;
+        }
+        let first = age * window.per_point;
+        self.bucket(window.tier, first)?;
+        Bucket::merge((first..first + window.per_point).map_while(|i| self.bucket(window.tier, i)))
+    }
+
+    /// Number of points of `window` that hold data.
+    pub fn len(
+        &self,
+        window: Window,
+    ) -> usize {
+        let buckets = match window.tier {
+            Tier::Minute => self.minute.len,
+            Tier::Hour => self.hour.len,
+            Tier::Session => self.session.len,
+        };
+        buckets.div_ceil(window.per_point).min(window.points)
+    }
+
+    pub fn is_empty(&self) -> bool { self.minute.len == 0 }
+
+    /// Lowest min and highest max over the recorded points of `window`.
+    pub fn range(
+        &self,
+        window: Window,
+    ) -> Option<(f32, f32)> {
+        (0..self.len(window))
+            .filter_map(|age| self.point(window, age))
+            .fold(None, |range, b| {
+                match range {
+                    None => Some((b.min, b.max)),
+                    Some((lo, hi)) => Some((lo.min(b.min), hi.max(b.max))),
+                }
+            })
+    }
+
+    pub fn reset(&mut self) { *self = Self::new(self.resolution); }
+
+    fn bucket(
+        &self,
+        tier: Tier,
+        age: usize,
+    ) -> Option<Bucket> {
+        let packed = match tier {
+            Tier::Minute => self.minute.get(age),
+            Tier::Hour => self.hour.get(age),
+            Tier::Session => self.session.get(age),
+        }?;
+        Some(self.unpack(packed))
+    }
+
+    fn pack(
+        &self,
+        bucket: Bucket,
+    ) -> Packed {
+        let steps = |value: f32| {
+            let scaled = value / self.resolution;
+            let rounded = if scaled >= 0.0 { scaled + 0.5 } else { scaled - 0.5 };
+            rounded.clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16
+        };
+        Packed {
+            min: steps(bucket.min),
+            max: steps(bucket.max),
+            avg: steps(bucket.avg),
+        }
+    }
+
+    fn unpack(
+        &self,
+        packed: Packed,
+    ) -> Bucket {
+        Bucket {
+            min: f32::from(packed.min) * self.resolution,
+            max: f32::from(packed.max) * self.resolution,
+            avg: f32::from(packed.avg) * self.resolution,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_clock::FakeClock;
+
+    #[test]
+    fn zoom_windows_map_to_tiers() {
+        let windows = [Zoom::OneMinute, Zoom::TenMinutes, Zoom::OneHour, Zoom::Session].map(Zoom::window);
+        assert_eq!(
+            windows[0],
+            Window {
+                tier: Tier::Minute,
+                per_point: 1,
+                points: 60
+            }
+        );
+        assert_eq!(
+            windows[1],
+            Window {
+                tier: Tier::Hour,
+                per_point: 1,
+                points: 60
+            }
+        );
+        assert_eq!(
+            windows[2],
+            Window {
+                tier: Tier::Hour,
+                per_point: 6,
+                points: 60
+            }
+        );
+        assert_eq!(
+            windows[3],
+            Window {
+                tier: Tier::Session,
+                per_point: 4,
+                points: 60
+            }
+        );
+        for (zoom, window) in [Zoom::OneMinute, Zoom::TenMinutes, Zoom::OneHour, Zoom::Session]
+            .into_iter()
+            .zip(windows)
+        {
+            assert_eq!(window.age_ms(window.points), zoom.span_ms());
+        }
+    }
+
+    #[test]
+    fn buckets_fill_every_tier() {
+        let mut history = TieredHistory::new(0.1);
+        FakeClock::new().run(100, 130_100, |now| history.update(20.0, now));
+
+        let minute = Zoom::OneMinute.window();
+        assert_eq!(history.len(minute), 60);
+        assert_eq!(history.len(Zoom::TenMinutes.window()), 13);
+        assert_eq!(
+            history.len(Window {
+                tier: Tier::Session,
+                per_point: 1,
+                points: 240
+            }),
+            2
+        );
+        assert_eq!(history.point(minute, 0).map(|b| b.avg), Some(20.0));
+    }
+
+    #[test]
+    fn spikes_survive_downsampling() {
+        let mut history = TieredHistory::new(0.1);
+        // One 100 ms spike to 120 in an otherwise flat two minutes
+        FakeClock::new().run(100, 120_100, |now| {
+            history.update(if now == 30_000 { 120.0 } else { 90.0 }, now);
+        });
+
+        let session = Window {
+            tier: Tier::Session,
+            per_point: 1,
+            points: 240,
+        };
+        let oldest = history.point(session, 1).unwrap();
+        assert_eq!(oldest.max, 120.0);
+        assert_eq!(oldest.min, 90.0);
+        assert!(oldest.avg < 90.1, "spike barely moves the average: {}", oldest.avg);
+    }
+
+    #[test]
+    fn point_merges_buckets_and_stops_at_history_end() {
+        let mut history = TieredHistory::new(0.1);
+        FakeClock::new().run(100, 4_100, |now| history.update((now / 1_000) as f32, now));
+
+        let pairs = Window {
+            tier: Tier::Minute,
+            per_point: 2,
+            points: 30,
+        };
+        assert_eq!(history.len(pairs), 2);
+        assert_eq!(
+            history.point(pairs, 0),
+            Some(Bucket {
+                min: 2.0,
+                max: 3.0,
+                avg: 2.5
+            })
+        );
+        assert_eq!(
+            history.point(pairs, 1),
+            Some(Bucket {
+                min: 0.0,
+                max: 1.0,
+                avg: 0.5
+            })
+        );
+        assert_eq!(history.point(pairs, 2), None);
+        assert_eq!(history.range(pairs), Some((0.0, 3.0)));
+    }
+
+    #[test]
+    fn values_are_clamped_to_storage_range() {
+        let mut history = TieredHistory::new(0.001);
+        history.update(100.0, 0);
+        history.update(-100.0, 1_000);
+        history.update(0.0, 2_000);
+
+        let minute = Zoom::OneMinute.window();
+        assert_eq!(history.len(minute), 2);
+        let newest = history.point(minute, 0).unwrap().avg;
+        let oldest = history.point(minute, 1).unwrap().avg;
+        assert!((newest + 32.768).abs() < 1e-3, "{newest}");
+        assert!((oldest - 32.767).abs() < 1e-3, "{oldest}");
+    }
+
+    #[test]
+    fn reset_clears_all_tiers() {
+        let mut history = TieredHistory::new(1.0);
+        FakeClock::new().run(100, 70_000, |now| history.update(1.0, now));
+        history.reset();
+        assert!(history.is_empty());
+        assert_eq!(history.range(Zoom::TenMinutes.window()), None);
+    }
+}
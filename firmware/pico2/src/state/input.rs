@@ -1,57 +1,449 @@
-use embassy_time::Instant;
-
-use super::{ButtonState, Page, Popup};
-use crate::render::FpsMode;
-
-#[derive(Default)]
-pub struct InputResult {
-    pub new_fps_mode: Option<FpsMode>,
-    pub new_page: Option<Page>,
-    pub boost_unit_toggled: bool,
-    pub reset_requested: bool,
-    pub show_popup: Option<Popup>,
-    pub clear_frames: bool,
-    pub reset_fps_average: bool,
-}
-
-#[allow(clippy::too_many_arguments)]
-pub fn process_buttons(
-    btn_x_state: &mut ButtonState,
-    btn_y_state: &mut ButtonState,
-    btn_a_state: &mut ButtonState,
-    btn_b_state: &mut ButtonState,
-    btn_x_pressed: bool,
-    btn_y_pressed: bool,
-    btn_a_pressed: bool,
-    btn_b_pressed: bool,
-    current_page: Page,
-    current_fps_mode: FpsMode,
-) -> InputResult {
-    let mut result = InputResult::default();
-
-    if btn_x_state.just_pressed(btn_x_pressed) && current_page == Page::Dashboard {
-        let new_mode = current_fps_mode.next();
-        result.new_fps_mode = Some(new_mode);
-        result.show_popup = Some(Popup::Fps(Instant::now()));
-        result.clear_frames = true;
-    }
-
-    if btn_y_state.just_pressed(btn_y_pressed) {
-        let new_page = current_page.toggle();
-        result.new_page = Some(new_page);
-        result.clear_frames = true;
-        result.reset_fps_average = true;
-    }
-
-    if btn_a_state.just_pressed(btn_a_pressed) && current_page == Page::Dashboard {
-        result.boost_unit_toggled = true;
-        result.show_popup = Some(Popup::BoostUnit(Instant::now()));
-    }
-
-    if btn_b_state.just_pressed(btn_b_pressed) && current_page == Page::Dashboard {
-        result.reset_requested = true;
-        result.show_popup = Some(Popup::Reset(Instant::now()));
-    }
-
-    result
+//! Button input: switch levels in, events out, and the action the current page binds
+//! to each event.
+//!
+//! `Buttons` is fed the level of every switch once a frame with a millisecond
+//! timestamp and turns level changes and hold times into `InputEvent`s. `Keymap` then
+//! looks each event up in a binding table, where a binding can hold on every page or
+//! only on one.
+
+use heapless::Vec;
+
+use crate::pages::Page;
+
+/// The PIM715's four buttons and the encoder's push switch.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    A,
+    B,
+    X,
+    Y,
+    Encoder,
+}
+
+impl Button {
+    /// Order of the levels passed to `Buttons::update`.
+    pub const ALL: [Self; Self::COUNT] = [Self::A, Self::B, Self::X, Self::Y, Self::Encoder];
+    pub const COUNT: usize = 5;
+
+    #[inline]
+    pub const fn index(self) -> usize { self as usize }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputEvent {
+    /// Went down.
+    Press(Button),
+    /// Came up.
+    Release(Button),
+    /// Came up before the long-press time. Waits out the double-press window if the
+    /// page binds a double press to the button, otherwise fires on release.
+    ShortPress(Button),
+    /// Held for the long-press time; the release that follows is not a short press.
+    LongPress(Button),
+    /// Still held: first after the repeat delay, then every repeat interval.
+    Repeat(Button),
+    /// A second short press within the double-press window of the first.
+    DoublePress(Button),
+    /// Two buttons held together, in `Button::ALL` order. Both only report their
+    /// release after it; a press bound to either should therefore be a short press.
+    Chord(Button, Button),
+}
+
+/// Timing of the events, in milliseconds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InputTiming {
+    /// Level changes closer than this to the last one are contact bounce.
+    pub debounce_ms: u64,
+    pub long_press_ms: u64,
+    pub repeat_delay_ms: u64,
+    pub repeat_interval_ms: u64,
+    /// Longest gap between the release of a short press and the next press.
+    pub double_press_ms: u64,
+}
+
+impl InputTiming {
+    pub const fn new() -> Self {
+        Self {
+            debounce_ms: 50,
+            long_press_ms: 600,
+            repeat_delay_ms: 500,
+            repeat_interval_ms: 150,
+            double_press_ms: 300,
+        }
+    }
+}
+
+impl Default for InputTiming {
+    fn default() -> Self { Self::new() }
+}
+
+/// Events one update can produce: a press with its chord, or a release with its
+/// short or double press, plus a pending short press, per button.
+pub const MAX_EVENTS: usize = 3 * Button::COUNT;
+
+#[derive(Clone, Copy)]
+struct Switch {
+    down: bool,
+    changed_at_ms: Option<u64>,
+    pressed_at_ms: u64,
+    long_sent: bool,
+    next_repeat_ms: u64,
+    /// Part of a chord since its press; its short, long and repeated presses are suppressed.
+    chorded: bool,
+    /// Release time of a short press that may still become a double press.
+    tap_released_at_ms: Option<u64>,
+    /// This press followed a short press within the double-press window.
+    second_press: bool,
+}
+
+impl Switch {
+    const fn new() -> Self {
+        Self {
+            down: false,
+            changed_at_ms: None,
+            pressed_at_ms: 0,
+            long_sent: false,
+            next_repeat_ms: 0,
+            chorded: false,
+            tap_released_at_ms: None,
+            second_press: false,
+        }
+    }
+}
+
+/// Event detection for every button.
+pub struct Buttons {
+    switches: [Switch; Button::COUNT],
+    timing: InputTiming,
+}
+
+impl Buttons {
+    pub const fn new(timing: InputTiming) -> Self {
+        Self {
+            switches: [Switch::new(); Button::COUNT],
+            timing,
+        }
+    }
+
+    /// Take one sample of the switch levels, `down` in `Button::ALL` order.
+    /// `double_press_bound` says whether a double press of a button means something
+    /// on the current page; only then does its short press wait for a second one.
+    pub fn update(
+        &mut self,
+        now_ms: u64,
+        down: [bool; Button::COUNT],
+        double_press_bound: impl Fn(Button) -> bool,
+    ) -> Vec<InputEvent, MAX_EVENTS> {
+        let mut events = Vec::new();
+        let timing = self.timing;
+        for button in Button::ALL {
+            let held_elsewhere = Button::ALL
+                .into_iter()
+                .find(|&other| other != button && self.switches[other.index()].down);
+            let switch = &mut self.switches[button.index()];
+
+            if let Some(released_at) = switch.tap_released_at_ms
+                && now_ms.saturating_sub(released_at) > timing.double_press_ms
+            {
+                switch.tap_released_at_ms = None;
+                let _ = events.push(InputEvent::ShortPress(button));
+            }
+
+            let settled = switch
+                .changed_at_ms
+                .is_none_or(|changed_at| now_ms.saturating_sub(changed_at) >= timing.debounce_ms);
+            if down[button.index()] != switch.down && settled {
+                switch.down = !switch.down;
+                switch.changed_at_ms = Some(now_ms);
+                if switch.down {
+                    switch.pressed_at_ms = now_ms;
+                    switch.long_sent = false;
+                    switch.next_repeat_ms = now_ms + timing.repeat_delay_ms;
+                    switch.second_press = switch.tap_released_at_ms.take().is_some();
+                    switch.chorded = false;
+                    let _ = events.push(InputEvent::Press(button));
+                    if let Some(other) = held_elsewhere {
+                        switch.chorded = true;
+                        let partner = &mut self.switches[other.index()];
+                        if !partner.chorded {
+                            partner.chorded = true;
+                            let (first, second) = if other.index() < button.index() {
+                                (other, button)
+                            } else {
+                                (button, other)
+                            };
+                            let _ = events.push(InputEvent::Chord(first, second));
+                        }
+                    }
+                } else {
+                    let _ = events.push(InputEvent::Release(button));
+                    if !switch.chorded && !switch.long_sent {
+                        if switch.second_press {
+                            let _ = events.push(InputEvent::DoublePress(button));
+                        } else if double_press_bound(button) {
+                            switch.tap_released_at_ms = Some(now_ms);
+                        } else {
+                            let _ = events.push(InputEvent::ShortPress(button));
+                        }
+                    }
+                    switch.second_press = false;
+                }
+                continue;
+            }
+
+            if !switch.down || switch.chorded {
+                continue;
+            }
+            if !switch.long_sent && now_ms.saturating_sub(switch.pressed_at_ms) >= timing.long_press_ms {
+                switch.long_sent = true;
+                let _ = events.push(InputEvent::LongPress(button));
+            }
+            if now_ms >= switch.next_repeat_ms {
+                switch.next_repeat_ms = now_ms + timing.repeat_interval_ms;
+                let _ = events.push(InputEvent::Repeat(button));
+            }
+        }
+        events
+    }
+}
+
+impl Default for Buttons {
+    fn default() -> Self { Self::new(InputTiming::new()) }
+}
+
+/// What an input event does. Some depend on what the page shows, e.g. `Select` on the
+/// dashboard acknowledges the shown alert if there is one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    NextPage,
+    /// Back to the dashboard.
+    Home,
+    CycleFps,
+    CycleBoostUnit,
+    /// End the session and reset the statistics.
+    ResetStats,
+    HighlightNextTrace,
+    ToggleTrace,
+    /// Switch the Stats page between this and the last session.
+    ToggleSession,
+    /// Acknowledge the shown alert, open the selected cell or toggle the backlight
+    /// (dashboard); open or save the menu item (settings).
+    Select,
+    /// Leave the edit or menu level (settings).
+    Back,
+    /// Acknowledge every alert while one is shown, otherwise open the settings menu.
+    OpenSettings,
+    CycleZoom,
+    /// Move the graph cursor back to the newest sample.
+    GoLive,
+    ToggleBacklight,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Binding {
+    /// `None` binds the event on every page.
+    pub page: Option<Page>,
+    pub event: InputEvent,
+    pub action: Action,
+}
+
+impl Binding {
+    const fn on(
+        page: Page,
+        event: InputEvent,
+        action: Action,
+    ) -> Self {
+        Self {
+            page: Some(page),
+            event,
+            action,
+        }
+    }
+
+    const fn everywhere(
+        event: InputEvent,
+        action: Action,
+    ) -> Self {
+        Self {
+            page: None,
+            event,
+            action,
+        }
+    }
+}
+
+/// Event-to-action table. The first binding for the page and event wins, so a page's
+/// own bindings go before the ones for every page.
+#[derive(Clone, Copy)]
+pub struct Keymap {
+    bindings: &'static [Binding],
+}
+
+impl Keymap {
+    pub const fn new(bindings: &'static [Binding]) -> Self { Self { bindings } }
+
+    pub fn action(
+        &self,
+        page: Page,
+        event: InputEvent,
+    ) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.event == event && binding.page.is_none_or(|bound| bound == page))
+            .map(|binding| binding.action)
+    }
+
+    #[inline]
+    pub fn binds(
+        &self,
+        page: Page,
+        event: InputEvent,
+    ) -> bool {
+        self.action(page, event).is_some()
+    }
+}
+
+pub const DEFAULT_KEYMAP: Keymap = {
+    use Action::*;
+    use Button::{A, B, Encoder, X, Y};
+    use InputEvent::{LongPress, Press, Repeat, ShortPress};
+
+    Keymap::new(&[
+        Binding::on(Page::Dashboard, Press(X), CycleFps),
+        Binding::on(Page::Dashboard, Press(A), CycleBoostUnit),
+        Binding::on(Page::Dashboard, Press(B), ResetStats),
+        Binding::on(Page::Dashboard, ShortPress(Encoder), Select),
+        Binding::on(Page::Dashboard, LongPress(Encoder), OpenSettings),
+        Binding::on(Page::Graph, Press(X), HighlightNextTrace),
+        Binding::on(Page::Graph, Repeat(X), HighlightNextTrace),
+        Binding::on(Page::Graph, Press(A), ToggleTrace),
+        Binding::on(Page::Graph, ShortPress(Encoder), GoLive),
+        Binding::on(Page::Graph, LongPress(Encoder), CycleZoom),
+        Binding::on(Page::Stats, Press(A), ToggleSession),
+        Binding::on(Page::Stats, Press(B), ResetStats),
+        Binding::on(Page::Debug, ShortPress(Encoder), ToggleBacklight),
+        Binding::on(Page::Settings, ShortPress(Encoder), Select),
+        Binding::on(Page::Settings, LongPress(Encoder), Back),
+        Binding::on(Page::Focus, ShortPress(Encoder), Home),
+        Binding::on(Page::Focus, LongPress(Encoder), CycleZoom),
+        // Y flips pages on release, so holding it can go home without passing a page
+        Binding::everywhere(ShortPress(Y), NextPage),
+        Binding::everywhere(LongPress(Y), Home),
+    ])
+};
+
+#[cfg(test)]
+mod tests {
+    use Action::*;
+    use Button::{A, B, Encoder, X, Y};
+    use InputEvent::*;
+
+    use super::*;
+
+    const TIMING: InputTiming = InputTiming::new();
+
+    fn levels(held: &[Button]) -> [bool; Button::COUNT] { Button::ALL.map(|button| held.contains(&button)) }
+
+    /// Events of one update with no double presses bound.
+    fn step(
+        buttons: &mut Buttons,
+        now_ms: u64,
+        held: &[Button],
+    ) -> Vec<InputEvent, MAX_EVENTS> {
+        buttons.update(now_ms, levels(held), |_| false)
+    }
+
+    #[test]
+    fn short_press_fires_on_release_and_bounce_is_ignored() {
+        let mut buttons = Buttons::default();
+        assert_eq!(step(&mut buttons, 0, &[X]), [Press(X)]);
+        assert!(step(&mut buttons, 10, &[]).is_empty(), "bounce");
+        assert!(step(&mut buttons, 20, &[X]).is_empty());
+        assert_eq!(step(&mut buttons, 200, &[]), [Release(X), ShortPress(X)]);
+        assert!(step(&mut buttons, 220, &[X]).is_empty(), "bounce after release");
+    }
+
+    #[test]
+    fn hold_gives_long_press_and_repeats_but_no_short_press() {
+        let mut buttons = Buttons::default();
+        step(&mut buttons, 0, &[Encoder]);
+        assert!(step(&mut buttons, TIMING.repeat_delay_ms - 1, &[Encoder]).is_empty());
+        assert_eq!(
+            step(&mut buttons, TIMING.repeat_delay_ms, &[Encoder]),
+            [Repeat(Encoder)]
+        );
+        assert_eq!(
+            step(&mut buttons, TIMING.long_press_ms, &[Encoder]),
+            [LongPress(Encoder)]
+        );
+
+        let next_repeat = TIMING.repeat_delay_ms + TIMING.repeat_interval_ms;
+        assert_eq!(step(&mut buttons, next_repeat, &[Encoder]), [Repeat(Encoder)]);
+        assert_eq!(step(&mut buttons, next_repeat + 10, &[]), [Release(Encoder)]);
+    }
+
+    #[test]
+    fn double_press_when_bound() {
+        let mut buttons = Buttons::default();
+        let bound = |button| button == B;
+        buttons.update(0, levels(&[B]), bound);
+        assert_eq!(buttons.update(100, levels(&[]), bound), [Release(B)]);
+        assert_eq!(buttons.update(250, levels(&[B]), bound), [Press(B)]);
+        assert_eq!(buttons.update(350, levels(&[]), bound), [Release(B), DoublePress(B)]);
+
+        // A single press waits out the window, then is a short press
+        buttons.update(1000, levels(&[B]), bound);
+        buttons.update(1100, levels(&[]), bound);
+        assert!(
+            buttons
+                .update(1100 + TIMING.double_press_ms, levels(&[]), bound)
+                .is_empty()
+        );
+        assert_eq!(
+            buttons.update(1101 + TIMING.double_press_ms, levels(&[]), bound),
+            [ShortPress(B)]
+        );
+    }
+
+    #[test]
+    fn chord_suppresses_presses_of_both_buttons() {
+        let mut buttons = Buttons::default();
+        step(&mut buttons, 0, &[Y]);
+        assert_eq!(step(&mut buttons, 80, &[X, Y]), [Press(X), Chord(X, Y)]);
+        assert!(step(&mut buttons, 2000, &[X, Y]).is_empty(), "no long press or repeat");
+        assert_eq!(step(&mut buttons, 2100, &[]), [Release(X), Release(Y)]);
+
+        // Pressed together in one sample
+        assert_eq!(step(&mut buttons, 3000, &[A, B]), [Press(A), Press(B), Chord(A, B)]);
+    }
+
+    #[test]
+    fn page_bindings_win_over_global_ones() {
+        const KEYMAP: Keymap = Keymap::new(&[
+            Binding::on(Page::Settings, ShortPress(Y), Back),
+            Binding::everywhere(ShortPress(Y), NextPage),
+        ]);
+        assert_eq!(KEYMAP.action(Page::Settings, ShortPress(Y)), Some(Back));
+        assert_eq!(KEYMAP.action(Page::Graph, ShortPress(Y)), Some(NextPage));
+        assert_eq!(KEYMAP.action(Page::Graph, Press(Y)), None);
+    }
+
+    #[test]
+    fn default_keymap_covers_every_page() {
+        let pages = [
+            Page::Dashboard,
+            Page::Graph,
+            Page::Stats,
+            Page::Debug,
+            Page::Logs,
+            Page::Settings,
+            Page::Focus,
+        ];
+        for page in pages {
+            assert_eq!(DEFAULT_KEYMAP.action(page, ShortPress(Y)), Some(NextPage));
+            assert_eq!(DEFAULT_KEYMAP.action(page, LongPress(Y)), Some(Home));
+        }
+        assert_eq!(DEFAULT_KEYMAP.action(Page::Graph, Repeat(X)), Some(HighlightNextTrace));
+        assert_eq!(DEFAULT_KEYMAP.action(Page::Logs, ShortPress(Encoder)), None);
+    }
 }
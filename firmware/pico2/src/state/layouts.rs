@@ -0,0 +1,197 @@
+use crate::config::dashboard::DEFAULT_LAYOUT_NAME;
+use crate::config::{HEADER_HEIGHT, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::profiles::VehicleProfile;
+use crate::sensor_id::SensorId;
+
+/// Cell grid below the header. Every grid has two rows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Grid {
+    TwoByTwo,
+    ThreeByTwo,
+    FourByTwo,
+}
+
+impl Grid {
+    pub const ROWS: u32 = 2;
+
+    pub const fn columns(self) -> u32 {
+        match self {
+            Self::TwoByTwo => 2,
+            Self::ThreeByTwo => 3,
+            Self::FourByTwo => 4,
+        }
+    }
+
+    #[inline]
+    pub const fn slot_count(self) -> usize { (self.columns() * Self::ROWS) as usize }
+
+    #[inline]
+    pub const fn cell_width(self) -> u32 { SCREEN_WIDTH / self.columns() }
+
+    #[inline]
+    pub const fn cell_height(self) -> u32 { (SCREEN_HEIGHT - HEADER_HEIGHT) / Self::ROWS }
+
+    /// Top-left corner of a slot; slots are numbered left to right, top to bottom.
+    pub const fn slot_origin(
+        self,
+        slot: usize,
+    ) -> (u32, u32) {
+        let col = slot as u32 % self.columns();
+        let row = slot as u32 / self.columns();
+        (col * self.cell_width(), HEADER_HEIGHT + row * self.cell_height())
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::TwoByTwo => "2x2",
+            Self::ThreeByTwo => "3x2",
+            Self::FourByTwo => "4x2",
+        }
+    }
+}
+
+/// Widget that draws a slot.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Widget {
+    /// Boost with PSI/BAR display and peak.
+    Boost,
+    /// AFR with lambda readout.
+    Afr,
+    /// Voltage with min/max.
+    Battery,
+    /// Generic value cell: label, whole-number value, mini graph, avg/max.
+    Temp,
+    /// Analog needle dial with warning arcs and a peak marker. Sensors without
+    /// a gauge scale fall back to the generic value cell.
+    Gauge,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Slot {
+    pub sensor: SensorId,
+    pub widget: Widget,
+}
+
+impl Slot {
+    pub const fn new(
+        sensor: SensorId,
+        widget: Widget,
+    ) -> Self {
+        Self { sensor, widget }
+    }
+}
+
+pub struct Layout {
+    pub name: &'static str,
+    pub grid: Grid,
+    /// One entry per grid slot, in slot order.
+    pub slots: &'static [Slot],
+    /// Shown in place of a slot whose sensor the car does not have.
+    pub spare: Slot,
+}
+
+impl Layout {
+    /// Slot `index` as fitted to `profile`.
+    pub const fn slot(
+        &self,
+        index: usize,
+        profile: &VehicleProfile,
+    ) -> Slot {
+        let slot = self.slots[index];
+        if profile.has_sensor(slot.sensor) {
+            slot
+        } else {
+            self.spare
+        }
+    }
+}
+
+pub const LAYOUTS: [Layout; 4] = [
+    Layout {
+        name: "FULL",
+        grid: Grid::FourByTwo,
+        slots: &[
+            Slot::new(SensorId::Boost, Widget::Boost),
+            Slot::new(SensorId::Afr, Widget::Afr),
+            Slot::new(SensorId::Battery, Widget::Battery),
+            Slot::new(SensorId::Coolant, Widget::Temp),
+            Slot::new(SensorId::Oil, Widget::Temp),
+            Slot::new(SensorId::Dsg, Widget::Temp),
+            Slot::new(SensorId::Iat, Widget::Temp),
+            Slot::new(SensorId::Egt, Widget::Temp),
+        ],
+        spare: Slot::new(SensorId::Rpm, Widget::Temp),
+    },
+    Layout {
+        name: "ENGINE",
+        grid: Grid::ThreeByTwo,
+        slots: &[
+            Slot::new(SensorId::Boost, Widget::Boost),
+            Slot::new(SensorId::Afr, Widget::Afr),
+            Slot::new(SensorId::Egt, Widget::Temp),
+            Slot::new(SensorId::Coolant, Widget::Temp),
+            Slot::new(SensorId::Oil, Widget::Temp),
+            Slot::new(SensorId::Iat, Widget::Temp),
+        ],
+        spare: Slot::new(SensorId::Rpm, Widget::Temp),
+    },
+    Layout {
+        name: "CRUISE",
+        grid: Grid::TwoByTwo,
+        slots: &[
+            Slot::new(SensorId::Coolant, Widget::Temp),
+            Slot::new(SensorId::Oil, Widget::Temp),
+            Slot::new(SensorId::Dsg, Widget::Temp),
+            Slot::new(SensorId::Battery, Widget::Battery),
+        ],
+        spare: Slot::new(SensorId::Iat, Widget::Temp),
+    },
+    Layout {
+        name: "GAUGES",
+        grid: Grid::TwoByTwo,
+        slots: &[
+            Slot::new(SensorId::Boost, Widget::Gauge),
+            Slot::new(SensorId::Rpm, Widget::Gauge),
+            Slot::new(SensorId::Coolant, Widget::Temp),
+            Slot::new(SensorId::Egt, Widget::Temp),
+        ],
+        spare: Slot::new(SensorId::Oil, Widget::Temp),
+    },
+];
+
+/// Layout a fresh settings record starts with, named in `dashboard.toml`.
+pub const DEFAULT_LAYOUT: usize = layout_index(DEFAULT_LAYOUT_NAME);
+
+const fn layout_index(name: &str) -> usize {
+    let mut i = 0;
+    while i < LAYOUTS.len() {
+        if name.eq_ignore_ascii_case(LAYOUTS[i].name) {
+            return i;
+        }
+        i += 1;
+    }
+    panic!("dashboard.toml: `layout.default` names no layout in LAYOUTS");
+}
+
+const fn layouts_are_valid(layouts: &[Layout]) -> bool {
+    let mut i = 0;
+    while i < layouts.len() {
+        let layout = &layouts[i];
+        if layout.slots.len() != layout.grid.slot_count() || matches!(layout.spare.sensor, SensorId::Dsg) {
+            return false;
+        }
+        let mut j = 0;
+        while j < layout.slots.len() {
+            if layout.slots[j].sensor as usize == layout.spare.sensor as usize {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Every layout must fill exactly the slots of its grid, with a spare every car has
+// that the layout does not already show
+const _: () = assert!(layouts_are_valid(&LAYOUTS));
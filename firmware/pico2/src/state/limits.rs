@@ -0,0 +1,547 @@
+//! Runtime colour band and alarm limits.
+//!
+//! The constants in `config::sensors` are the defaults. The active set lives here so
+//! the status and critical functions, band edges and alert rules all follow an edit
+//! made in the settings menu without a reflash. Edits are validated against the same
+//! ordering the constants are asserted to have, so a saved set can never leave two
+//! bands overlapping.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::config::sensors::{
+    BATT_CRITICAL,
+    BATT_EDIT_RANGE,
+    BATT_WARNING,
+    BOOST_CRITICAL,
+    BOOST_EDIT_RANGE,
+    BOOST_HIGH,
+    COOLANT_COLD_MAX,
+    COOLANT_CRITICAL,
+    COOLANT_EDIT_RANGE,
+    DSG_CRITICAL,
+    DSG_EDIT_RANGE,
+    DSG_ELEVATED,
+    DSG_HIGH,
+    EGT_COLD_MAX,
+    EGT_CRITICAL,
+    EGT_DANGER_MANIFOLD,
+    EGT_EDIT_RANGE,
+    EGT_HIGH_LOAD,
+    EGT_SPIRITED,
+    IAT_COLD,
+    IAT_CRITICAL,
+    IAT_EDIT_RANGE,
+    IAT_EXTREME_COLD,
+    IAT_HOT,
+    IAT_WARM,
+    LAMBDA_EDIT_RANGE,
+    LAMBDA_LEAN_CRITICAL,
+    LAMBDA_OPTIMAL_MAX,
+    LAMBDA_RICH,
+    LAMBDA_RICH_AF,
+    OIL_CRITICAL,
+    OIL_EDIT_RANGE,
+    OIL_ELEVATED,
+    OIL_HIGH,
+    OIL_LOW_TEMP,
+    RPM_EDIT_RANGE,
+    RPM_REDLINE,
+    RPM_SHIFT,
+};
+use crate::settings::fletcher16;
+use crate::units::Quantity;
+
+const MAGIC: [u8; 4] = *b"DLIM";
+
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+const CHECKSUM_LEN: usize = 2;
+
+const PAYLOAD_LEN: usize = Limit::COUNT * 4;
+
+/// Size of a serialized thresholds record.
+pub const THRESHOLDS_RECORD_LEN: usize = HEADER_LEN + PAYLOAD_LEN + CHECKSUM_LEN;
+
+/// One editable limit.
+///
+/// Limits of a sensor form a chain in ascending order, and the variants follow
+/// the chains. Stored records are read by position, so only ever append.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Limit {
+    OilLowTemp,
+    OilElevated,
+    OilHigh,
+    OilCritical,
+    CoolantColdMax,
+    CoolantCritical,
+    IatExtremeCold,
+    IatCold,
+    IatWarm,
+    IatHot,
+    IatCritical,
+    EgtColdMax,
+    EgtSpirited,
+    EgtHighLoad,
+    EgtCritical,
+    EgtDangerManifold,
+    BattCritical,
+    BattWarning,
+    LambdaRichAf,
+    LambdaRich,
+    LambdaOptimalMax,
+    LambdaLeanCritical,
+    BoostHigh,
+    BoostCritical,
+    RpmShift,
+    RpmRedline,
+    DsgElevated,
+    DsgHigh,
+    DsgCritical,
+}
+
+/// The limits of one sensor, or sensors sharing bands, with the range and
+/// step they are edited in. The ranges come from `build.rs`, which also checks
+/// `dashboard.toml` values against them.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Chain {
+    pub quantity: Quantity,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+const OIL: Chain = Chain::new(Quantity::Temperature, OIL_EDIT_RANGE, 1.0);
+const DSG: Chain = Chain::new(Quantity::Temperature, DSG_EDIT_RANGE, 1.0);
+const COOLANT: Chain = Chain::new(Quantity::Temperature, COOLANT_EDIT_RANGE, 1.0);
+const IAT: Chain = Chain::new(Quantity::Temperature, IAT_EDIT_RANGE, 1.0);
+const EGT: Chain = Chain::new(Quantity::Temperature, EGT_EDIT_RANGE, 10.0);
+const BATT: Chain = Chain::new(Quantity::Voltage, BATT_EDIT_RANGE, 0.1);
+const LAMBDA: Chain = Chain::new(Quantity::Mixture, LAMBDA_EDIT_RANGE, 0.01);
+const BOOST: Chain = Chain::new(Quantity::Pressure, BOOST_EDIT_RANGE, 0.05);
+const RPM: Chain = Chain::new(Quantity::EngineSpeed, RPM_EDIT_RANGE, 100.0);
+
+impl Chain {
+    const fn new(
+        quantity: Quantity,
+        (min, max): (f32, f32),
+        step: f32,
+    ) -> Self {
+        Self {
+            quantity,
+            min,
+            max,
+            step,
+        }
+    }
+}
+
+impl Limit {
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::OilLowTemp,
+        Self::OilElevated,
+        Self::OilHigh,
+        Self::OilCritical,
+        Self::CoolantColdMax,
+        Self::CoolantCritical,
+        Self::IatExtremeCold,
+        Self::IatCold,
+        Self::IatWarm,
+        Self::IatHot,
+        Self::IatCritical,
+        Self::EgtColdMax,
+        Self::EgtSpirited,
+        Self::EgtHighLoad,
+        Self::EgtCritical,
+        Self::EgtDangerManifold,
+        Self::BattCritical,
+        Self::BattWarning,
+        Self::LambdaRichAf,
+        Self::LambdaRich,
+        Self::LambdaOptimalMax,
+        Self::LambdaLeanCritical,
+        Self::BoostHigh,
+        Self::BoostCritical,
+        Self::RpmShift,
+        Self::RpmRedline,
+        Self::DsgElevated,
+        Self::DsgHigh,
+        Self::DsgCritical,
+    ];
+    pub const COUNT: usize = 29;
+
+    #[inline]
+    pub const fn index(self) -> usize { self as usize }
+
+    /// Settings menu label, at most 13 characters.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::OilLowTemp => "Oil low",
+            Self::OilElevated => "Oil elevated",
+            Self::OilHigh => "Oil high",
+            Self::OilCritical => "Oil crit",
+            Self::CoolantColdMax => "Coolant cold",
+            Self::CoolantCritical => "Coolant crit",
+            Self::IatExtremeCold => "IAT ext cold",
+            Self::IatCold => "IAT cold",
+            Self::IatWarm => "IAT warm",
+            Self::IatHot => "IAT hot",
+            Self::IatCritical => "IAT crit",
+            Self::EgtColdMax => "EGT cold",
+            Self::EgtSpirited => "EGT spirited",
+            Self::EgtHighLoad => "EGT high load",
+            Self::EgtCritical => "EGT crit",
+            Self::EgtDangerManifold => "EGT manifold",
+            Self::BattCritical => "Batt crit",
+            Self::BattWarning => "Batt warn",
+            Self::LambdaRichAf => "Rich AF",
+            Self::LambdaRich => "Rich",
+            Self::LambdaOptimalMax => "Optimal max",
+            Self::LambdaLeanCritical => "Lean crit",
+            Self::BoostHigh => "Boost high",
+            Self::BoostCritical => "Boost crit",
+            Self::RpmShift => "RPM shift",
+            Self::RpmRedline => "RPM redline",
+            Self::DsgElevated => "DSG elevated",
+            Self::DsgHigh => "DSG high",
+            Self::DsgCritical => "DSG crit",
+        }
+    }
+
+    /// Compile-time value from `config::sensors`.
+    pub const fn default_value(self) -> f32 {
+        match self {
+            Self::OilLowTemp => OIL_LOW_TEMP,
+            Self::OilElevated => OIL_ELEVATED,
+            Self::OilHigh => OIL_HIGH,
+            Self::OilCritical => OIL_CRITICAL,
+            Self::CoolantColdMax => COOLANT_COLD_MAX,
+            Self::CoolantCritical => COOLANT_CRITICAL,
+            Self::IatExtremeCold => IAT_EXTREME_COLD,
+            Self::IatCold => IAT_COLD,
+            Self::IatWarm => IAT_WARM,
+            Self::IatHot => IAT_HOT,
+            Self::IatCritical => IAT_CRITICAL,
+            Self::EgtColdMax => EGT_COLD_MAX,
+            Self::EgtSpirited => EGT_SPIRITED,
+            Self::EgtHighLoad => EGT_HIGH_LOAD,
+            Self::EgtCritical => EGT_CRITICAL,
+            Self::EgtDangerManifold => EGT_DANGER_MANIFOLD,
+            Self::BattCritical => BATT_CRITICAL,
+            Self::BattWarning => BATT_WARNING,
+            Self::LambdaRichAf => LAMBDA_RICH_AF,
+            Self::LambdaRich => LAMBDA_RICH,
+            Self::LambdaOptimalMax => LAMBDA_OPTIMAL_MAX,
+            Self::LambdaLeanCritical => LAMBDA_LEAN_CRITICAL,
+            Self::BoostHigh => BOOST_HIGH,
+            Self::BoostCritical => BOOST_CRITICAL,
+            Self::RpmShift => RPM_SHIFT,
+            Self::RpmRedline => RPM_REDLINE,
+            Self::DsgElevated => DSG_ELEVATED,
+            Self::DsgHigh => DSG_HIGH,
+            Self::DsgCritical => DSG_CRITICAL,
+        }
+    }
+
+    pub const fn chain(self) -> Chain {
+        match self {
+            Self::OilLowTemp | Self::OilElevated | Self::OilHigh | Self::OilCritical => OIL,
+            Self::CoolantColdMax | Self::CoolantCritical => COOLANT,
+            Self::IatExtremeCold | Self::IatCold | Self::IatWarm | Self::IatHot | Self::IatCritical => IAT,
+            Self::EgtColdMax | Self::EgtSpirited | Self::EgtHighLoad | Self::EgtCritical | Self::EgtDangerManifold => {
+                EGT
+            }
+            Self::BattCritical | Self::BattWarning => BATT,
+            Self::LambdaRichAf | Self::LambdaRich | Self::LambdaOptimalMax | Self::LambdaLeanCritical => LAMBDA,
+            Self::BoostHigh | Self::BoostCritical => BOOST,
+            Self::RpmShift | Self::RpmRedline => RPM,
+            Self::DsgElevated | Self::DsgHigh | Self::DsgCritical => DSG,
+        }
+    }
+
+    /// Next lower limit of the same chain.
+    pub const fn below(self) -> Option<Self> {
+        match self {
+            Self::OilLowTemp
+            | Self::CoolantColdMax
+            | Self::IatExtremeCold
+            | Self::EgtColdMax
+            | Self::BattCritical
+            | Self::LambdaRichAf
+            | Self::BoostHigh
+            | Self::RpmShift
+            | Self::DsgElevated => None,
+            _ => Some(Self::ALL[self.index() - 1]),
+        }
+    }
+
+    /// Next higher limit of the same chain.
+    pub const fn above(self) -> Option<Self> {
+        match self.index() + 1 {
+            next if next < Self::COUNT && Self::ALL[next].below().is_some() => Some(Self::ALL[next]),
+            _ => None,
+        }
+    }
+}
+
+const fn defaults_are_consistent() -> bool {
+    let mut i = 0;
+    while i < Limit::COUNT {
+        let limit = Limit::ALL[i];
+        let value = limit.default_value();
+        let chain = limit.chain();
+        if limit.index() != i || value < chain.min || value > chain.max {
+            return false;
+        }
+        if let Some(below) = limit.below()
+            && below.default_value() >= value
+        {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Every default sits at its own index, inside its chain's edit range and above the limit below it
+const _: () = assert!(defaults_are_consistent());
+
+/// A complete set of limits in base units, indexed by `Limit::index()`.
+///
+/// Record layout: `MAGIC`, payload length, little-endian `f32` per limit, Fletcher-16
+/// of length and payload. A shorter record from older firmware keeps defaults for
+/// the limits it does not contain; a record that does not validate is ignored.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Thresholds {
+    values: [f32; Limit::COUNT],
+}
+
+impl Thresholds {
+    pub const fn new() -> Self {
+        let mut values = [0.0; Limit::COUNT];
+        let mut i = 0;
+        while i < Limit::COUNT {
+            values[i] = Limit::ALL[i].default_value();
+            i += 1;
+        }
+        Self { values }
+    }
+
+    #[inline]
+    pub const fn get(
+        &self,
+        limit: Limit,
+    ) -> f32 {
+        self.values[limit.index()]
+    }
+
+    /// Values `limit` can take with the rest of the set unchanged: its chain's edit
+    /// range, at least one step clear of its neighbours.
+    pub fn range(
+        &self,
+        limit: Limit,
+    ) -> (f32, f32) {
+        let chain = limit.chain();
+        let lo = limit
+            .below()
+            .map_or(chain.min, |below| chain.min.max(self.get(below) + chain.step));
+        let hi = limit
+            .above()
+            .map_or(chain.max, |above| chain.max.min(self.get(above) - chain.step));
+        (lo, hi)
+    }
+
+    /// Whether `value` keeps the set valid: finite, inside the chain's edit range and
+    /// strictly between the neighbouring limits.
+    pub fn accepts(
+        &self,
+        limit: Limit,
+        value: f32,
+    ) -> bool {
+        let chain = limit.chain();
+        (chain.min..=chain.max).contains(&value)
+            && limit.below().is_none_or(|below| self.get(below) < value)
+            && limit.above().is_none_or(|above| value < self.get(above))
+    }
+
+    /// Store `value` if the set accepts it. Returns whether anything changed.
+    pub fn set(
+        &mut self,
+        limit: Limit,
+        value: f32,
+    ) -> bool {
+        if !self.accepts(limit, value) || self.get(limit) == value {
+            return false;
+        }
+        self.values[limit.index()] = value;
+        true
+    }
+
+    /// Whether every limit is inside its edit range and every chain ascends.
+    pub fn is_valid(&self) -> bool { Limit::ALL.iter().all(|&limit| self.accepts(limit, self.get(limit))) }
+
+    pub fn to_bytes(&self) -> [u8; THRESHOLDS_RECORD_LEN] {
+        let mut bytes = [0u8; THRESHOLDS_RECORD_LEN];
+        bytes[..MAGIC.len()].copy_from_slice(&MAGIC);
+        bytes[MAGIC.len()] = PAYLOAD_LEN as u8;
+        for (field, value) in bytes[HEADER_LEN..HEADER_LEN + PAYLOAD_LEN]
+            .chunks_exact_mut(4)
+            .zip(self.values)
+        {
+            field.copy_from_slice(&value.to_le_bytes());
+        }
+
+        let checksum = fletcher16(&bytes[MAGIC.len()..HEADER_LEN + PAYLOAD_LEN]);
+        bytes[HEADER_LEN + PAYLOAD_LEN..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Parse a stored record. Returns `None` for erased flash, a corrupt record or
+    /// one whose limits do not validate.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_LEN || bytes[..MAGIC.len()] != MAGIC {
+            return None;
+        }
+
+        let len = bytes[MAGIC.len()] as usize;
+        let end = HEADER_LEN + len;
+        if bytes.len() < end + CHECKSUM_LEN {
+            return None;
+        }
+
+        let stored = u16::from_le_bytes([bytes[end], bytes[end + 1]]);
+        if fletcher16(&bytes[MAGIC.len()..end]) != stored {
+            return None;
+        }
+
+        let mut thresholds = Self::new();
+        for (value, field) in thresholds.values.iter_mut().zip(bytes[HEADER_LEN..end].chunks_exact(4)) {
+            *value = f32::from_le_bytes([field[0], field[1], field[2], field[3]]);
+        }
+        thresholds.is_valid().then_some(thresholds)
+    }
+}
+
+impl Default for Thresholds {
+    fn default() -> Self { Self::new() }
+}
+
+/// Active limits as `f32` bits, read by every status and critical function.
+static ACTIVE: [AtomicU32; Limit::COUNT] = {
+    let mut active = [const { AtomicU32::new(0) }; Limit::COUNT];
+    let mut i = 0;
+    while i < Limit::COUNT {
+        active[i] = AtomicU32::new(Limit::ALL[i].default_value().to_bits());
+        i += 1;
+    }
+    active
+};
+
+/// Active value of `limit`.
+#[inline]
+pub fn get(limit: Limit) -> f32 { f32::from_bits(ACTIVE[limit.index()].load(Ordering::Relaxed)) }
+
+/// Snapshot of the active set, to edit or save.
+pub fn active() -> Thresholds {
+    let mut thresholds = Thresholds::new();
+    for limit in Limit::ALL {
+        thresholds.values[limit.index()] = get(limit);
+    }
+    thresholds
+}
+
+/// Make `thresholds` the active set. Only the render loop writes it, so a reader on
+/// the same core never sees a half-updated set.
+pub fn set_active(thresholds: &Thresholds) {
+    for (active, value) in ACTIVE.iter().zip(thresholds.values) {
+        active.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_config_constants() {
+        let thresholds = Thresholds::new();
+        assert!(thresholds.is_valid());
+        assert_eq!(thresholds.get(Limit::EgtCritical), EGT_CRITICAL);
+        assert_eq!(thresholds.get(Limit::RpmRedline), RPM_REDLINE);
+        assert_eq!(active(), thresholds, "active set starts at the defaults");
+    }
+
+    #[test]
+    fn chains_link_neighbours() {
+        assert_eq!(Limit::EgtColdMax.below(), None);
+        assert_eq!(Limit::EgtCritical.below(), Some(Limit::EgtHighLoad));
+        assert_eq!(Limit::EgtCritical.above(), Some(Limit::EgtDangerManifold));
+        assert_eq!(Limit::EgtDangerManifold.above(), None);
+        assert_eq!(Limit::RpmRedline.above(), None);
+    }
+
+    #[test]
+    fn set_keeps_chain_order() {
+        let mut thresholds = Thresholds::new();
+        assert!(thresholds.set(Limit::EgtCritical, 980.0));
+        assert_eq!(thresholds.get(Limit::EgtCritical), 980.0);
+
+        assert!(
+            !thresholds.set(Limit::EgtCritical, EGT_HIGH_LOAD),
+            "not above the limit below"
+        );
+        assert!(!thresholds.set(Limit::EgtCritical, EGT_DANGER_MANIFOLD + 10.0));
+        assert!(!thresholds.set(Limit::EgtCritical, 980.0), "unchanged");
+        assert!(!thresholds.set(Limit::BoostCritical, f32::NAN));
+        assert!(!thresholds.set(Limit::BoostCritical, 5.0), "outside the edit range");
+        assert!(thresholds.is_valid());
+    }
+
+    #[test]
+    fn range_stays_a_step_clear_of_neighbours() {
+        let thresholds = Thresholds::new();
+        assert_eq!(thresholds.range(Limit::CoolantColdMax), (40.0, COOLANT_CRITICAL - 1.0));
+        assert_eq!(
+            thresholds.range(Limit::CoolantCritical),
+            (COOLANT_COLD_MAX + 1.0, 130.0)
+        );
+    }
+
+    #[test]
+    fn record_round_trips() {
+        let mut thresholds = Thresholds::new();
+        thresholds.set(Limit::EgtDangerManifold, 1_100.0);
+        thresholds.set(Limit::RpmShift, 5_500.0);
+        assert_eq!(Thresholds::from_bytes(&thresholds.to_bytes()), Some(thresholds));
+    }
+
+    #[test]
+    fn rejects_erased_corrupt_and_invalid_records() {
+        assert_eq!(Thresholds::from_bytes(&[0xFF; THRESHOLDS_RECORD_LEN]), None);
+
+        let mut bytes = Thresholds::new().to_bytes();
+        bytes[HEADER_LEN] ^= 0x01;
+        assert_eq!(Thresholds::from_bytes(&bytes), None);
+
+        // Swap two limits of a chain and fix up the checksum: the order check catches it
+        let mut swapped = Thresholds::new();
+        swapped
+            .values
+            .swap(Limit::BoostHigh.index(), Limit::BoostCritical.index());
+        assert_eq!(Thresholds::from_bytes(&swapped.to_bytes()), None);
+    }
+
+    #[test]
+    fn short_record_keeps_defaults_for_missing_limits() {
+        let mut thresholds = Thresholds::new();
+        thresholds.set(Limit::OilCritical, 115.0);
+        let full = thresholds.to_bytes();
+
+        let mut short = [0u8; HEADER_LEN + 8 + CHECKSUM_LEN];
+        short[..HEADER_LEN + 8].copy_from_slice(&full[..HEADER_LEN + 8]);
+        short[MAGIC.len()] = 8;
+        let checksum = fletcher16(&short[MAGIC.len()..HEADER_LEN + 8]);
+        short[HEADER_LEN + 8..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert_eq!(Thresholds::from_bytes(&short), Some(Thresholds::new()));
+    }
+}
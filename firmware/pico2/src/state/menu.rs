@@ -0,0 +1,727 @@
+use core::fmt::{self, Write};
+
+use crate::layouts::LAYOUTS;
+use crate::limits::{self, Limit};
+use crate::profiles::PROFILES;
+use crate::sensor_id::SensorId;
+use crate::settings::{MAX_BRIGHTNESS_STEP, MIN_BOOT_BRIGHTNESS, Setting, Settings};
+
+/// Deepest nesting of the menu tree, counting the root.
+pub const MAX_DEPTH: usize = 2;
+
+pub enum ItemKind {
+    /// Opens a nested menu.
+    Submenu(&'static [MenuItem]),
+    /// One of a fixed list of options; the setting stores the option index.
+    Choice {
+        setting: Setting,
+        options: &'static [&'static str],
+    },
+    /// Number in `min..=max`, edited in `step` increments.
+    Number {
+        setting: Setting,
+        min: u8,
+        max: u8,
+        step: u8,
+        unit: &'static str,
+    },
+    /// Active value of a limit in its chain's edit range and step, shown in the
+    /// preferred display unit.
+    Limit(Limit),
+    /// Read-only number.
+    Value {
+        value: f32,
+        precision: usize,
+        unit: &'static str,
+    },
+    /// Read-only text.
+    Text(&'static str),
+}
+
+pub struct MenuItem {
+    pub label: &'static str,
+    pub kind: ItemKind,
+}
+
+impl MenuItem {
+    const fn new(
+        label: &'static str,
+        kind: ItemKind,
+    ) -> Self {
+        Self { label, kind }
+    }
+
+    /// Value to start editing from, if the item is editable.
+    fn start_edit(
+        &self,
+        settings: &Settings,
+    ) -> Option<Pending> {
+        match self.kind {
+            ItemKind::Limit(limit) => Some(Pending::Limit(limits::get(limit))),
+            _ => self.setting().map(|setting| Pending::Setting(settings.get(setting))),
+        }
+    }
+
+    /// The setting this item edits, if it is editable.
+    pub const fn setting(&self) -> Option<Setting> {
+        match self.kind {
+            ItemKind::Choice { setting, .. } | ItemKind::Number { setting, .. } => Some(setting),
+            _ => None,
+        }
+    }
+
+    /// Write the value column of the item. `pending` replaces the stored value while it is being edited.
+    pub fn write_value<W: Write>(
+        &self,
+        settings: &Settings,
+        pending: Option<Pending>,
+        out: &mut W,
+    ) -> fmt::Result {
+        let stored = self.setting().map(|setting| settings.get(setting));
+        let pending_limit = match pending {
+            Some(Pending::Limit(value)) => Some(value),
+            _ => None,
+        };
+        let pending = match pending {
+            Some(Pending::Setting(value)) => Some(value),
+            _ => None,
+        };
+        match self.kind {
+            ItemKind::Submenu(_) => out.write_str(">"),
+            ItemKind::Choice { options, .. } => {
+                let index = pending.or(stored).unwrap_or(0) as usize;
+                out.write_str(options.get(index).copied().unwrap_or("?"))
+            }
+            ItemKind::Number { unit, .. } => write!(out, "{}{unit}", pending.or(stored).unwrap_or(0)),
+            ItemKind::Limit(limit) => {
+                let quantity = limit.chain().quantity;
+                let value = pending_limit.unwrap_or_else(|| limits::get(limit));
+                settings.units.write(quantity, value, out)?;
+                match settings.units.suffix(quantity) {
+                    "" => {
+                        match settings.units.symbol(quantity) {
+                            "" => Ok(()),
+                            symbol => write!(out, " {symbol}"),
+                        }
+                    }
+                    suffix => out.write_str(suffix),
+                }
+            }
+            ItemKind::Value { value, precision, unit } => write!(out, "{value:.precision$}{unit}"),
+            ItemKind::Text(text) => out.write_str(text),
+        }
+    }
+
+    /// Value after rotating `delta` detents while editing: choices wrap, numbers clamp,
+    /// and limits move in their chain's step without passing a neighbouring limit.
+    fn step(
+        &self,
+        pending: Pending,
+        delta: i32,
+    ) -> Pending {
+        match (&self.kind, pending) {
+            (ItemKind::Choice { options, .. }, Pending::Setting(value)) => {
+                Pending::Setting((value as i32 + delta).rem_euclid(options.len() as i32) as u8)
+            }
+            (&ItemKind::Number { min, max, step, .. }, Pending::Setting(value)) => {
+                Pending::Setting((value as i32 + delta * step as i32).clamp(min as i32, max as i32) as u8)
+            }
+            (&ItemKind::Limit(limit), Pending::Limit(value)) => {
+                let step = limit.chain().step;
+                let (lo, hi) = limits::active().range(limit);
+                Pending::Limit(((micromath::F32(value / step).round().0 + delta as f32) * step).clamp(lo, hi))
+            }
+            _ => pending,
+        }
+    }
+}
+
+const OFF_ON: &[&str] = &["OFF", "ON"];
+
+const LAYOUT_NAMES: [&str; LAYOUTS.len()] = layout_names();
+
+const fn layout_names() -> [&'static str; LAYOUTS.len()] {
+    let mut names = [""; LAYOUTS.len()];
+    let mut i = 0;
+    while i < LAYOUTS.len() {
+        names[i] = LAYOUTS[i].name;
+        i += 1;
+    }
+    names
+}
+
+const VEHICLE_NAMES: [&str; PROFILES.len() + 1] = vehicle_names();
+
+/// `AUTO` first, so the option index is the `Setting::Vehicle` value.
+const fn vehicle_names() -> [&'static str; PROFILES.len() + 1] {
+    let mut names = ["AUTO"; PROFILES.len() + 1];
+    let mut i = 0;
+    while i < PROFILES.len() {
+        names[i + 1] = PROFILES[i].name;
+        i += 1;
+    }
+    names
+}
+
+const THRESHOLDS: [MenuItem; Limit::COUNT] = limit_items();
+
+const fn limit_items() -> [MenuItem; Limit::COUNT] {
+    let mut items = [const { MenuItem::new("", ItemKind::Text("")) }; Limit::COUNT];
+    let mut i = 0;
+    while i < Limit::COUNT {
+        let limit = Limit::ALL[i];
+        items[i] = MenuItem::new(limit.label(), ItemKind::Limit(limit));
+        i += 1;
+    }
+    items
+}
+
+// Option order follows the `TempUnit`, `PressureUnit`, `MixtureUnit` and `Fuel` variants
+const UNITS: &[MenuItem] = &[
+    MenuItem::new(
+        "Temperature",
+        ItemKind::Choice {
+            setting: Setting::TempUnit,
+            options: &["C", "F"],
+        },
+    ),
+    MenuItem::new(
+        "Pressure",
+        ItemKind::Choice {
+            setting: Setting::PressureUnit,
+            options: &["BAR", "PSI", "KPA"],
+        },
+    ),
+    MenuItem::new(
+        "Mixture",
+        ItemKind::Choice {
+            setting: Setting::MixtureUnit,
+            options: &["AFR", "LAMBDA"],
+        },
+    ),
+    MenuItem::new(
+        "Fuel",
+        ItemKind::Choice {
+            setting: Setting::Fuel,
+            options: &["E0", "E10", "E85"],
+        },
+    ),
+    MenuItem::new("Voltage", ItemKind::Text("V")),
+];
+
+const BRIGHTNESS: &[MenuItem] = &[
+    MenuItem::new(
+        "Boot level",
+        ItemKind::Number {
+            setting: Setting::Brightness,
+            min: MIN_BOOT_BRIGHTNESS,
+            max: 100,
+            step: 5,
+            unit: "%",
+        },
+    ),
+    MenuItem::new(
+        "Knob step",
+        ItemKind::Number {
+            setting: Setting::BrightnessStep,
+            min: 1,
+            max: MAX_BRIGHTNESS_STEP,
+            step: 1,
+            unit: "%",
+        },
+    ),
+];
+
+const ALARMS: &[MenuItem] = &[
+    MenuItem::new(
+        "EGT warning",
+        ItemKind::Choice {
+            setting: Setting::EgtAlarm,
+            options: OFF_ON,
+        },
+    ),
+    MenuItem::new(
+        "Critical blink",
+        ItemKind::Choice {
+            setting: Setting::CriticalBlink,
+            options: OFF_ON,
+        },
+    ),
+    MenuItem::new(
+        "Status cues",
+        ItemKind::Choice {
+            setting: Setting::StatusCues,
+            options: OFF_ON,
+        },
+    ),
+];
+
+const ABOUT: &[MenuItem] = &[
+    MenuItem::new("Firmware", ItemKind::Text(env!("CARGO_PKG_VERSION"))),
+    MenuItem::new("Board", ItemKind::Text("Pico 2 (RP2350)")),
+    MenuItem::new(
+        "Sensors",
+        ItemKind::Value {
+            value: SensorId::COUNT as f32,
+            precision: 0,
+            unit: "",
+        },
+    ),
+];
+
+/// Top level of the settings menu.
+pub const ROOT: &[MenuItem] = &[
+    MenuItem::new(
+        "Vehicle",
+        ItemKind::Choice {
+            setting: Setting::Vehicle,
+            options: &VEHICLE_NAMES,
+        },
+    ),
+    MenuItem::new("Units", ItemKind::Submenu(UNITS)),
+    MenuItem::new("Thresholds", ItemKind::Submenu(&THRESHOLDS)),
+    MenuItem::new(
+        "Layout",
+        ItemKind::Choice {
+            setting: Setting::Layout,
+            options: &LAYOUT_NAMES,
+        },
+    ),
+    MenuItem::new("Brightness", ItemKind::Submenu(BRIGHTNESS)),
+    // Option order follows the `ThemeMode` variants
+    MenuItem::new(
+        "Theme",
+        ItemKind::Choice {
+            setting: Setting::Theme,
+            options: &["DAY", "NIGHT", "HI-CONTRAST", "AUTO", "COLOR-SAFE"],
+        },
+    ),
+    MenuItem::new("Alarms", ItemKind::Submenu(ALARMS)),
+    MenuItem::new(
+        "Boot screen",
+        ItemKind::Choice {
+            setting: Setting::BootScreen,
+            options: &["FULL", "LOG ONLY", "SKIP"],
+        },
+    ),
+    MenuItem::new("About", ItemKind::Submenu(ABOUT)),
+];
+
+const fn depth(items: &[MenuItem]) -> usize {
+    let mut deepest = 0;
+    let mut i = 0;
+    while i < items.len() {
+        if let ItemKind::Submenu(children) = items[i].kind {
+            let d = depth(children);
+            if d > deepest {
+                deepest = d;
+            }
+        }
+        i += 1;
+    }
+    deepest + 1
+}
+
+// The state machine keeps a fixed-size stack of open menus
+const _: () = assert!(depth(ROOT) <= MAX_DEPTH);
+
+/// Encoder input to the menu.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuInput {
+    /// Detents turned; positive moves down the list or increases a value.
+    Rotate(i32),
+    Press,
+    LongPress,
+}
+
+/// Value of the item being edited.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pending {
+    Setting(u8),
+    Limit(f32),
+}
+
+/// What the caller has to do after an input.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MenuAction {
+    None,
+    /// A setting was committed with a new value: apply and persist it.
+    Changed(Setting),
+    /// A limit was committed with a new value, inside its editable range: make it
+    /// active and persist the set.
+    LimitChanged(Limit, f32),
+    /// Long-press on the top level: leave the menu.
+    Close,
+}
+
+#[derive(Clone, Copy)]
+struct Level {
+    title: &'static str,
+    items: &'static [MenuItem],
+    cursor: usize,
+}
+
+/// Navigation state of the settings menu.
+///
+/// Rotate moves the cursor (clamped to the list) or, while editing, changes the
+/// pending value. Press opens a submenu, starts editing, or commits the edit.
+/// Long-press cancels an edit, goes up one level, or closes the menu at the top.
+pub struct MenuState {
+    stack: [Level; MAX_DEPTH],
+    depth: usize,
+    pending: Option<Pending>,
+}
+
+impl MenuState {
+    pub const fn new() -> Self {
+        let root = Level {
+            title: "SETTINGS",
+            items: ROOT,
+            cursor: 0,
+        };
+        Self {
+            stack: [root; MAX_DEPTH],
+            depth: 1,
+            pending: None,
+        }
+    }
+
+    #[inline]
+    const fn level(&self) -> &Level { &self.stack[self.depth - 1] }
+
+    #[inline]
+    pub const fn title(&self) -> &'static str { self.level().title }
+
+    #[inline]
+    pub const fn items(&self) -> &'static [MenuItem] { self.level().items }
+
+    #[inline]
+    pub const fn cursor(&self) -> usize { self.level().cursor }
+
+    /// Value being edited on the item under the cursor, if editing.
+    #[inline]
+    pub const fn pending(&self) -> Option<Pending> { self.pending }
+
+    #[inline]
+    pub const fn is_top_level(&self) -> bool { self.depth == 1 }
+
+    pub fn handle(
+        &mut self,
+        input: MenuInput,
+        settings: &mut Settings,
+    ) -> MenuAction {
+        let level = self.stack[self.depth - 1];
+        let item = &level.items[level.cursor];
+
+        match input {
+            MenuInput::Rotate(delta) => {
+                if let Some(value) = self.pending {
+                    self.pending = Some(item.step(value, delta));
+                } else {
+                    let last = level.items.len() as i32 - 1;
+                    self.stack[self.depth - 1].cursor = (level.cursor as i32 + delta).clamp(0, last) as usize;
+                }
+                MenuAction::None
+            }
+            MenuInput::Press => {
+                if let Some(pending) = self.pending.take() {
+                    return match (pending, &item.kind) {
+                        (Pending::Limit(value), &ItemKind::Limit(limit)) if value != limits::get(limit) => {
+                            MenuAction::LimitChanged(limit, value)
+                        }
+                        (Pending::Setting(value), _) => {
+                            match item.setting() {
+                                Some(setting) if settings.set(setting, value) => MenuAction::Changed(setting),
+                                _ => MenuAction::None,
+                            }
+                        }
+                        _ => MenuAction::None,
+                    };
+                }
+                match item.kind {
+                    ItemKind::Submenu(items) if self.depth < MAX_DEPTH => {
+                        self.stack[self.depth] = Level {
+                            title: item.label,
+                            items,
+                            cursor: 0,
+                        };
+                        self.depth += 1;
+                    }
+                    _ => self.pending = item.start_edit(settings),
+                }
+                MenuAction::None
+            }
+            MenuInput::LongPress => {
+                if self.pending.take().is_some() {
+                    MenuAction::None
+                } else if self.depth > 1 {
+                    self.depth -= 1;
+                    MenuAction::None
+                } else {
+                    MenuAction::Close
+                }
+            }
+        }
+    }
+}
+
+impl Default for MenuState {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::sensors::{COOLANT_COLD_MAX, COOLANT_CRITICAL};
+    use crate::settings::BootScreen;
+    use crate::units::{Fuel, MixtureUnit, PressureUnit, TempUnit};
+
+    fn index_of(
+        items: &[MenuItem],
+        label: &str,
+    ) -> i32 {
+        items.iter().position(|item| item.label == label).unwrap() as i32
+    }
+
+    /// Move the cursor from the top of the current list to `label`.
+    fn go_to(
+        menu: &mut MenuState,
+        settings: &mut Settings,
+        label: &str,
+    ) {
+        menu.handle(MenuInput::Rotate(-100), settings);
+        menu.handle(MenuInput::Rotate(index_of(menu.items(), label)), settings);
+        assert_eq!(menu.items()[menu.cursor()].label, label);
+    }
+
+    #[test]
+    fn rotate_clamps_cursor_to_list() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+
+        assert_eq!(menu.handle(MenuInput::Rotate(-3), &mut settings), MenuAction::None);
+        assert_eq!(menu.cursor(), 0);
+        menu.handle(MenuInput::Rotate(2), &mut settings);
+        assert_eq!(menu.cursor(), 2);
+        menu.handle(MenuInput::Rotate(100), &mut settings);
+        assert_eq!(menu.cursor(), ROOT.len() - 1);
+    }
+
+    #[test]
+    fn press_opens_submenu_and_long_press_returns_to_parent_item() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+
+        go_to(&mut menu, &mut settings, "Alarms");
+        menu.handle(MenuInput::Press, &mut settings);
+        assert_eq!(menu.title(), "Alarms");
+        assert!(!menu.is_top_level());
+        assert_eq!(menu.cursor(), 0);
+
+        menu.handle(MenuInput::Rotate(1), &mut settings);
+        assert_eq!(menu.items()[menu.cursor()].label, "Critical blink");
+
+        assert_eq!(menu.handle(MenuInput::LongPress, &mut settings), MenuAction::None);
+        assert!(menu.is_top_level());
+        assert_eq!(menu.items()[menu.cursor()].label, "Alarms");
+    }
+
+    #[test]
+    fn long_press_at_top_level_closes() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+        assert_eq!(menu.handle(MenuInput::LongPress, &mut settings), MenuAction::Close);
+    }
+
+    #[test]
+    fn choice_edit_wraps_and_commits_on_press() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+
+        go_to(&mut menu, &mut settings, "Boot screen");
+        menu.handle(MenuInput::Press, &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Setting(BootScreen::Full as u8)));
+
+        // Three options: two steps back from FULL wraps round to LOG ONLY
+        menu.handle(MenuInput::Rotate(-2), &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Setting(BootScreen::LogOnly as u8)));
+        assert_eq!(settings.boot_screen, BootScreen::Full, "not applied before commit");
+
+        assert_eq!(
+            menu.handle(MenuInput::Press, &mut settings),
+            MenuAction::Changed(Setting::BootScreen)
+        );
+        assert_eq!(settings.boot_screen, BootScreen::LogOnly);
+        assert_eq!(menu.pending(), None);
+    }
+
+    #[test]
+    fn long_press_cancels_edit_without_changing_setting() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+
+        go_to(&mut menu, &mut settings, "Layout");
+        menu.handle(MenuInput::Press, &mut settings);
+        menu.handle(MenuInput::Rotate(1), &mut settings);
+        assert_eq!(menu.handle(MenuInput::LongPress, &mut settings), MenuAction::None);
+
+        assert_eq!(menu.pending(), None);
+        assert!(menu.is_top_level(), "cancelling an edit does not leave the level");
+        assert_eq!(settings, Settings::new());
+    }
+
+    #[test]
+    fn number_edit_clamps_to_range() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+
+        go_to(&mut menu, &mut settings, "Brightness");
+        menu.handle(MenuInput::Press, &mut settings);
+        menu.handle(MenuInput::Press, &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Setting(100)));
+
+        menu.handle(MenuInput::Rotate(3), &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Setting(100)));
+        menu.handle(MenuInput::Rotate(-4), &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Setting(80)));
+        menu.handle(MenuInput::Rotate(-100), &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Setting(MIN_BOOT_BRIGHTNESS)));
+
+        assert_eq!(
+            menu.handle(MenuInput::Press, &mut settings),
+            MenuAction::Changed(Setting::Brightness)
+        );
+        assert_eq!(settings.brightness, MIN_BOOT_BRIGHTNESS);
+    }
+
+    #[test]
+    fn committing_unchanged_value_reports_nothing() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+
+        go_to(&mut menu, &mut settings, "Layout");
+        menu.handle(MenuInput::Press, &mut settings);
+        assert_eq!(menu.handle(MenuInput::Press, &mut settings), MenuAction::None);
+    }
+
+    #[test]
+    fn read_only_items_ignore_press() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+
+        go_to(&mut menu, &mut settings, "About");
+        menu.handle(MenuInput::Press, &mut settings);
+        menu.handle(MenuInput::Press, &mut settings);
+        assert_eq!(menu.pending(), None);
+        assert_eq!(menu.title(), "About");
+    }
+
+    #[test]
+    fn every_editable_value_is_accepted_by_its_setting() {
+        fn check(items: &[MenuItem]) {
+            for item in items {
+                match item.kind {
+                    ItemKind::Submenu(children) => check(children),
+                    ItemKind::Choice { setting, options } => {
+                        for index in 0..options.len() {
+                            assert!(setting.accepts(index as u8), "{}", item.label);
+                        }
+                        assert!(!setting.accepts(options.len() as u8), "{}", item.label);
+                    }
+                    ItemKind::Number { setting, min, max, .. } => {
+                        assert!(setting.accepts(min) && setting.accepts(max), "{}", item.label);
+                    }
+                    ItemKind::Limit(_) | ItemKind::Value { .. } | ItemKind::Text(_) => {}
+                }
+            }
+        }
+        check(ROOT);
+    }
+
+    #[test]
+    fn value_column_shows_pending_value_while_editing() {
+        let settings = Settings::new();
+        let item = &BRIGHTNESS[0];
+        let mut s: heapless::String<16> = heapless::String::new();
+
+        item.write_value(&settings, None, &mut s).unwrap();
+        assert_eq!(s.as_str(), "100%");
+        s.clear();
+        item.write_value(&settings, Some(Pending::Setting(35)), &mut s).unwrap();
+        assert_eq!(s.as_str(), "35%");
+    }
+
+    #[test]
+    fn limit_edit_stops_short_of_neighbouring_limit() {
+        let mut menu = MenuState::new();
+        let mut settings = Settings::new();
+
+        go_to(&mut menu, &mut settings, "Thresholds");
+        menu.handle(MenuInput::Press, &mut settings);
+        go_to(&mut menu, &mut settings, "Coolant cold");
+        menu.handle(MenuInput::Press, &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Limit(COOLANT_COLD_MAX)));
+
+        menu.handle(MenuInput::Rotate(3), &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Limit(COOLANT_COLD_MAX + 3.0)));
+        menu.handle(MenuInput::Rotate(100), &mut settings);
+        assert_eq!(menu.pending(), Some(Pending::Limit(COOLANT_CRITICAL - 1.0)));
+
+        assert_eq!(
+            menu.handle(MenuInput::Press, &mut settings),
+            MenuAction::LimitChanged(Limit::CoolantColdMax, COOLANT_CRITICAL - 1.0)
+        );
+        assert_eq!(
+            limits::get(Limit::CoolantColdMax),
+            COOLANT_COLD_MAX,
+            "applied by the caller"
+        );
+
+        menu.handle(MenuInput::Press, &mut settings);
+        assert_eq!(
+            menu.handle(MenuInput::Press, &mut settings),
+            MenuAction::None,
+            "unchanged"
+        );
+    }
+
+    #[test]
+    fn threshold_readouts_follow_unit_preferences() {
+        let mut settings = Settings::new();
+        let boost = THRESHOLDS.iter().find(|item| item.label == "Boost crit").unwrap();
+        let coolant = THRESHOLDS.iter().find(|item| item.label == "Coolant crit").unwrap();
+        let mut s: heapless::String<16> = heapless::String::new();
+
+        boost.write_value(&settings, None, &mut s).unwrap();
+        assert_eq!(s.as_str(), "1.90 BAR");
+
+        settings.set(Setting::PressureUnit, PressureUnit::Kpa as u8);
+        settings.set(Setting::TempUnit, TempUnit::Fahrenheit as u8);
+        s.clear();
+        boost.write_value(&settings, None, &mut s).unwrap();
+        assert_eq!(s.as_str(), "190 KPA");
+        s.clear();
+        coolant.write_value(&settings, None, &mut s).unwrap();
+        assert_eq!(s.as_str(), "194F");
+    }
+
+    #[test]
+    fn mixture_readout_scales_with_fuel() {
+        let mut settings = Settings::new();
+        let lean = THRESHOLDS.iter().find(|item| item.label == "Lean crit").unwrap();
+        let mut s: heapless::String<16> = heapless::String::new();
+
+        lean.write_value(&settings, None, &mut s).unwrap();
+        assert_eq!(s.as_str(), "15.4 AFR");
+
+        settings.set(Setting::Fuel, Fuel::E85 as u8);
+        s.clear();
+        lean.write_value(&settings, None, &mut s).unwrap();
+        assert_eq!(s.as_str(), "10.3 AFR");
+
+        settings.set(Setting::MixtureUnit, MixtureUnit::Lambda as u8);
+        s.clear();
+        lean.write_value(&settings, None, &mut s).unwrap();
+        assert_eq!(s.as_str(), "1.05 LAMBDA");
+    }
+}
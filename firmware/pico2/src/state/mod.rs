@@ -1,11 +1,27 @@
-mod button;
-mod input;
-mod pages;
+mod graph_view;
 mod popup;
+mod sensor_bank;
 mod sensor_state;
+mod sensors;
 
-pub use button::ButtonState;
-pub use input::process_buttons;
-pub use pages::Page;
-pub use popup::Popup;
+pub use dashboard_pico2::alerts::{AlertManager, AlertRule, RULES, Severity};
+pub use dashboard_pico2::classifier::{BandClassifier, Threshold};
+pub use dashboard_pico2::filter::{Filter, FilterSpec, SensorFilter};
+pub use dashboard_pico2::history::{TieredHistory, ZOOM_POINTS, Zoom};
+pub use dashboard_pico2::input::{Action, Buttons, DEFAULT_KEYMAP, InputEvent};
+pub use dashboard_pico2::layouts::{Grid, LAYOUTS, Widget};
+pub use dashboard_pico2::menu::{ItemKind, MenuAction, MenuInput, MenuState};
+pub use dashboard_pico2::overlay::{Overlay, OverlaySpec, OverlayStack, Priority};
+pub use dashboard_pico2::pages::Page;
+pub use dashboard_pico2::plausibility::{CrossChecker, Fault, Plausibility, PlausibilityCheck};
+pub use dashboard_pico2::profiles::{self, PROFILES, VehicleProfile};
+pub use dashboard_pico2::sensor_id::SensorId;
+pub use dashboard_pico2::session::{SESSION_RECORD_LEN, SensorSummary, SessionAutosave, SessionRecord};
+pub use dashboard_pico2::settings::{BootScreen, SETTINGS_RECORD_LEN, Setting, Settings};
+pub use dashboard_pico2::trend::{RateUnit, SlopeEstimator, TrendSpec, round_eta_s, time_to_flag};
+pub use dashboard_pico2::zones::{MAX_BANDS, ZoneStats, ZoneTracker};
+pub use graph_view::{GraphView, MAX_TRACES};
+pub use popup::{POPUP_DURATION_MS, POPUP_DURATION_SHORT_MS, Popup};
+pub use sensor_bank::{SensorBank, SensorChannel};
 pub use sensor_state::{GRAPH_HISTORY_SIZE, SensorState};
+pub use sensors::{SensorDescriptor, descriptor};
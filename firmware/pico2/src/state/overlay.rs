@@ -0,0 +1,233 @@
+//! Overlays: popups drawn over the current page.
+//!
+//! Every overlay says how it stacks, how long it stays and what it covers through its
+//! `OverlaySpec`, and draws itself. `OverlayStack` keeps the shown ones in stacking
+//! order; only the top one is drawn, and the ones below it keep their place (and their
+//! timeouts keep running) until it closes. Once the covered region changes,
+//! `RenderState` has the page repaint what was underneath.
+
+use core::mem::discriminant;
+
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::DrawTarget;
+use embedded_graphics::primitives::Rectangle;
+use heapless::Vec;
+
+/// Overlays shown at once; showing one more drops the bottom one.
+pub const MAX_OVERLAYS: usize = 4;
+
+/// Stacking order: an overlay is shown above every overlay of a lower priority, and
+/// above older ones of the same priority.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Priority {
+    /// Stays until its condition is dealt with, e.g. a raised alert.
+    Alert,
+    /// Confirms a button or encoder action. Short enough to be shown over an alert
+    /// rather than lost behind it.
+    Feedback,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OverlaySpec {
+    pub priority: Priority,
+    /// Area the overlay draws over, border included.
+    pub region: Rectangle,
+    /// Closes this long after it was last shown; `None` stays until dismissed.
+    pub timeout_ms: Option<u64>,
+    /// Closes when the page changes.
+    pub page_scoped: bool,
+}
+
+/// Content of an overlay. Overlays of the same enum variant are one overlay: showing
+/// one again replaces it, with its new content and a fresh timeout.
+pub trait Overlay {
+    /// State the overlay reads when drawn, besides its own content.
+    type Context<'a>;
+
+    fn spec(&self) -> OverlaySpec;
+
+    /// Render callback, drawn over the page within `spec().region`.
+    fn draw<D>(
+        &self,
+        display: &mut D,
+        context: &Self::Context<'_>,
+    ) where
+        D: DrawTarget<Color = Rgb565>;
+}
+
+struct Shown<T> {
+    overlay: T,
+    shown_at_ms: u64,
+}
+
+/// Shown overlays, bottom first.
+pub struct OverlayStack<T: Overlay> {
+    shown: Vec<Shown<T>, MAX_OVERLAYS>,
+}
+
+impl<T: Overlay> OverlayStack<T> {
+    pub const fn new() -> Self { Self { shown: Vec::new() } }
+
+    /// Show `overlay` above everything of its priority, replacing the one of its kind.
+    pub fn show(
+        &mut self,
+        overlay: T,
+        now_ms: u64,
+    ) {
+        self.dismiss(|shown| discriminant(shown) == discriminant(&overlay));
+        if self.shown.is_full() {
+            self.shown.remove(0);
+        }
+        let priority = overlay.spec().priority;
+        let index = self
+            .shown
+            .iter()
+            .position(|shown| shown.overlay.spec().priority > priority)
+            .unwrap_or(self.shown.len());
+        let _ = self.shown.insert(
+            index,
+            Shown {
+                overlay,
+                shown_at_ms: now_ms,
+            },
+        );
+    }
+
+    /// Close every overlay `matches` picks. Returns whether any was shown.
+    pub fn dismiss(
+        &mut self,
+        matches: impl Fn(&T) -> bool,
+    ) -> bool {
+        let len = self.shown.len();
+        self.shown.retain(|shown| !matches(&shown.overlay));
+        self.shown.len() != len
+    }
+
+    /// Close the overlays whose timeout has run out.
+    pub fn expire(
+        &mut self,
+        now_ms: u64,
+    ) {
+        self.shown.retain(|shown| {
+            match shown.overlay.spec().timeout_ms {
+                Some(timeout_ms) => now_ms.saturating_sub(shown.shown_at_ms) < timeout_ms,
+                None => true,
+            }
+        });
+    }
+
+    /// Close the overlays that belong to the page being left.
+    pub fn leave_page(&mut self) { self.dismiss(|overlay| overlay.spec().page_scoped); }
+
+    /// The overlay that is drawn.
+    pub fn top(&self) -> Option<&T> { self.shown.last().map(|shown| &shown.overlay) }
+
+    /// Whether any shown overlay, drawn or waiting, satisfies `matches`.
+    pub fn any(
+        &self,
+        matches: impl Fn(&T) -> bool,
+    ) -> bool {
+        self.shown.iter().any(|shown| matches(&shown.overlay))
+    }
+
+    /// Area the top overlay covers.
+    pub fn region(&self) -> Option<Rectangle> { self.top().map(|overlay| overlay.spec().region) }
+
+    pub fn draw<D>(
+        &self,
+        display: &mut D,
+        context: &T::Context<'_>,
+    ) where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if let Some(overlay) = self.top() {
+            overlay.draw(display, context);
+        }
+    }
+}
+
+impl<T: Overlay> Default for OverlayStack<T> {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::prelude::{Point, Size};
+
+    use super::*;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum TestOverlay {
+        Alert(u8),
+        Toast(u8),
+        Sticky,
+    }
+
+    impl Overlay for TestOverlay {
+        type Context<'a> = ();
+
+        fn spec(&self) -> OverlaySpec {
+            let (priority, timeout_ms, page_scoped, width) = match self {
+                Self::Alert(_) => (Priority::Alert, None, false, 200),
+                Self::Toast(_) => (Priority::Feedback, Some(1000), true, 100),
+                Self::Sticky => (Priority::Feedback, None, true, 50),
+            };
+            OverlaySpec {
+                priority,
+                region: Rectangle::new(Point::zero(), Size::new(width, 40)),
+                timeout_ms,
+                page_scoped,
+            }
+        }
+
+        fn draw<D>(
+            &self,
+            _display: &mut D,
+            _context: &(),
+        ) where
+            D: DrawTarget<Color = Rgb565>,
+        {
+        }
+    }
+
+    #[test]
+    fn higher_priority_stays_on_top() {
+        let mut stack = OverlayStack::new();
+        stack.show(TestOverlay::Toast(1), 0);
+        stack.show(TestOverlay::Alert(3), 10);
+        assert_eq!(stack.top(), Some(&TestOverlay::Toast(1)), "alert waits behind feedback");
+
+        stack.show(TestOverlay::Sticky, 20);
+        assert_eq!(stack.top(), Some(&TestOverlay::Sticky), "newest of equal priority");
+
+        stack.dismiss(|overlay| *overlay == TestOverlay::Sticky);
+        stack.expire(1000);
+        assert_eq!(stack.top(), Some(&TestOverlay::Alert(3)));
+        assert_eq!(stack.region().map(|region| region.size.width), Some(200));
+    }
+
+    #[test]
+    fn same_kind_replaces_and_restarts_timeout() {
+        let mut stack = OverlayStack::new();
+        stack.show(TestOverlay::Toast(1), 0);
+        stack.show(TestOverlay::Toast(2), 800);
+        assert!(!stack.any(|overlay| *overlay == TestOverlay::Toast(1)));
+
+        stack.expire(1500);
+        assert_eq!(stack.top(), Some(&TestOverlay::Toast(2)));
+        stack.expire(1800);
+        assert_eq!(stack.top(), None);
+    }
+
+    #[test]
+    fn page_change_closes_page_scoped_overlays() {
+        let mut stack = OverlayStack::new();
+        stack.show(TestOverlay::Alert(0), 0);
+        stack.show(TestOverlay::Toast(0), 0);
+        stack.show(TestOverlay::Sticky, 0);
+        stack.leave_page();
+        assert_eq!(stack.top(), Some(&TestOverlay::Alert(0)));
+        assert!(stack.dismiss(|overlay| matches!(overlay, TestOverlay::Alert(_))));
+        assert!(!stack.dismiss(|overlay| matches!(overlay, TestOverlay::Alert(_))));
+    }
+}
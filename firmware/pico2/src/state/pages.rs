@@ -3,18 +3,33 @@ pub enum Page {
     #[default]
     Dashboard,
 
+    /// Several sensors overlaid on one time axis.
+    Graph,
+
+    /// Time-in-zone bar charts for one sensor over the session.
+    Stats,
+
     Debug,
 
     Logs,
+
+    /// Encoder-driven settings menu.
+    Settings,
+
+    /// Full-screen view of one sensor, opened from a selected dashboard cell.
+    Focus,
 }
 
 impl Page {
     #[inline]
     pub const fn toggle(self) -> Self {
         match self {
-            Self::Dashboard => Self::Debug,
+            Self::Dashboard => Self::Graph,
+            Self::Graph => Self::Stats,
+            Self::Stats => Self::Debug,
             Self::Debug => Self::Logs,
-            Self::Logs => Self::Dashboard,
+            Self::Logs => Self::Settings,
+            Self::Settings | Self::Focus => Self::Dashboard,
         }
     }
 }
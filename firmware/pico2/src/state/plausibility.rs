@@ -0,0 +1,333 @@
+//! Plausibility checks on raw sensor samples.
+//!
+//! A reading outside what the sensor can physically report, one that jumps faster
+//! than the quantity can change, or one frozen at the same value is a sensor or
+//! wiring fault rather than an engine condition. Such samples are flagged so the
+//! dashboard shows a fault instead of an overheat, and are kept out of statistics.
+
+use heapless::Vec;
+
+use crate::sensor_id::SensorId;
+use crate::thresholds::COOLANT_COLD_MAX;
+
+/// A fault stays reported this long after the last implausible sample, so an
+/// intermittent fault does not flicker in and out.
+pub const FAULT_HOLD_MS: u32 = 2_000;
+
+/// What is plausible for one sensor, in base units.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Plausibility {
+    /// Lowest reading the sensor can physically report.
+    pub min: f32,
+    /// Highest reading the sensor can physically report.
+    pub max: f32,
+    /// Fastest real change, in base units per second.
+    pub max_rate_per_s: Option<f32>,
+    /// A raw value unchanged for this long is stuck. Only for sensors whose raw
+    /// signal always carries some noise while the engine runs.
+    pub stuck_ms: Option<u32>,
+}
+
+impl Plausibility {
+    pub const fn new(
+        min: f32,
+        max: f32,
+        max_rate_per_s: Option<f32>,
+        stuck_ms: Option<u32>,
+    ) -> Self {
+        Self {
+            min,
+            max,
+            max_rate_per_s,
+            stuck_ms,
+        }
+    }
+
+    pub const fn is_valid(&self) -> bool {
+        let rate_ok = match self.max_rate_per_s {
+            Some(rate) => rate > 0.0,
+            None => true,
+        };
+        self.min < self.max && rate_ok
+    }
+}
+
+/// Why a sensor's reading is not trusted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fault {
+    /// Outside the physical range.
+    Range,
+    /// Changed faster than the quantity can.
+    Jump,
+    /// Frozen at one raw value.
+    Stuck,
+    /// Contradicts a related sensor.
+    CrossCheck,
+}
+
+impl Fault {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Range => "RANGE",
+            Self::Jump => "JUMP",
+            Self::Stuck => "STUCK",
+            Self::CrossCheck => "XCHECK",
+        }
+    }
+}
+
+/// Running plausibility state of one sensor.
+#[derive(Clone, Copy, Debug)]
+pub struct PlausibilityCheck {
+    /// Last in-range sample and its time, the reference for the rate check.
+    last: Option<(f32, u64)>,
+    /// When the raw value last changed.
+    changed_ms: u64,
+    /// Reported fault and the time of the last sample that showed it.
+    fault: Option<(Fault, u64)>,
+}
+
+impl PlausibilityCheck {
+    pub const fn new() -> Self {
+        Self {
+            last: None,
+            changed_ms: 0,
+            fault: None,
+        }
+    }
+
+    /// Check the raw sample `raw` taken at `now_ms` and return the fault to report.
+    pub fn check(
+        &mut self,
+        raw: f32,
+        now_ms: u64,
+        spec: &Plausibility,
+    ) -> Option<Fault> {
+        // NaN fails the range check too
+        if !(spec.min..=spec.max).contains(&raw) {
+            return self.flag(Fault::Range, now_ms);
+        }
+
+        let found = match self.last {
+            Some((last, since)) => {
+                if raw != last {
+                    self.changed_ms = now_ms;
+                }
+                let dt_s = now_ms.saturating_sub(since) as f32 / 1_000.0;
+                let jumped = spec
+                    .max_rate_per_s
+                    .is_some_and(|rate| dt_s > 0.0 && (raw - last).abs() > rate * dt_s);
+                let stuck = spec
+                    .stuck_ms
+                    .is_some_and(|stuck| now_ms - self.changed_ms >= u64::from(stuck));
+                if jumped {
+                    Some(Fault::Jump)
+                } else if stuck {
+                    Some(Fault::Stuck)
+                } else {
+                    None
+                }
+            }
+            None => {
+                self.changed_ms = now_ms;
+                None
+            }
+        };
+        self.last = Some((raw, now_ms));
+
+        match found {
+            Some(fault) => self.flag(fault, now_ms),
+            None => {
+                if let Some((_, seen)) = self.fault
+                    && now_ms - seen >= u64::from(FAULT_HOLD_MS)
+                {
+                    self.fault = None;
+                }
+                self.fault()
+            }
+        }
+    }
+
+    /// Report `fault` from `now_ms`, e.g. from a cross-check, and return it.
+    pub const fn flag(
+        &mut self,
+        fault: Fault,
+        now_ms: u64,
+    ) -> Option<Fault> {
+        self.fault = Some((fault, now_ms));
+        Some(fault)
+    }
+
+    /// Fault currently reported, if any.
+    pub fn fault(&self) -> Option<Fault> { self.fault.map(|(fault, _)| fault) }
+}
+
+impl Default for PlausibilityCheck {
+    fn default() -> Self { Self::new() }
+}
+
+/// A sensor that must not read far below a related one once that one is past a level.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CrossCheck {
+    pub sensor: SensorId,
+    pub reference: SensorId,
+    /// The check applies while the reference reads above this.
+    pub active_above: f32,
+    /// Largest plausible gap below the reference.
+    pub max_below: f32,
+    /// How long the gap has to persist, to allow for normal lag between the two.
+    pub hold_ms: u32,
+}
+
+impl CrossCheck {
+    const fn violated(
+        &self,
+        value: f32,
+        reference: f32,
+    ) -> bool {
+        reference > self.active_above && value < reference - self.max_below
+    }
+}
+
+pub const CROSS_CHECKS: [CrossCheck; 1] = [
+    // Oil warms slower than coolant but catches up within minutes of warm-up;
+    // far below it for longer means a failed oil sender
+    CrossCheck {
+        sensor: SensorId::Oil,
+        reference: SensorId::Coolant,
+        active_above: COOLANT_COLD_MAX,
+        max_below: 45.0,
+        hold_ms: 120_000,
+    },
+];
+
+/// Tracks how long each of `CROSS_CHECKS` has been violated.
+#[derive(Clone, Copy, Debug)]
+pub struct CrossChecker {
+    since: [Option<u64>; CROSS_CHECKS.len()],
+}
+
+impl CrossChecker {
+    pub const fn new() -> Self {
+        Self {
+            since: [None; CROSS_CHECKS.len()],
+        }
+    }
+
+    /// Evaluate every cross-check at `now_ms` and return the sensors failing one.
+    /// `value` returns `None` for a sensor that is already faulty, which skips the
+    /// checks that involve it.
+    pub fn update(
+        &mut self,
+        now_ms: u64,
+        value: impl Fn(SensorId) -> Option<f32>,
+    ) -> Vec<SensorId, { CROSS_CHECKS.len() }> {
+        let mut failed = Vec::new();
+        for (check, since) in CROSS_CHECKS.iter().zip(&mut self.since) {
+            let violated = match (value(check.sensor), value(check.reference)) {
+                (Some(value), Some(reference)) => check.violated(value, reference),
+                _ => false,
+            };
+            if !violated {
+                *since = None;
+                continue;
+            }
+            let start = *since.get_or_insert(now_ms);
+            if now_ms - start >= u64::from(check.hold_ms) {
+                let _ = failed.push(check.sensor);
+            }
+        }
+        failed
+    }
+}
+
+impl Default for CrossChecker {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLANT: Plausibility = Plausibility::new(-40.0, 150.0, Some(20.0), None);
+    const AFR: Plausibility = Plausibility::new(0.5, 1.6, Some(10.0), Some(5_000));
+
+    #[test]
+    fn out_of_range_is_a_fault() {
+        let mut check = PlausibilityCheck::new();
+        assert_eq!(check.check(90.0, 0, &COOLANT), None);
+        assert_eq!(check.check(215.0, 10, &COOLANT), Some(Fault::Range));
+        assert_eq!(check.check(f32::NAN, 20, &COOLANT), Some(Fault::Range));
+    }
+
+    #[test]
+    fn jump_faster_than_max_rate_is_a_fault() {
+        let mut check = PlausibilityCheck::new();
+        check.check(90.0, 0, &COOLANT);
+        assert_eq!(check.check(90.1, 10, &COOLANT), None);
+        assert_eq!(check.check(60.0, 20, &COOLANT), Some(Fault::Jump));
+    }
+
+    #[test]
+    fn fault_holds_then_clears() {
+        let mut check = PlausibilityCheck::new();
+        check.check(90.0, 0, &COOLANT);
+        check.check(215.0, 100, &COOLANT);
+        assert_eq!(check.check(90.0, 200, &COOLANT), Some(Fault::Range), "held");
+        assert_eq!(
+            check.check(90.0, 100 + u64::from(FAULT_HOLD_MS) - 1, &COOLANT),
+            Some(Fault::Range)
+        );
+        assert_eq!(check.check(90.0, 100 + u64::from(FAULT_HOLD_MS), &COOLANT), None);
+    }
+
+    #[test]
+    fn frozen_value_is_stuck() {
+        let mut check = PlausibilityCheck::new();
+        let mut now = 0;
+        while now < 5_000 {
+            assert_eq!(check.check(0.95, now, &AFR), None, "{now} ms");
+            now += 20;
+        }
+        assert_eq!(check.check(0.95, now, &AFR), Some(Fault::Stuck));
+        assert_eq!(check.check(0.96, now + 20, &AFR), Some(Fault::Stuck), "held");
+        assert_eq!(check.check(0.95, now + 20 + u64::from(FAULT_HOLD_MS), &AFR), None);
+        assert_eq!(
+            PlausibilityCheck::new().check(90.0, 10_000, &COOLANT),
+            None,
+            "coolant has no stuck check"
+        );
+    }
+
+    #[test]
+    fn cross_check_needs_a_persistent_gap_after_warm_up() {
+        let check = CROSS_CHECKS[0];
+        let mut checker = CrossChecker::new();
+        let readings = |oil: f32, coolant: f32| {
+            move |id| {
+                match id {
+                    SensorId::Oil => Some(oil),
+                    SensorId::Coolant => Some(coolant),
+                    _ => None,
+                }
+            }
+        };
+
+        assert!(checker.update(0, readings(20.0, 60.0)).is_empty(), "still warming up");
+        assert!(checker.update(1_000, readings(20.0, 90.0)).is_empty());
+        assert!(
+            checker
+                .update(1_000 + u64::from(check.hold_ms) - 1, readings(20.0, 90.0))
+                .is_empty()
+        );
+        assert_eq!(
+            checker
+                .update(1_000 + u64::from(check.hold_ms), readings(20.0, 90.0))
+                .as_slice(),
+            &[SensorId::Oil]
+        );
+
+        assert!(checker.update(200_000, readings(70.0, 90.0)).is_empty(), "gap closed");
+        assert!(checker.update(500_000, |_| None).is_empty(), "faulty sensors skip");
+    }
+}
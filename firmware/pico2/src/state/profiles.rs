@@ -0,0 +1,290 @@
+//! Vehicle profiles: what the dashboard needs to know about the car it is fitted to.
+//!
+//! A profile names the car and its gearbox, says where the engine and gearbox
+//! controllers answer on the diagnostic bus and which data identifiers carry each
+//! sensor, and holds the limits and layout that suit the car. At boot the profile is
+//! matched from the VIN unless one is picked in the settings menu.
+
+use crate::layouts::LAYOUTS;
+use crate::limits::{Limit, Thresholds};
+use crate::sensor_id::SensorId;
+
+/// Request and response CAN identifiers of a diagnostic controller.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EcuAddress {
+    pub request: u16,
+    pub response: u16,
+}
+
+impl EcuAddress {
+    pub const fn new(
+        request: u16,
+        response: u16,
+    ) -> Self {
+        Self { request, response }
+    }
+}
+
+/// Engine controller on the standard OBD functional pair.
+pub const ENGINE_ECU: EcuAddress = EcuAddress::new(0x7E0, 0x7E8);
+
+/// Gearbox controller on the standard OBD functional pair.
+pub const GEARBOX_TCU: EcuAddress = EcuAddress::new(0x7E1, 0x7E9);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gearbox {
+    /// 7-speed wet-clutch DSG.
+    Dq381,
+    /// 6-speed wet-clutch DSG.
+    Dq250,
+    Manual,
+}
+
+impl Gearbox {
+    /// Boot log line.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Dq381 => "DQ381 7-speed DSG",
+            Self::Dq250 => "DQ250 6-speed DSG",
+            Self::Manual => "6-speed manual",
+        }
+    }
+
+    /// Gearbox controller and the data identifier of its clutch oil temperature;
+    /// `None` for a gearbox without one. The identifiers are manufacturer-specific
+    /// and can differ between software versions of the same gearbox.
+    pub const fn fluid_temp(self) -> Option<(EcuAddress, u16)> {
+        match self {
+            Self::Dq381 => Some((GEARBOX_TCU, 0x2104)),
+            Self::Dq250 => Some((GEARBOX_TCU, 0x1940)),
+            Self::Manual => None,
+        }
+    }
+}
+
+/// Data identifier the engine controller reports a sensor under.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Did {
+    pub sensor: SensorId,
+    pub id: u16,
+}
+
+impl Did {
+    const fn new(
+        sensor: SensorId,
+        id: u16,
+    ) -> Self {
+        Self { sensor, id }
+    }
+}
+
+/// Engine sensors of the EA888.3, as the OBD parameters mapped into the `0xF4xx`
+/// identifier range.
+const EA888_DIDS: &[Did] = &[
+    Did::new(SensorId::Boost, 0xF40B),
+    Did::new(SensorId::Afr, 0xF434),
+    Did::new(SensorId::Battery, 0xF442),
+    Did::new(SensorId::Coolant, 0xF405),
+    Did::new(SensorId::Oil, 0xF45C),
+    Did::new(SensorId::Iat, 0xF40F),
+    Did::new(SensorId::Egt, 0xF478),
+    Did::new(SensorId::Rpm, 0xF40C),
+];
+
+/// VINs a profile applies to: the manufacturer code (positions 1–3) and the model
+/// code (positions 7–8).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VinMatch {
+    pub manufacturer: &'static str,
+    pub model: &'static str,
+}
+
+impl VinMatch {
+    const fn new(
+        manufacturer: &'static str,
+        model: &'static str,
+    ) -> Self {
+        Self { manufacturer, model }
+    }
+
+    pub fn matches(
+        &self,
+        vin: &str,
+    ) -> bool {
+        vin.len() == VIN_LEN && vin.get(..3) == Some(self.manufacturer) && vin.get(6..8) == Some(self.model)
+    }
+}
+
+const VIN_LEN: usize = 17;
+
+pub struct VehicleProfile {
+    /// Settings menu and log name, at most 13 characters.
+    pub name: &'static str,
+    /// Model line of the boot log.
+    pub model: &'static str,
+    pub vin: VinMatch,
+    pub gearbox: Gearbox,
+    pub engine: EcuAddress,
+    /// Identifiers of the engine sensors; the gearbox one follows from `gearbox`.
+    pub dids: &'static [Did],
+    /// Limits that differ from the defaults. Applied in order, each within the
+    /// edit rules of `Thresholds::set`, so move the far end of a chain first.
+    pub limits: &'static [(Limit, f32)],
+    /// Index into `LAYOUTS`, applied when the profile is picked.
+    pub layout: usize,
+}
+
+impl VehicleProfile {
+    /// Default limits with the profile's overrides applied.
+    pub fn thresholds(&self) -> Thresholds {
+        let mut thresholds = Thresholds::new();
+        for &(limit, value) in self.limits {
+            thresholds.set(limit, value);
+        }
+        thresholds
+    }
+
+    /// Whether the car reports `sensor` at all; a manual gearbox has no DSG temperature.
+    pub const fn has_sensor(
+        &self,
+        sensor: SensorId,
+    ) -> bool {
+        !matches!(sensor, SensorId::Dsg) || self.gearbox.fluid_temp().is_some()
+    }
+
+    /// Controller and data identifier to read `sensor` from.
+    pub fn source(
+        &self,
+        sensor: SensorId,
+    ) -> Option<(EcuAddress, u16)> {
+        if sensor == SensorId::Dsg {
+            return self.gearbox.fluid_temp();
+        }
+        self.dids
+            .iter()
+            .find(|did| did.sensor == sensor)
+            .map(|did| (self.engine, did.id))
+    }
+}
+
+pub static PROFILES: [VehicleProfile; 3] = [
+    // The defaults in `config::sensors` are this car's limits
+    VehicleProfile {
+        name: "CUPRA 300",
+        model: "Leon Cupra 5F FL | 2.0 TSI 300HP",
+        vin: VinMatch::new("VSS", "5F"),
+        gearbox: Gearbox::Dq381,
+        engine: ENGINE_ECU,
+        dids: EA888_DIDS,
+        limits: &[],
+        layout: 0,
+    },
+    VehicleProfile {
+        name: "GOLF GTI",
+        model: "Golf 7 GTI | 2.0 TSI 230HP",
+        vin: VinMatch::new("WVW", "AU"),
+        gearbox: Gearbox::Dq250,
+        engine: ENGINE_ECU,
+        dids: EA888_DIDS,
+        limits: &[
+            (Limit::BoostHigh, 1.3),
+            (Limit::BoostCritical, 1.6),
+            (Limit::DsgCritical, 115.0),
+            (Limit::DsgHigh, 105.0),
+            (Limit::DsgElevated, 95.0),
+        ],
+        layout: 0,
+    },
+    VehicleProfile {
+        name: "OCTAVIA RS MT",
+        model: "Octavia RS 5E | 2.0 TSI 245HP",
+        vin: VinMatch::new("TMB", "5E"),
+        gearbox: Gearbox::Manual,
+        engine: ENGINE_ECU,
+        dids: EA888_DIDS,
+        limits: &[(Limit::BoostHigh, 1.4), (Limit::BoostCritical, 1.8)],
+        layout: 1,
+    },
+];
+
+/// Profile used when none is picked and the VIN matches none.
+pub const DEFAULT_PROFILE: usize = 0;
+
+/// `Setting::Vehicle` value that matches the profile from the VIN; a picked profile
+/// is stored as its index plus one.
+pub const AUTO: u8 = 0;
+
+/// Index into `PROFILES` of the profile to use: the picked one, else the first whose
+/// VIN pattern matches, else the default.
+pub fn resolve(
+    selection: u8,
+    vin: Option<&str>,
+) -> usize {
+    if selection != AUTO && usize::from(selection) <= PROFILES.len() {
+        return usize::from(selection) - 1;
+    }
+    vin.and_then(|vin| PROFILES.iter().position(|profile| profile.vin.matches(vin)))
+        .unwrap_or(DEFAULT_PROFILE)
+}
+
+const fn profiles_are_valid() -> bool {
+    let mut i = 0;
+    while i < PROFILES.len() {
+        let profile = &PROFILES[i];
+        if profile.layout >= LAYOUTS.len()
+            || profile.name.len() > 13
+            || profile.vin.manufacturer.len() != 3
+            || profile.vin.model.len() != 2
+        {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Every profile names an existing layout, fits the menu and has a well-formed VIN pattern
+const _: () = assert!(profiles_are_valid());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vin_picks_profile_unless_one_is_selected() {
+        assert_eq!(resolve(AUTO, Some("WVWZZZAUZJW123456")), 1);
+        assert_eq!(resolve(AUTO, Some("TMBZZZ5EZK8123456")), 2);
+        assert_eq!(resolve(AUTO, Some("WBA00000000000000")), DEFAULT_PROFILE, "unknown car");
+        assert_eq!(resolve(AUTO, Some("WVWZZZAU")), DEFAULT_PROFILE, "truncated VIN");
+        assert_eq!(resolve(AUTO, None), DEFAULT_PROFILE);
+        assert_eq!(resolve(3, Some("WVWZZZAUZJW123456")), 2, "manual pick wins");
+        assert_eq!(resolve(PROFILES.len() as u8 + 1, None), DEFAULT_PROFILE, "stale pick");
+    }
+
+    #[test]
+    fn every_override_takes_effect() {
+        for profile in &PROFILES {
+            let thresholds = profile.thresholds();
+            assert!(thresholds.is_valid());
+            for &(limit, value) in profile.limits {
+                assert_eq!(thresholds.get(limit), value, "{} {:?}", profile.name, limit);
+            }
+        }
+    }
+
+    #[test]
+    fn manual_gearbox_has_no_dsg_source() {
+        let manual = &PROFILES[2];
+        assert!(!manual.has_sensor(SensorId::Dsg));
+        assert_eq!(manual.source(SensorId::Dsg), None);
+        assert_eq!(manual.source(SensorId::Oil), Some((ENGINE_ECU, 0xF45C)));
+
+        // The DSG slot of the full layout shows the spare instead
+        assert_eq!(LAYOUTS[0].slots[5].sensor, SensorId::Dsg);
+        assert_eq!(LAYOUTS[0].slot(5, manual).sensor, SensorId::Rpm);
+
+        let dsg = &PROFILES[0];
+        assert!(dsg.has_sensor(SensorId::Dsg));
+        assert_eq!(dsg.source(SensorId::Dsg).map(|(ecu, _)| ecu), Some(GEARBOX_TCU));
+    }
+}
@@ -0,0 +1,262 @@
+use super::sensors::{Extremes, SENSORS, SensorDescriptor, descriptor};
+use super::{
+    BandClassifier,
+    CrossChecker,
+    Fault,
+    PlausibilityCheck,
+    SensorFilter,
+    SensorId,
+    SensorState,
+    SensorSummary,
+    SessionRecord,
+    SlopeEstimator,
+    TieredHistory,
+    ZoneTracker,
+    time_to_flag,
+};
+use crate::tasks::DemoSensorValues;
+use crate::ui::Status;
+use crate::units::Quantity;
+
+/// Live value, running extremes and display statistics for one sensor.
+pub struct SensorChannel {
+    /// Filtered value, as displayed.
+    pub value: f32,
+    /// Last unfiltered sample from the source.
+    pub raw: f32,
+    /// What the critical flag, alerts and extremes look at: `raw` or `value`, per the
+    /// descriptor's filter spec.
+    pub alarm: f32,
+    /// Set while the raw samples are implausible; `value` and `alarm` then hold the last
+    /// good reading, and statistics, zones, history and trend skip the sensor.
+    pub fault: Option<Fault>,
+    /// Commanded value from the ECU, if the sensor has one and the last sample carried it.
+    pub target: Option<f32>,
+    pub min: f32,
+    pub max: f32,
+    /// Colour band of the value, debounced by the descriptor's threshold hysteresis and dwell.
+    pub status: Status,
+    /// Critical flag of the value, debounced the same way.
+    pub critical: bool,
+    pub state: SensorState,
+    /// Long-term min/max/avg buckets; kept across a statistics reset.
+    pub history: TieredHistory,
+    /// Least-squares rate of change over the descriptor's trend window, in base units per second.
+    pub slope_per_s: Option<f32>,
+    /// Trend arrow: rising, falling, or `None` while the rate is under the sensor's arrow threshold.
+    pub trend: Option<bool>,
+    /// Seconds until the value turns critical if it keeps its current trend.
+    pub critical_in_s: Option<f32>,
+    /// Time in and entries into each threshold band since the last statistics reset.
+    pub zones: ZoneTracker,
+    slope: SlopeEstimator,
+    status_band: BandClassifier<Status>,
+    critical_band: BandClassifier<bool>,
+    filter: SensorFilter,
+    plausibility: PlausibilityCheck,
+}
+
+impl SensorChannel {
+    fn new(desc: &SensorDescriptor) -> Self {
+        Self {
+            value: desc.initial,
+            raw: desc.initial,
+            alarm: desc.initial,
+            fault: None,
+            target: None,
+            min: f32::MAX,
+            max: f32::MIN,
+            status: (desc.status_fn)(desc.initial),
+            critical: (desc.critical_fn)(desc.initial),
+            state: SensorState::new(),
+            history: TieredHistory::new(history_resolution(desc.quantity)),
+            slope_per_s: None,
+            trend: None,
+            critical_in_s: None,
+            zones: ZoneTracker::new(),
+            slope: SlopeEstimator::new(desc.trend.window_ms),
+            status_band: BandClassifier::new(),
+            critical_band: BandClassifier::new(),
+            filter: SensorFilter::new(),
+            plausibility: PlausibilityCheck::new(),
+        }
+    }
+}
+
+/// Owns the state of every sensor in the descriptor table.
+pub struct SensorBank {
+    channels: [SensorChannel; SensorId::COUNT],
+    cross_checks: CrossChecker,
+}
+
+impl SensorBank {
+    pub fn new() -> Self {
+        Self {
+            channels: core::array::from_fn(|i| SensorChannel::new(&SENSORS[i])),
+            cross_checks: CrossChecker::new(),
+        }
+    }
+
+    #[inline]
+    pub fn get(
+        &self,
+        id: SensorId,
+    ) -> &SensorChannel {
+        &self.channels[id.index()]
+    }
+
+    #[inline]
+    pub fn value(
+        &self,
+        id: SensorId,
+    ) -> f32 {
+        self.channels[id.index()].value
+    }
+
+    /// Value the alarms of sensor `id` look at, `None` while the sensor is faulty.
+    #[inline]
+    pub fn alarm_value(
+        &self,
+        id: SensorId,
+    ) -> Option<f32> {
+        let channel = &self.channels[id.index()];
+        channel.fault.is_none().then_some(channel.alarm)
+    }
+
+    /// Take the latest value of every sensor from a sample published at `now_ms`, check
+    /// it is plausible and run it through the sensor's filter.
+    pub fn ingest(
+        &mut self,
+        values: &DemoSensorValues,
+        now_ms: u64,
+    ) {
+        for (channel, desc) in self.channels.iter_mut().zip(SENSORS.iter()) {
+            channel.raw = (desc.source)(values);
+            channel.fault = channel.plausibility.check(channel.raw, now_ms, &desc.plausibility);
+            if channel.fault.is_some() {
+                // Hold the last good reading; the filter restarts from the first good sample
+                channel.filter.reset();
+            } else {
+                channel.value = channel.filter.apply(&desc.filter.filter, channel.raw, now_ms);
+                channel.alarm = if desc.filter.raw_alarms {
+                    channel.raw
+                } else {
+                    channel.value
+                };
+            }
+            channel.target = desc.target.and_then(|target| target(values));
+        }
+
+        // A sensor held faulty by a cross-check keeps feeding it, so the fault lasts as
+        // long as the contradiction does
+        let channels = &self.channels;
+        let failed = self.cross_checks.update(now_ms, |id| {
+            let channel = &channels[id.index()];
+            matches!(channel.fault, None | Some(Fault::CrossCheck)).then_some(channel.raw)
+        });
+        for id in failed {
+            let channel = &mut self.channels[id.index()];
+            channel.fault = channel.plausibility.flag(Fault::CrossCheck, now_ms);
+        }
+    }
+
+    /// Per-frame update of every sensor that is not faulty: band status, trend, zone times,
+    /// extremes, peak hold, averages and graph history.
+    pub fn update(
+        &mut self,
+        now_ms: u64,
+    ) {
+        for (channel, desc) in self.channels.iter_mut().zip(SENSORS.iter()) {
+            if channel.fault.is_some() {
+                // A faulty reading says nothing about the engine: keep it out of every
+                // statistic, and resume them from the first good sample
+                channel.critical = false;
+                channel.slope_per_s = None;
+                channel.trend = None;
+                channel.critical_in_s = None;
+                channel.slope.reset();
+                channel.zones.pause();
+                continue;
+            }
+
+            let value = channel.value;
+            let thresholds = desc.thresholds();
+            channel.status = channel.status_band.classify(value, now_ms, &thresholds, desc.status_fn);
+            channel.critical = channel
+                .critical_band
+                .classify(channel.alarm, now_ms, &thresholds, desc.critical_fn);
+
+            let extreme = channel.alarm;
+            let is_new_extreme = match desc.extremes {
+                Extremes::None => false,
+                Extremes::Max => extreme > channel.max,
+                Extremes::MinMax => extreme > channel.max || extreme < channel.min,
+            };
+
+            channel.max = channel.max.max(extreme);
+            channel.min = channel.min.min(extreme);
+            channel.state.update(value, is_new_extreme, now_ms);
+            channel.history.update(value, now_ms);
+            channel.zones.update(value, now_ms, &thresholds);
+
+            channel.slope.update(value, now_ms);
+            channel.slope_per_s = channel.slope.slope_per_s();
+            channel.trend = channel.slope_per_s.and_then(|slope| desc.trend.direction(slope));
+            // Only predict from a trend that is showing, not from noise around a steady value
+            channel.critical_in_s = channel
+                .slope_per_s
+                .filter(|_| channel.trend.is_some())
+                .and_then(|slope| time_to_flag(value, slope, &thresholds, desc.critical_fn));
+        }
+    }
+
+    /// Clear averages, graphs, zone statistics and peak hold, and restart extremes from
+    /// the current values. This also starts a new session.
+    pub fn reset_stats(&mut self) {
+        for channel in &mut self.channels {
+            channel.state.reset_average();
+            channel.state.reset_graph();
+            channel.state.reset_peak();
+            channel.zones.reset();
+            channel.max = channel.alarm;
+            channel.min = channel.alarm;
+        }
+    }
+
+    /// Extremes and zone statistics of every sensor since the last reset. All zone
+    /// trackers run on the same clock, so any of them gives the session length.
+    pub fn session_record(&self) -> SessionRecord {
+        let mut record = SessionRecord::new();
+        for (summary, channel) in record.sensors.iter_mut().zip(&self.channels) {
+            *summary = SensorSummary {
+                min: channel.min,
+                max: channel.max,
+                zones: *channel.zones.stats(),
+            };
+            record.duration_ms = record.duration_ms.max(summary.zones.total_ms());
+        }
+        record
+    }
+
+    /// Iterate over every sensor with its descriptor, in `SensorId` order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static SensorDescriptor, &SensorChannel)> {
+        SensorId::ALL
+            .into_iter()
+            .map(|id| (descriptor(id), &self.channels[id.index()]))
+    }
+}
+
+/// Smallest step the long-term history stores, in the base unit. Fine enough for
+/// every display unit while keeping the sensor's full range within an `i16`.
+const fn history_resolution(quantity: Quantity) -> f32 {
+    match quantity {
+        Quantity::Temperature => 0.1,
+        Quantity::Pressure | Quantity::Mixture => 0.001,
+        Quantity::Voltage => 0.01,
+        Quantity::EngineSpeed => 1.0,
+    }
+}
+
+impl Default for SensorBank {
+    fn default() -> Self { Self::new() }
+}
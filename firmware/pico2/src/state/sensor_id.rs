@@ -0,0 +1,42 @@
+/// Identifies one of the dashboard's sensors. The discriminant doubles as the
+/// index into per-sensor arrays (`SensorBank`, colour transitions, ...).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum SensorId {
+    Boost,
+    Afr,
+    Battery,
+    Coolant,
+    Oil,
+    Dsg,
+    Iat,
+    Egt,
+    Rpm,
+}
+
+impl SensorId {
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::Boost,
+        Self::Afr,
+        Self::Battery,
+        Self::Coolant,
+        Self::Oil,
+        Self::Dsg,
+        Self::Iat,
+        Self::Egt,
+        Self::Rpm,
+    ];
+    pub const COUNT: usize = 9;
+
+    #[inline]
+    pub const fn index(self) -> usize { self as usize }
+
+    #[inline]
+    pub const fn from_index(index: usize) -> Option<Self> {
+        if index < Self::COUNT {
+            Some(Self::ALL[index])
+        } else {
+            None
+        }
+    }
+}
@@ -1,23 +1,58 @@
-use crate::config::{HISTORY_SIZE, TREND_THRESHOLD};
+//! Per-sensor peak hold, rolling average and graph samples.
+//!
+//! Everything is driven by the timestamp passed to `update`, not by frame count,
+//! so the windows cover the same time at any frame rate, overclock profile or page.
 
 const AVG_BUFFER_SIZE: usize = 60;
 
-const AVG_SAMPLE_INTERVAL: u32 = 250;
+/// Time between samples of the rolling average.
+pub const AVG_SAMPLE_INTERVAL_MS: u32 = 5_000;
+
+/// Span the rolling average settles to once its buffer is full.
+pub const AVG_WINDOW_MS: u32 = AVG_BUFFER_SIZE as u32 * AVG_SAMPLE_INTERVAL_MS;
 
 pub const GRAPH_HISTORY_SIZE: usize = 60;
 
-const GRAPH_SAMPLE_INTERVAL: u32 = 100;
+/// Time between graph samples.
+pub const GRAPH_SAMPLE_INTERVAL_MS: u32 = 2_000;
 
-const PEAK_HOLD_FRAMES: u32 = 30;
+/// How long a new peak stays highlighted.
+pub const PEAK_HOLD_MS: u32 = 600;
 
-pub struct SensorState {
-    history: [f32; HISTORY_SIZE],
-    history_index: usize,
-    history_count: usize,
+/// Fixed-rate sampling clock: fires on the first update, then once per `interval_ms`.
+/// After a stall it fires once and restarts from `now`, instead of catching up with
+/// a burst of identical samples.
+#[derive(Clone, Copy, Debug)]
+struct Sampler {
+    next_ms: Option<u64>,
+}
 
-    prev_value: f32,
+impl Sampler {
+    const fn new() -> Self { Self { next_ms: None } }
+
+    fn due(
+        &mut self,
+        now_ms: u64,
+        interval_ms: u32,
+    ) -> bool {
+        let interval = u64::from(interval_ms);
+        match self.next_ms {
+            Some(next) if now_ms < next => false,
+            Some(next) if now_ms < next + interval => {
+                self.next_ms = Some(next + interval);
+                true
+            }
+            _ => {
+                self.next_ms = Some(now_ms + interval);
+                true
+            }
+        }
+    }
+}
 
-    peak_hold_frames: u32,
+pub struct SensorState {
+    /// End of the current peak hold, if one is running.
+    peak_hold_until: Option<u64>,
 
     pub is_new_peak: bool,
 
@@ -25,12 +60,12 @@ pub struct SensorState {
     avg_index: usize,
     avg_count: usize,
     avg_sum: f32,
-    avg_frame_counter: u32,
+    avg_sampler: Sampler,
 
     graph_buffer: [f32; GRAPH_HISTORY_SIZE],
     graph_index: usize,
     graph_count: usize,
-    graph_frame_counter: u32,
+    graph_sampler: Sampler,
     graph_min: f32,
     graph_max: f32,
 }
@@ -38,21 +73,17 @@ pub struct SensorState {
 impl SensorState {
     pub const fn new() -> Self {
         Self {
-            history: [0.0; HISTORY_SIZE],
-            history_index: 0,
-            history_count: 0,
-            prev_value: 0.0,
-            peak_hold_frames: 0,
+            peak_hold_until: None,
             is_new_peak: false,
             avg_buffer: [0.0; AVG_BUFFER_SIZE],
             avg_index: 0,
             avg_count: 0,
             avg_sum: 0.0,
-            avg_frame_counter: 0,
+            avg_sampler: Sampler::new(),
             graph_buffer: [0.0; GRAPH_HISTORY_SIZE],
             graph_index: 0,
             graph_count: 0,
-            graph_frame_counter: 0,
+            graph_sampler: Sampler::new(),
             graph_min: f32::MAX,
             graph_max: f32::MIN,
         }
@@ -62,33 +93,22 @@ impl SensorState {
         &mut self,
         value: f32,
         is_max_updated: bool,
+        now_ms: u64,
     ) {
-        self.history[self.history_index] = value;
-        self.history_index = (self.history_index + 1) % HISTORY_SIZE;
-        if self.history_count < HISTORY_SIZE {
-            self.history_count += 1;
-        }
-        self.prev_value = value;
-
         if is_max_updated {
-            self.peak_hold_frames = PEAK_HOLD_FRAMES;
+            self.peak_hold_until = Some(now_ms + u64::from(PEAK_HOLD_MS));
             self.is_new_peak = true;
-        } else if self.peak_hold_frames > 0 {
-            self.peak_hold_frames -= 1;
-            if self.peak_hold_frames == 0 {
-                self.is_new_peak = false;
-            }
+        } else if let Some(until) = self.peak_hold_until
+            && now_ms >= until
+        {
+            self.reset_peak();
         }
 
-        self.avg_frame_counter += 1;
-        if self.avg_frame_counter >= AVG_SAMPLE_INTERVAL {
-            self.avg_frame_counter = 0;
+        if self.avg_sampler.due(now_ms, AVG_SAMPLE_INTERVAL_MS) {
             self.add_avg_sample(value);
         }
 
-        self.graph_frame_counter += 1;
-        if self.graph_frame_counter >= GRAPH_SAMPLE_INTERVAL {
-            self.graph_frame_counter = 0;
+        if self.graph_sampler.due(now_ms, GRAPH_SAMPLE_INTERVAL_MS) {
             self.add_graph_sample(value);
         }
     }
@@ -116,12 +136,15 @@ impl SensorState {
         }
     }
 
+    /// Time the average currently covers; grows to `AVG_WINDOW_MS` after a reset.
+    pub const fn average_span_ms(&self) -> u32 { self.avg_count as u32 * AVG_SAMPLE_INTERVAL_MS }
+
     pub fn reset_average(&mut self) {
         self.avg_buffer = [0.0; AVG_BUFFER_SIZE];
         self.avg_index = 0;
         self.avg_count = 0;
         self.avg_sum = 0.0;
-        self.avg_frame_counter = 0;
+        self.avg_sampler = Sampler::new();
     }
 
     fn add_graph_sample(
@@ -193,53 +216,94 @@ impl SensorState {
         )
     }
 
+    /// Time between the oldest and the newest graph sample.
+    pub const fn graph_span_ms(&self) -> u32 { graph_sample_age_ms(self.graph_count.saturating_sub(1)) }
+
     pub fn reset_graph(&mut self) {
         self.graph_buffer = [0.0; GRAPH_HISTORY_SIZE];
         self.graph_index = 0;
         self.graph_count = 0;
-        self.graph_frame_counter = 0;
+        self.graph_sampler = Sampler::new();
         self.graph_min = f32::MAX;
         self.graph_max = f32::MIN;
     }
 
-    pub fn reset_peak(&mut self) {
+    pub const fn reset_peak(&mut self) {
         self.is_new_peak = false;
-        self.peak_hold_frames = 0;
+        self.peak_hold_until = None;
     }
+}
 
-    pub fn get_trend(&self) -> Option<bool> {
-        if self.history_count < 20 {
-            return None;
-        }
+impl Default for SensorState {
+    fn default() -> Self { Self::new() }
+}
 
-        let mut recent_sum = 0.0f32;
-        for i in 0..10 {
-            let idx = (self.history_index + HISTORY_SIZE - 1 - i) % HISTORY_SIZE;
-            recent_sum += self.history[idx];
+/// Age of the graph sample `age` steps back from the newest one.
+#[inline]
+pub const fn graph_sample_age_ms(age: usize) -> u32 { age as u32 * GRAPH_SAMPLE_INTERVAL_MS }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_clock::FakeClock;
+
+    #[test]
+    fn graph_span_does_not_depend_on_frame_rate() {
+        for fps in [25, 40, 60, 100] {
+            let mut clock = FakeClock::new();
+            let mut state = SensorState::new();
+            clock.run(1_000 / fps, 30_000, |now| state.update(1.0, false, now));
+
+            let (_, _, count, ..) = state.get_graph_data();
+            assert_eq!(count, 15, "{fps} fps");
+            assert_eq!(state.graph_span_ms(), 28_000, "{fps} fps");
         }
-        let recent_avg = recent_sum / 10.0;
+    }
 
-        let mut older_sum = 0.0f32;
-        let start = if self.history_count < HISTORY_SIZE {
-            0
-        } else {
-            self.history_index
-        };
-        for i in 0..10 {
-            let idx = (start + i) % HISTORY_SIZE;
-            older_sum += self.history[idx];
-        }
-        let older_avg = older_sum / 10.0;
+    #[test]
+    fn graph_span_caps_at_history_size() {
+        let mut clock = FakeClock::new();
+        let mut state = SensorState::new();
+        clock.run(20, 10 * 60_000, |now| state.update(1.0, false, now));
+        assert_eq!(state.graph_span_ms(), graph_sample_age_ms(GRAPH_HISTORY_SIZE - 1));
+    }
 
-        let diff = recent_avg - older_avg;
-        if diff.abs() < TREND_THRESHOLD {
-            None
-        } else {
-            Some(diff > 0.0)
-        }
+    #[test]
+    fn stall_does_not_burst_samples() {
+        let mut state = SensorState::new();
+        state.update(1.0, false, 0);
+        state.update(2.0, false, 20_000);
+        state.update(3.0, false, 21_000);
+        let (_, _, count, ..) = state.get_graph_data();
+        assert_eq!(count, 2);
+        state.update(4.0, false, 22_000);
+        assert_eq!(state.get_graph_data().2, 3);
     }
-}
 
-impl Default for SensorState {
-    fn default() -> Self { Self::new() }
+    #[test]
+    fn average_covers_a_fixed_window() {
+        let mut clock = FakeClock::new();
+        let mut state = SensorState::new();
+        assert_eq!(state.get_average(), None);
+
+        clock.run(16, AVG_WINDOW_MS.into(), |now| state.update(10.0, false, now));
+        assert_eq!(state.average_span_ms(), AVG_WINDOW_MS);
+        clock.run(16, AVG_WINDOW_MS.into(), |now| state.update(20.0, false, now));
+        assert_eq!(state.get_average(), Some(20.0), "older window fully replaced");
+
+        state.reset_average();
+        assert_eq!(state.average_span_ms(), 0);
+        state.update(5.0, false, clock.now_ms());
+        assert_eq!(state.get_average(), Some(5.0));
+    }
+
+    #[test]
+    fn peak_hold_lasts_its_duration() {
+        let mut state = SensorState::new();
+        state.update(1.0, true, 1_000);
+        state.update(1.0, false, 1_000 + u64::from(PEAK_HOLD_MS) - 1);
+        assert!(state.is_new_peak);
+        state.update(1.0, false, 1_000 + u64::from(PEAK_HOLD_MS));
+        assert!(!state.is_new_peak);
+    }
 }
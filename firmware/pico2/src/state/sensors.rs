@@ -0,0 +1,376 @@
+use heapless::Vec;
+
+use super::{Filter, FilterSpec, MAX_BANDS, Plausibility, RateUnit, SensorId, Threshold, TrendSpec};
+use crate::limits::{self, Limit};
+use crate::tasks::DemoSensorValues;
+use crate::thresholds::{
+    BAND_DWELL_MS,
+    BATT_HYSTERESIS,
+    BOOST_HYSTERESIS,
+    CRITICAL_DWELL_MS,
+    EGT_HYSTERESIS,
+    LAMBDA_HYSTERESIS,
+    RPM_HYSTERESIS,
+    TEMP_HYSTERESIS,
+};
+use crate::ui::Status;
+use crate::units::Quantity;
+use crate::widgets::{
+    GaugeScale,
+    batt_status,
+    boost_status,
+    is_critical_batt,
+    is_critical_boost,
+    is_critical_dsg,
+    is_critical_egt,
+    is_critical_iat,
+    is_critical_lambda,
+    is_critical_oil,
+    is_critical_rpm,
+    is_critical_water,
+    is_low_temp_oil,
+    lambda_status,
+    rpm_status,
+    temp_status_dsg,
+    temp_status_egt,
+    temp_status_iat,
+    temp_status_oil,
+    temp_status_water,
+};
+
+/// Which running extremes are tracked (and shown) for a sensor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Extremes {
+    None,
+    Max,
+    MinMax,
+}
+
+/// A band edge of a sensor: the runtime limit it sits at, with the tuning of a crossing.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Edge {
+    pub limit: Limit,
+    /// How far past the edge a reading has to go before the band changes, in the base unit.
+    pub hysteresis: f32,
+    /// How long the new band has to hold before it is reported.
+    pub dwell_ms: u32,
+}
+
+impl Edge {
+    const fn new(
+        limit: Limit,
+        hysteresis: f32,
+        dwell_ms: u32,
+    ) -> Self {
+        Self {
+            limit,
+            hysteresis,
+            dwell_ms,
+        }
+    }
+}
+
+/// Band edges of one sensor at the active limits.
+pub type BandEdges = Vec<Threshold, { MAX_BANDS - 1 }>;
+
+/// Static description of a sensor: everything the render loop needs besides its live state
+/// and the widget the active layout draws it with.
+pub struct SensorDescriptor {
+    pub id: SensorId,
+    pub label: &'static str,
+    /// Selects display unit and precision; the value itself is always in the base unit.
+    pub quantity: Quantity,
+    /// Colour band edges in ascending order: the limits the status function compares against,
+    /// with the hysteresis and dwell time that debounce band changes at each edge.
+    pub edges: &'static [Edge],
+    /// Band a value falls into; the active theme maps it to a colour.
+    pub status_fn: fn(f32) -> Status,
+    pub critical_fn: fn(f32) -> bool,
+    pub low_fn: Option<fn(f32) -> bool>,
+    /// Extracts this sensor's value from a sample published by the sensor task.
+    pub source: fn(&DemoSensorValues) -> f32,
+    /// Smoothing between `source` and the displayed value.
+    pub filter: FilterSpec,
+    /// Physical range, fastest real change and stuck timeout of the raw samples.
+    pub plausibility: Plausibility,
+    /// Extracts the value the ECU is commanding, for sensors it has a target for.
+    /// The sample may still carry `None` if this ECU does not report one.
+    pub target: Option<fn(&DemoSensorValues) -> Option<f32>>,
+    pub extremes: Extremes,
+    /// Slope window, rate time base and trend arrow threshold.
+    pub trend: TrendSpec,
+    /// Value shown until the first sample arrives.
+    pub initial: f32,
+    /// Scale for `Widget::Gauge`; sensors without one fall back to the generic cell.
+    pub gauge: Option<GaugeScale>,
+}
+
+/// Descriptor table, indexed by `SensorId::index()`.
+pub static SENSORS: [SensorDescriptor; SensorId::COUNT] = [
+    SensorDescriptor {
+        id: SensorId::Boost,
+        label: "BOOST REL",
+        quantity: Quantity::Pressure,
+        edges: &[
+            Edge::new(Limit::BoostHigh, BOOST_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::BoostCritical, BOOST_HYSTERESIS, CRITICAL_DWELL_MS),
+        ],
+        status_fn: boost_status,
+        critical_fn: is_critical_boost,
+        low_fn: None,
+        source: |v| v.boost,
+        filter: FilterSpec::new(Filter::Ema { tau_ms: 150 }, true),
+        plausibility: Plausibility::new(-1.0, 3.0, Some(10.0), None),
+        target: None,
+        extremes: Extremes::Max,
+        trend: TrendSpec::new(1_000, RateUnit::PerSecond, 0.2),
+        initial: 0.5,
+        gauge: Some(GaugeScale {
+            min: 0.0,
+            max: 2.5,
+            major_step: 0.5,
+            minor_divisions: 5,
+            label_divisor: 1.0,
+            label_precision: 1,
+        }),
+    },
+    SensorDescriptor {
+        id: SensorId::Afr,
+        label: "AFR/LAMBDA",
+        quantity: Quantity::Mixture,
+        edges: &[
+            Edge::new(Limit::LambdaRichAf, LAMBDA_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::LambdaRich, LAMBDA_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::LambdaOptimalMax, LAMBDA_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::LambdaLeanCritical, LAMBDA_HYSTERESIS, CRITICAL_DWELL_MS),
+        ],
+        status_fn: lambda_status,
+        critical_fn: is_critical_lambda,
+        low_fn: None,
+        source: |v| v.lambda,
+        filter: FilterSpec::new(Filter::Median { n: 5 }, true),
+        plausibility: Plausibility::new(0.5, 1.6, Some(10.0), Some(10_000)),
+        target: Some(|v| v.lambda_target),
+        extremes: Extremes::None,
+        trend: TrendSpec::new(2_000, RateUnit::PerSecond, 0.05),
+        initial: 0.95,
+        gauge: None,
+    },
+    SensorDescriptor {
+        id: SensorId::Battery,
+        label: "BATT",
+        quantity: Quantity::Voltage,
+        edges: &[
+            Edge::new(Limit::BattCritical, BATT_HYSTERESIS, CRITICAL_DWELL_MS),
+            Edge::new(Limit::BattWarning, BATT_HYSTERESIS, BAND_DWELL_MS),
+        ],
+        status_fn: batt_status,
+        critical_fn: is_critical_batt,
+        low_fn: None,
+        source: |v| v.batt_voltage,
+        filter: FilterSpec::new(Filter::Ema { tau_ms: 500 }, false),
+        plausibility: Plausibility::new(6.0, 18.0, None, None),
+        target: None,
+        extremes: Extremes::MinMax,
+        trend: TrendSpec::new(10_000, RateUnit::PerMinute, 0.3),
+        initial: 12.0,
+        gauge: None,
+    },
+    SensorDescriptor {
+        id: SensorId::Coolant,
+        label: "COOL",
+        quantity: Quantity::Temperature,
+        edges: &[
+            Edge::new(Limit::CoolantColdMax, TEMP_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::CoolantCritical, TEMP_HYSTERESIS, CRITICAL_DWELL_MS),
+        ],
+        status_fn: temp_status_water,
+        critical_fn: is_critical_water,
+        low_fn: None,
+        source: |v| v.water_temp,
+        filter: FilterSpec::new(Filter::Ema { tau_ms: 1_000 }, false),
+        plausibility: Plausibility::new(-40.0, 150.0, Some(20.0), None),
+        target: None,
+        extremes: Extremes::Max,
+        trend: TrendSpec::new(30_000, RateUnit::PerMinute, 1.0),
+        initial: 88.0,
+        gauge: None,
+    },
+    SensorDescriptor {
+        id: SensorId::Oil,
+        label: "OIL",
+        quantity: Quantity::Temperature,
+        edges: &[
+            Edge::new(Limit::OilElevated, TEMP_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::OilHigh, TEMP_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::OilCritical, TEMP_HYSTERESIS, CRITICAL_DWELL_MS),
+        ],
+        status_fn: temp_status_oil,
+        critical_fn: is_critical_oil,
+        low_fn: Some(is_low_temp_oil),
+        source: |v| v.oil_temp,
+        filter: FilterSpec::new(Filter::Ema { tau_ms: 1_000 }, false),
+        plausibility: Plausibility::new(-40.0, 170.0, Some(20.0), None),
+        target: None,
+        extremes: Extremes::Max,
+        trend: TrendSpec::new(30_000, RateUnit::PerMinute, 1.0),
+        initial: 60.0,
+        gauge: None,
+    },
+    SensorDescriptor {
+        id: SensorId::Dsg,
+        label: "DSG",
+        quantity: Quantity::Temperature,
+        edges: &[
+            Edge::new(Limit::DsgElevated, TEMP_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::DsgHigh, TEMP_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::DsgCritical, TEMP_HYSTERESIS, CRITICAL_DWELL_MS),
+        ],
+        status_fn: temp_status_dsg,
+        critical_fn: is_critical_dsg,
+        low_fn: None,
+        source: |v| v.dsg_temp,
+        filter: FilterSpec::new(Filter::Ema { tau_ms: 1_000 }, false),
+        plausibility: Plausibility::new(-40.0, 160.0, Some(20.0), None),
+        target: None,
+        extremes: Extremes::Max,
+        trend: TrendSpec::new(30_000, RateUnit::PerMinute, 1.0),
+        initial: 75.0,
+        gauge: None,
+    },
+    SensorDescriptor {
+        id: SensorId::Iat,
+        label: "IAT",
+        quantity: Quantity::Temperature,
+        edges: &[
+            Edge::new(Limit::IatCold, TEMP_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::IatWarm, TEMP_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::IatHot, TEMP_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::IatCritical, TEMP_HYSTERESIS, CRITICAL_DWELL_MS),
+        ],
+        status_fn: temp_status_iat,
+        critical_fn: is_critical_iat,
+        low_fn: None,
+        source: |v| v.iat_temp,
+        filter: FilterSpec::new(Filter::Ema { tau_ms: 500 }, false),
+        plausibility: Plausibility::new(-40.0, 90.0, Some(30.0), None),
+        target: None,
+        extremes: Extremes::Max,
+        trend: TrendSpec::new(10_000, RateUnit::PerMinute, 3.0),
+        initial: 30.0,
+        gauge: None,
+    },
+    SensorDescriptor {
+        id: SensorId::Egt,
+        label: "EGT",
+        quantity: Quantity::Temperature,
+        edges: &[
+            Edge::new(Limit::EgtColdMax, EGT_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::EgtSpirited, EGT_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::EgtHighLoad, EGT_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::EgtCritical, EGT_HYSTERESIS, CRITICAL_DWELL_MS),
+        ],
+        status_fn: temp_status_egt,
+        critical_fn: is_critical_egt,
+        low_fn: None,
+        source: |v| v.egt_temp,
+        filter: FilterSpec::new(Filter::SlewLimit { per_s: 400.0 }, true),
+        plausibility: Plausibility::new(-40.0, 1_250.0, Some(500.0), Some(30_000)),
+        target: None,
+        extremes: Extremes::Max,
+        trend: TrendSpec::new(4_000, RateUnit::PerSecond, 10.0),
+        initial: 200.0,
+        gauge: None,
+    },
+    SensorDescriptor {
+        id: SensorId::Rpm,
+        label: "RPM",
+        quantity: Quantity::EngineSpeed,
+        edges: &[
+            Edge::new(Limit::RpmShift, RPM_HYSTERESIS, BAND_DWELL_MS),
+            Edge::new(Limit::RpmRedline, RPM_HYSTERESIS, CRITICAL_DWELL_MS),
+        ],
+        status_fn: rpm_status,
+        critical_fn: is_critical_rpm,
+        low_fn: None,
+        source: |v| v.rpm,
+        filter: FilterSpec::NONE,
+        plausibility: Plausibility::new(0.0, 9_000.0, Some(10_000.0), None),
+        target: None,
+        extremes: Extremes::Max,
+        trend: TrendSpec::new(1_000, RateUnit::PerSecond, 500.0),
+        initial: 800.0,
+        gauge: Some(GaugeScale {
+            min: 0.0,
+            max: 7000.0,
+            major_step: 1000.0,
+            minor_divisions: 4,
+            label_divisor: 1000.0,
+            label_precision: 0,
+        }),
+    },
+];
+
+impl SensorDescriptor {
+    /// Band edges at the active limits, lowest first.
+    pub fn thresholds(&self) -> BandEdges {
+        self.edges
+            .iter()
+            .map(|edge| Threshold::new(limits::get(edge.limit), edge.hysteresis, edge.dwell_ms))
+            .collect()
+    }
+}
+
+#[inline]
+pub fn descriptor(id: SensorId) -> &'static SensorDescriptor { &SENSORS[id.index()] }
+
+/// Whether `upper` is above `lower` in the same limit chain, which the runtime
+/// validation keeps ascending.
+const fn follows(
+    lower: Limit,
+    upper: Limit,
+) -> bool {
+    let mut limit = upper;
+    while let Some(below) = limit.below() {
+        if below as usize == lower as usize {
+            return true;
+        }
+        limit = below;
+    }
+    false
+}
+
+const fn table_is_consistent(table: &[SensorDescriptor]) -> bool {
+    let mut i = 0;
+    while i < table.len() {
+        if table[i].id as usize != i {
+            return false;
+        }
+        if let Some(gauge) = &table[i].gauge
+            && !gauge.is_valid()
+        {
+            return false;
+        }
+        if !table[i].trend.is_valid() || !table[i].filter.filter.is_valid() || !table[i].plausibility.is_valid() {
+            return false;
+        }
+        let edges = table[i].edges;
+        if edges.len() >= MAX_BANDS {
+            return false;
+        }
+        let mut j = 0;
+        while j < edges.len() {
+            if edges[j].hysteresis < 0.0 || (j > 0 && !follows(edges[j - 1].limit, edges[j].limit)) {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Every entry must sit at its own SensorId index with band edges ascending in one limit chain, non-negative
+// hysteresis, no more zones than the statistics track, a sane gauge scale, a trend window that fits its samples,
+// valid filter parameters and a non-empty plausible range
+const _: () = assert!(table_is_consistent(&SENSORS));
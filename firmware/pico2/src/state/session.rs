@@ -0,0 +1,289 @@
+//! Summary of a driving session, persisted so the last one survives a power cycle.
+//!
+//! A session runs from power-on or the last statistics reset until power-off or the
+//! next reset. A reset writes the session it ends to flash; the running session is
+//! written every few minutes and when the engine stops (`SessionAutosave`), so the
+//! record found at boot is the last session of the previous power cycle.
+
+use crate::sensor_id::SensorId;
+use crate::settings::fletcher16;
+use crate::zones::{MAX_BANDS, ZoneStats};
+
+const MAGIC: [u8; 4] = *b"DSES";
+
+/// Magic and little-endian `u16` payload length.
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+const CHECKSUM_LEN: usize = 2;
+
+/// Min, max, then zone times and zone excursions.
+const SENSOR_LEN: usize = 4 + 4 + MAX_BANDS * 4 + MAX_BANDS * 2;
+
+const PAYLOAD_LEN: usize = 4 + SensorId::COUNT * SENSOR_LEN;
+
+/// Size of a serialized session record.
+pub const SESSION_RECORD_LEN: usize = HEADER_LEN + PAYLOAD_LEN + CHECKSUM_LEN;
+
+/// Interval between saves of the running session. Each save erases a flash sector
+/// (~100k cycles), so this bounds wear to one erase per five minutes of driving.
+pub const SESSION_SAVE_INTERVAL_MS: u64 = 5 * 60 * 1000;
+
+/// Engine speed above which the engine counts as running.
+pub const ENGINE_RUNNING_RPM: f32 = 300.0;
+
+/// Extremes and zone statistics of one sensor over a session.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SensorSummary {
+    pub min: f32,
+    pub max: f32,
+    pub zones: ZoneStats,
+}
+
+impl SensorSummary {
+    pub const fn new() -> Self {
+        Self {
+            min: f32::MAX,
+            max: f32::MIN,
+            zones: ZoneStats::new(),
+        }
+    }
+}
+
+impl Default for SensorSummary {
+    fn default() -> Self { Self::new() }
+}
+
+/// One session, indexed by `SensorId::index()`.
+///
+/// Record layout: `MAGIC`, payload length, payload, Fletcher-16 of length and payload.
+/// The payload is fixed by the sensor table, so a record of another layout is ignored.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SessionRecord {
+    pub duration_ms: u32,
+    pub sensors: [SensorSummary; SensorId::COUNT],
+}
+
+impl SessionRecord {
+    pub const fn new() -> Self {
+        Self {
+            duration_ms: 0,
+            sensors: [SensorSummary::new(); SensorId::COUNT],
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; SESSION_RECORD_LEN] {
+        let mut bytes = [0u8; SESSION_RECORD_LEN];
+        bytes[..MAGIC.len()].copy_from_slice(&MAGIC);
+        bytes[MAGIC.len()..HEADER_LEN].copy_from_slice(&(PAYLOAD_LEN as u16).to_le_bytes());
+
+        let mut writer = Writer {
+            bytes: &mut bytes[HEADER_LEN..HEADER_LEN + PAYLOAD_LEN],
+            pos: 0,
+        };
+        writer.put(&self.duration_ms.to_le_bytes());
+        for sensor in &self.sensors {
+            writer.put(&sensor.min.to_le_bytes());
+            writer.put(&sensor.max.to_le_bytes());
+            for time in sensor.zones.time_ms {
+                writer.put(&time.to_le_bytes());
+            }
+            for count in sensor.zones.excursions {
+                writer.put(&count.to_le_bytes());
+            }
+        }
+
+        let checksum = fletcher16(&bytes[MAGIC.len()..HEADER_LEN + PAYLOAD_LEN]);
+        bytes[HEADER_LEN + PAYLOAD_LEN..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Parse a stored record. Returns `None` for erased flash, a corrupt record or
+    /// one written for a different sensor table.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < SESSION_RECORD_LEN || bytes[..MAGIC.len()] != MAGIC {
+            return None;
+        }
+        let len = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]) as usize;
+        if len != PAYLOAD_LEN {
+            return None;
+        }
+        let end = HEADER_LEN + PAYLOAD_LEN;
+        let stored = u16::from_le_bytes([bytes[end], bytes[end + 1]]);
+        if fletcher16(&bytes[MAGIC.len()..end]) != stored {
+            return None;
+        }
+
+        let mut reader = Reader {
+            bytes: &bytes[HEADER_LEN..end],
+            pos: 0,
+        };
+        let mut record = Self::new();
+        record.duration_ms = u32::from_le_bytes(reader.take());
+        for sensor in &mut record.sensors {
+            sensor.min = f32::from_le_bytes(reader.take());
+            sensor.max = f32::from_le_bytes(reader.take());
+            for time in &mut sensor.zones.time_ms {
+                *time = u32::from_le_bytes(reader.take());
+            }
+            for count in &mut sensor.zones.excursions {
+                *count = u16::from_le_bytes(reader.take());
+            }
+        }
+        Some(record)
+    }
+}
+
+impl Default for SessionRecord {
+    fn default() -> Self { Self::new() }
+}
+
+/// Decides when the running session is written to flash: once per
+/// `SESSION_SAVE_INTERVAL_MS`, and right away when the engine stops, which usually
+/// comes just before the ignition (and the dashboard) goes off.
+pub struct SessionAutosave {
+    last_save_ms: u64,
+    engine_running: bool,
+    /// Set by a reset until the new session's first periodic save.
+    after_reset: bool,
+}
+
+impl SessionAutosave {
+    pub const fn new() -> Self {
+        Self {
+            last_save_ms: 0,
+            engine_running: false,
+            after_reset: false,
+        }
+    }
+
+    /// The session ended by a reset was just saved. The new one is not saved before it
+    /// has run a full interval, so a short remainder (an engine stop right after the
+    /// reset) cannot replace the ended session.
+    pub fn restart(
+        &mut self,
+        now_ms: u64,
+    ) {
+        self.last_save_ms = now_ms;
+        self.after_reset = true;
+    }
+
+    /// Whether to save now. `rpm` is `None` while the sensor is faulty, which keeps
+    /// the last known engine state rather than reading as a stop.
+    pub fn due(
+        &mut self,
+        now_ms: u64,
+        rpm: Option<f32>,
+    ) -> bool {
+        let was_running = self.engine_running;
+        if let Some(rpm) = rpm {
+            self.engine_running = rpm > ENGINE_RUNNING_RPM;
+        }
+        let stopped = was_running && !self.engine_running && !self.after_reset;
+        if stopped || now_ms.saturating_sub(self.last_save_ms) >= SESSION_SAVE_INTERVAL_MS {
+            self.last_save_ms = now_ms;
+            self.after_reset = false;
+            return true;
+        }
+        false
+    }
+}
+
+impl Default for SessionAutosave {
+    fn default() -> Self { Self::new() }
+}
+
+struct Writer<'a> {
+    bytes: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn put(
+        &mut self,
+        field: &[u8],
+    ) {
+        self.bytes[self.pos..self.pos + field.len()].copy_from_slice(field);
+        self.pos += field.len();
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut field = [0u8; N];
+        field.copy_from_slice(&self.bytes[self.pos..self.pos + N]);
+        self.pos += N;
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SessionRecord {
+        let mut record = SessionRecord::new();
+        record.duration_ms = 3_600_000;
+        let oil = &mut record.sensors[SensorId::Oil.index()];
+        oil.min = 61.5;
+        oil.max = 112.25;
+        oil.zones.time_ms = [600_000, 1_800_000, 900_000, 300_000, 0];
+        oil.zones.excursions = [0, 2, 5, 1, 0];
+        record
+    }
+
+    #[test]
+    fn round_trips() {
+        let record = sample();
+        assert_eq!(SessionRecord::from_bytes(&record.to_bytes()), Some(record));
+    }
+
+    #[test]
+    fn rejects_erased_and_corrupt_records() {
+        assert_eq!(SessionRecord::from_bytes(&[0xFF; SESSION_RECORD_LEN]), None);
+        assert_eq!(SessionRecord::from_bytes(&sample().to_bytes()[..10]), None);
+
+        let mut bytes = sample().to_bytes();
+        bytes[HEADER_LEN + 20] ^= 0x01;
+        assert_eq!(SessionRecord::from_bytes(&bytes), None);
+    }
+
+    #[test]
+    fn autosaves_periodically_and_on_engine_stop() {
+        let mut autosave = SessionAutosave::new();
+        assert!(!autosave.due(1000, Some(0.0)), "ignition on, engine off");
+        assert!(!autosave.due(2000, Some(850.0)));
+        assert!(!autosave.due(3000, None), "faulty sensor is not a stop");
+        assert!(autosave.due(4000, Some(0.0)), "engine stopped");
+        assert!(!autosave.due(5000, Some(0.0)));
+
+        assert!(!autosave.due(6000, Some(2500.0)));
+        assert!(!autosave.due(4000 + SESSION_SAVE_INTERVAL_MS - 1, Some(2500.0)));
+        assert!(autosave.due(4000 + SESSION_SAVE_INTERVAL_MS, Some(2500.0)));
+    }
+
+    #[test]
+    fn reset_keeps_the_ended_session_for_an_interval() {
+        let mut autosave = SessionAutosave::new();
+        assert!(!autosave.due(1000, Some(2500.0)));
+        autosave.restart(2000);
+        assert!(!autosave.due(3000, Some(0.0)), "stop right after the reset");
+        assert!(!autosave.due(2000 + SESSION_SAVE_INTERVAL_MS - 1, Some(2500.0)));
+        assert!(autosave.due(2000 + SESSION_SAVE_INTERVAL_MS, Some(2500.0)));
+        assert!(
+            autosave.due(2000 + SESSION_SAVE_INTERVAL_MS + 1000, Some(0.0)),
+            "stops count again"
+        );
+    }
+
+    #[test]
+    fn rejects_other_layouts() {
+        let mut bytes = sample().to_bytes();
+        bytes[MAGIC.len()] = bytes[MAGIC.len()].wrapping_add(1);
+        assert_eq!(SessionRecord::from_bytes(&bytes), None);
+    }
+}
@@ -0,0 +1,262 @@
+use crate::layouts::{DEFAULT_LAYOUT, LAYOUTS};
+use crate::profiles::{self, PROFILES};
+use crate::theme::ThemeMode;
+use crate::units::{Fuel, MixtureUnit, PressureUnit, TempUnit, UnitPrefs};
+
+const MAGIC: [u8; 4] = *b"DSET";
+
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+const CHECKSUM_LEN: usize = 2;
+
+/// Payload byte order. Only ever append: older records are read by position.
+const FIELDS: [Setting; 13] = [
+    Setting::Layout,
+    Setting::PressureUnit,
+    Setting::Brightness,
+    Setting::BrightnessStep,
+    Setting::EgtAlarm,
+    Setting::CriticalBlink,
+    Setting::BootScreen,
+    Setting::TempUnit,
+    Setting::MixtureUnit,
+    Setting::Fuel,
+    Setting::Theme,
+    Setting::StatusCues,
+    Setting::Vehicle,
+];
+
+const PAYLOAD_LEN: usize = FIELDS.len();
+
+/// Size of a serialized settings record.
+pub const SETTINGS_RECORD_LEN: usize = HEADER_LEN + PAYLOAD_LEN + CHECKSUM_LEN;
+
+/// Lowest boot brightness, so the display never comes up dark.
+pub const MIN_BOOT_BRIGHTNESS: u8 = 5;
+
+/// Upper bound of the brightness adjustment step, in percent.
+pub const MAX_BRIGHTNESS_STEP: u8 = 20;
+
+/// What is shown between power-on and the dashboard.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BootScreen {
+    /// Init log followed by the welcome animation.
+    Full,
+    /// Init log only.
+    LogOnly,
+    /// Straight to the dashboard.
+    Skip,
+}
+
+impl BootScreen {
+    pub const COUNT: usize = 3;
+
+    pub const fn from_index(index: u8) -> Self {
+        match index {
+            1 => Self::LogOnly,
+            2 => Self::Skip,
+            _ => Self::Full,
+        }
+    }
+}
+
+/// A single persisted setting, addressed by the settings menu.
+///
+/// Every setting is stored as one byte: an option index, a number or 0/1.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Setting {
+    /// Index into `LAYOUTS`.
+    Layout,
+    /// `PressureUnit` index; 0 and 1 match the bar/PSI flag of older records.
+    PressureUnit,
+    /// Backlight level at boot, in percent.
+    Brightness,
+    /// Backlight change per encoder detent on the Debug page, in percent.
+    BrightnessStep,
+    /// Full-screen warning when EGT reaches the manifold danger limit.
+    EgtAlarm,
+    /// Critical cells blink instead of staying solid.
+    CriticalBlink,
+    /// `BootScreen` index.
+    BootScreen,
+    /// `TempUnit` index.
+    TempUnit,
+    /// `MixtureUnit` index.
+    MixtureUnit,
+    /// `Fuel` index.
+    Fuel,
+    /// `ThemeMode` index.
+    Theme,
+    /// Border and icon on every reading outside its ideal range, in addition to its colour.
+    StatusCues,
+    /// `profiles::AUTO`, or index into `PROFILES` plus one.
+    Vehicle,
+}
+
+impl Setting {
+    /// Whether `value` is in range; out-of-range values from a corrupt or newer
+    /// record are ignored on load.
+    pub const fn accepts(
+        self,
+        value: u8,
+    ) -> bool {
+        match self {
+            Self::Layout => (value as usize) < LAYOUTS.len(),
+            Self::EgtAlarm | Self::CriticalBlink | Self::StatusCues => value <= 1,
+            Self::Brightness => value >= MIN_BOOT_BRIGHTNESS && value <= 100,
+            Self::BrightnessStep => value >= 1 && value <= MAX_BRIGHTNESS_STEP,
+            Self::BootScreen => (value as usize) < BootScreen::COUNT,
+            Self::PressureUnit => (value as usize) < PressureUnit::COUNT,
+            Self::TempUnit => (value as usize) < TempUnit::COUNT,
+            Self::MixtureUnit => (value as usize) < MixtureUnit::COUNT,
+            Self::Fuel => (value as usize) < Fuel::COUNT,
+            Self::Theme => (value as usize) < ThemeMode::COUNT,
+            Self::Vehicle => (value as usize) <= PROFILES.len(),
+        }
+    }
+}
+
+/// User settings persisted to flash.
+///
+/// Record layout: `MAGIC`, payload length, payload bytes, Fletcher-16 of length and payload.
+/// New fields are appended to the payload; a shorter record from older firmware
+/// keeps defaults for the fields it does not contain.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Settings {
+    pub layout: u8,
+    pub units: UnitPrefs,
+    pub brightness: u8,
+    pub brightness_step: u8,
+    pub egt_alarm: bool,
+    pub critical_blink: bool,
+    pub boot_screen: BootScreen,
+    pub theme: ThemeMode,
+    pub status_cues: bool,
+    pub vehicle: u8,
+}
+
+impl Settings {
+    pub const fn new() -> Self {
+        Self {
+            layout: DEFAULT_LAYOUT as u8,
+            units: UnitPrefs::new(),
+            brightness: 100,
+            brightness_step: 5,
+            egt_alarm: true,
+            critical_blink: true,
+            boot_screen: BootScreen::Full,
+            theme: ThemeMode::Day,
+            status_cues: true,
+            vehicle: profiles::AUTO,
+        }
+    }
+
+    /// Index into `LAYOUTS`, falling back to the default if the stored one no longer exists.
+    pub const fn layout_index(&self) -> usize {
+        if (self.layout as usize) < LAYOUTS.len() {
+            self.layout as usize
+        } else {
+            DEFAULT_LAYOUT
+        }
+    }
+
+    pub const fn get(
+        &self,
+        setting: Setting,
+    ) -> u8 {
+        match setting {
+            Setting::Layout => self.layout,
+            Setting::PressureUnit => self.units.pressure as u8,
+            Setting::Brightness => self.brightness,
+            Setting::BrightnessStep => self.brightness_step,
+            Setting::EgtAlarm => self.egt_alarm as u8,
+            Setting::CriticalBlink => self.critical_blink as u8,
+            Setting::BootScreen => self.boot_screen as u8,
+            Setting::TempUnit => self.units.temperature as u8,
+            Setting::MixtureUnit => self.units.mixture as u8,
+            Setting::Fuel => self.units.fuel as u8,
+            Setting::Theme => self.theme as u8,
+            Setting::StatusCues => self.status_cues as u8,
+            Setting::Vehicle => self.vehicle,
+        }
+    }
+
+    /// Store `value` if the setting accepts it. Returns whether anything changed.
+    pub fn set(
+        &mut self,
+        setting: Setting,
+        value: u8,
+    ) -> bool {
+        if !setting.accepts(value) || self.get(setting) == value {
+            return false;
+        }
+        match setting {
+            Setting::Layout => self.layout = value,
+            Setting::PressureUnit => self.units.pressure = PressureUnit::from_index(value),
+            Setting::Brightness => self.brightness = value,
+            Setting::BrightnessStep => self.brightness_step = value,
+            Setting::EgtAlarm => self.egt_alarm = value != 0,
+            Setting::CriticalBlink => self.critical_blink = value != 0,
+            Setting::BootScreen => self.boot_screen = BootScreen::from_index(value),
+            Setting::TempUnit => self.units.temperature = TempUnit::from_index(value),
+            Setting::MixtureUnit => self.units.mixture = MixtureUnit::from_index(value),
+            Setting::Fuel => self.units.fuel = Fuel::from_index(value),
+            Setting::Theme => self.theme = ThemeMode::from_index(value),
+            Setting::StatusCues => self.status_cues = value != 0,
+            Setting::Vehicle => self.vehicle = value,
+        }
+        true
+    }
+
+    pub fn to_bytes(&self) -> [u8; SETTINGS_RECORD_LEN] {
+        let mut bytes = [0u8; SETTINGS_RECORD_LEN];
+        bytes[..MAGIC.len()].copy_from_slice(&MAGIC);
+        bytes[MAGIC.len()] = PAYLOAD_LEN as u8;
+        for (byte, setting) in bytes[HEADER_LEN..HEADER_LEN + PAYLOAD_LEN].iter_mut().zip(FIELDS) {
+            *byte = self.get(setting);
+        }
+
+        let checksum = fletcher16(&bytes[MAGIC.len()..HEADER_LEN + PAYLOAD_LEN]);
+        bytes[HEADER_LEN + PAYLOAD_LEN..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Parse a stored record. Returns `None` for erased flash or a corrupt record.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_LEN || bytes[..MAGIC.len()] != MAGIC {
+            return None;
+        }
+
+        let len = bytes[MAGIC.len()] as usize;
+        let end = HEADER_LEN + len;
+        if bytes.len() < end + CHECKSUM_LEN {
+            return None;
+        }
+
+        let stored = u16::from_le_bytes([bytes[end], bytes[end + 1]]);
+        if fletcher16(&bytes[MAGIC.len()..end]) != stored {
+            return None;
+        }
+
+        let payload = &bytes[HEADER_LEN..end];
+        let mut settings = Self::new();
+        for (&byte, setting) in payload.iter().zip(FIELDS) {
+            settings.set(setting, byte);
+        }
+        Some(settings)
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self { Self::new() }
+}
+
+pub(crate) fn fletcher16(data: &[u8]) -> u16 {
+    let mut sum1: u16 = 0;
+    let mut sum2: u16 = 0;
+    for &byte in data {
+        sum1 = (sum1 + u16::from(byte)) % 255;
+        sum2 = (sum2 + sum1) % 255;
+    }
+    (sum2 << 8) | sum1
+}
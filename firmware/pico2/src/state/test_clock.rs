@@ -0,0 +1,27 @@
+//! Frame loop stand-in shared by the unit tests of the time-driven state.
+
+/// Advances time by a fixed frame period, like the render loop at a steady rate.
+pub struct FakeClock {
+    now_ms: u64,
+}
+
+impl FakeClock {
+    pub const fn new() -> Self { Self { now_ms: 0 } }
+
+    pub const fn now_ms(&self) -> u64 { self.now_ms }
+
+    /// Call `frame` with the time of every frame in the next `duration_ms`, `step_ms`
+    /// apart and starting now, then stop the clock at the first frame after them.
+    pub fn run(
+        &mut self,
+        step_ms: u64,
+        duration_ms: u64,
+        mut frame: impl FnMut(u64),
+    ) {
+        let end = self.now_ms + duration_ms;
+        while self.now_ms < end {
+            frame(self.now_ms);
+            self.now_ms += step_ms;
+        }
+    }
+}
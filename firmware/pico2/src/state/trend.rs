@@ -0,0 +1,262 @@
+//! Rate of change of a sensor reading.
+//!
+//! `SlopeEstimator` fits a least-squares line through the samples of the last
+//! time window, so the rate comes out in base units per second regardless of
+//! frame rate or noise on single samples. `TrendSpec` sets the window and the
+//! rate a sensor must change by before it shows a trend arrow, in the unit of
+//! time that suits it: EGT moves by the second, coolant by the minute.
+
+use crate::classifier::Threshold;
+
+/// Samples fitted per window; the sample interval is the window divided by this.
+pub const SLOPE_SAMPLES: usize = 32;
+
+/// Fewest samples a slope is fitted over, so a restart does not read one step as a trend.
+const MIN_SAMPLES: usize = 8;
+
+/// Predictions further out than this are not reported.
+pub const PREDICTION_HORIZON_S: f32 = 120.0;
+
+/// Time base a rate is shown in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RateUnit {
+    PerSecond,
+    PerMinute,
+}
+
+impl RateUnit {
+    pub const fn seconds(self) -> f32 {
+        match self {
+            Self::PerSecond => 1.0,
+            Self::PerMinute => 60.0,
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::PerSecond => "/s",
+            Self::PerMinute => "/min",
+        }
+    }
+}
+
+/// How a sensor's trend is estimated and shown.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TrendSpec {
+    /// Span of samples the slope is fitted over.
+    pub window_ms: u32,
+    /// Time base of the displayed rate and of `arrow`.
+    pub per: RateUnit,
+    /// Smallest rate, in base units per `per`, that shows a trend arrow.
+    pub arrow: f32,
+}
+
+impl TrendSpec {
+    pub const fn new(
+        window_ms: u32,
+        per: RateUnit,
+        arrow: f32,
+    ) -> Self {
+        Self { window_ms, per, arrow }
+    }
+
+    /// `slope_per_s` in base units per `per`.
+    #[inline]
+    pub fn rate(
+        &self,
+        slope_per_s: f32,
+    ) -> f32 {
+        slope_per_s * self.per.seconds()
+    }
+
+    /// Trend arrow for `slope_per_s`: `Some(true)` rising, `Some(false)` falling,
+    /// `None` while the rate stays under `arrow`.
+    pub fn direction(
+        &self,
+        slope_per_s: f32,
+    ) -> Option<bool> {
+        let rate = self.rate(slope_per_s);
+        (rate.abs() >= self.arrow).then_some(rate > 0.0)
+    }
+
+    pub const fn is_valid(&self) -> bool { self.window_ms as usize >= SLOPE_SAMPLES && self.arrow > 0.0 }
+}
+
+/// Ring of the last `SLOPE_SAMPLES` readings with their timestamps.
+#[derive(Clone, Copy, Debug)]
+pub struct SlopeEstimator {
+    samples: [(u64, f32); SLOPE_SAMPLES],
+    index: usize,
+    count: usize,
+    interval_ms: u32,
+    next_ms: u64,
+}
+
+impl SlopeEstimator {
+    pub const fn new(window_ms: u32) -> Self {
+        Self {
+            samples: [(0, 0.0); SLOPE_SAMPLES],
+            index: 0,
+            count: 0,
+            interval_ms: window_ms / SLOPE_SAMPLES as u32,
+            next_ms: 0,
+        }
+    }
+
+    /// Record `value` if a sample is due at `now_ms`. Samples keep their real
+    /// timestamps, so a stalled frame only thins the window instead of skewing it.
+    pub fn update(
+        &mut self,
+        value: f32,
+        now_ms: u64,
+    ) {
+        if self.count > 0 && now_ms < self.next_ms {
+            return;
+        }
+        self.samples[self.index] = (now_ms, value);
+        self.index = (self.index + 1) % SLOPE_SAMPLES;
+        self.count = (self.count + 1).min(SLOPE_SAMPLES);
+        self.next_ms = now_ms + u64::from(self.interval_ms);
+    }
+
+    /// Least-squares slope over the window in base units per second, once enough
+    /// samples are in.
+    pub fn slope_per_s(&self) -> Option<f32> {
+        if self.count < MIN_SAMPLES {
+            return None;
+        }
+        let newest = self.samples[(self.index + SLOPE_SAMPLES - 1) % SLOPE_SAMPLES].0;
+        let samples = || {
+            (0..self.count).map(|i| {
+                let (t, v) = self.samples[(self.index + SLOPE_SAMPLES - 1 - i) % SLOPE_SAMPLES];
+                // Seconds relative to the newest sample keep the sums small enough for f32
+                (-((newest - t) as f32) / 1_000.0, v)
+            })
+        };
+
+        let n = self.count as f32;
+        let (sum_t, sum_v) = samples().fold((0.0, 0.0), |(st, sv), (t, v)| (st + t, sv + v));
+        let (mean_t, mean_v) = (sum_t / n, sum_v / n);
+        let (cov, var) = samples().fold((0.0, 0.0), |(cov, var), (t, v)| {
+            let dt = t - mean_t;
+            (cov + dt * (v - mean_v), var + dt * dt)
+        });
+        (var > 0.0).then(|| cov / var)
+    }
+
+    /// Drop all samples, e.g. after the source was reset.
+    pub const fn reset(&mut self) {
+        self.index = 0;
+        self.count = 0;
+    }
+}
+
+/// Seconds until `value`, moving at `slope_per_s`, reaches a band where `flag` holds,
+/// judged at the band edges ahead of it. `None` if it is already there, is not heading
+/// towards one, or would take longer than `PREDICTION_HORIZON_S`.
+pub fn time_to_flag(
+    value: f32,
+    slope_per_s: f32,
+    thresholds: &[Threshold],
+    flag: impl Fn(f32) -> bool,
+) -> Option<f32> {
+    if flag(value) || slope_per_s == 0.0 {
+        return None;
+    }
+    let rising = slope_per_s > 0.0;
+    let ahead = |edge: &&Threshold| if rising { edge.value > value } else { edge.value < value };
+    // Probe just past each edge so `>` and `>=` comparisons both count as crossed
+    let crosses = |edge: &Threshold| {
+        let step = edge.value.abs().max(1.0) * 1e-4;
+        flag(if rising { edge.value + step } else { edge.value - step })
+    };
+
+    let edge = if rising {
+        thresholds.iter().filter(ahead).find(|edge| crosses(edge))
+    } else {
+        thresholds.iter().rev().filter(ahead).find(|edge| crosses(edge))
+    }?;
+    let seconds = (edge.value - value) / slope_per_s;
+    (seconds <= PREDICTION_HORIZON_S).then_some(seconds)
+}
+
+/// Prediction rounded for display: to the second under 20 s, to 5 s above, since
+/// the rate behind it rarely holds that precisely.
+pub fn round_eta_s(seconds: f32) -> u32 {
+    let seconds = seconds.max(0.0) as u32;
+    if seconds < 20 { seconds } else { (seconds + 2) / 5 * 5 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_clock::FakeClock;
+
+    const OIL: TrendSpec = TrendSpec::new(30_000, RateUnit::PerMinute, 1.0);
+
+    #[test]
+    fn ramp_gives_its_slope_at_any_frame_rate() {
+        for fps in [25, 60, 100] {
+            let mut estimator = SlopeEstimator::new(OIL.window_ms);
+            FakeClock::new().run(1_000 / fps, 40_000, |now| {
+                estimator.update(90.0 + now as f32 * 0.0005, now)
+            });
+            let slope = estimator.slope_per_s().unwrap();
+            assert!((slope - 0.5).abs() < 1e-3, "{fps} fps: {slope}");
+            assert!((OIL.rate(slope) - 30.0).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn noise_averages_out() {
+        let mut estimator = SlopeEstimator::new(OIL.window_ms);
+        // Deterministic +-0.25 jitter, uncorrelated with the sample clock
+        FakeClock::new().run(16, 40_000, |now| {
+            let hash = now.wrapping_mul(2_654_435_761) % 1_000;
+            estimator.update(90.0 + (hash as f32 / 1_000.0 - 0.5) / 2.0, now);
+        });
+        let slope = estimator.slope_per_s().unwrap();
+        assert_eq!(OIL.direction(slope), None, "{slope}");
+    }
+
+    #[test]
+    fn needs_a_few_samples() {
+        let mut estimator = SlopeEstimator::new(OIL.window_ms);
+        estimator.update(1.0, 0);
+        estimator.update(2.0, 250);
+        assert_eq!(estimator.slope_per_s(), None);
+        FakeClock::new().run(16, 3_000, |now| estimator.update(now as f32, now));
+        estimator.reset();
+        assert_eq!(estimator.slope_per_s(), None);
+    }
+
+    #[test]
+    fn arrow_threshold_is_per_time_base() {
+        let egt = TrendSpec::new(4_000, RateUnit::PerSecond, 10.0);
+        assert_eq!(egt.direction(12.0), Some(true));
+        assert_eq!(egt.direction(-5.0), None);
+        assert_eq!(OIL.direction(-0.05), Some(false), "3 per minute");
+        assert_eq!(OIL.direction(0.01), None);
+    }
+
+    #[test]
+    fn predicts_time_to_the_flagged_band() {
+        let edges = [
+            Threshold::new(12.0, 0.1, 0),
+            Threshold::new(90.0, 1.0, 0),
+            Threshold::new(110.0, 1.0, 0),
+        ];
+        let hot = |v: f32| v > 110.0;
+        assert_eq!(time_to_flag(100.0, 0.25, &edges, hot), Some(40.0));
+        assert_eq!(time_to_flag(100.0, -0.25, &edges, hot), None, "moving away");
+        assert_eq!(time_to_flag(100.0, 0.05, &edges, hot), None, "beyond the horizon");
+        assert_eq!(time_to_flag(111.0, 1.0, &edges, hot), None, "already there");
+
+        assert_eq!(round_eta_s(7.8), 7);
+        assert_eq!(round_eta_s(38.0), 40);
+        assert_eq!(round_eta_s(42.0), 40);
+
+        let flat = |v: f32| v < 12.0;
+        assert_eq!(time_to_flag(12.5, -0.015_625, &edges, flat), Some(32.0));
+    }
+}
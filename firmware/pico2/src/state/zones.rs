@@ -0,0 +1,173 @@
+//! Time spent in each threshold band of a sensor, and how often the reading entered it.
+//!
+//! A sensor's band edges split its range into `thresholds.len() + 1` zones, lowest
+//! first. `ZoneTracker` classifies every reading with the edges' hysteresis and
+//! dwell, so a value hovering at an edge neither splits its time between two zones
+//! nor counts as a stream of excursions.
+
+use crate::classifier::{BandClassifier, Threshold};
+
+/// Most zones a sensor can have, i.e. one more than its most band edges.
+pub const MAX_BANDS: usize = 5;
+
+/// Zone of `value`: the number of band edges it lies above.
+pub fn band_index(
+    thresholds: &[Threshold],
+    value: f32,
+) -> usize {
+    thresholds.iter().take_while(|edge| value > edge.value).count()
+}
+
+/// Accumulated time and entries per zone, lowest zone first.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ZoneStats {
+    pub time_ms: [u32; MAX_BANDS],
+    /// Times the reading entered the zone from another one.
+    pub excursions: [u16; MAX_BANDS],
+}
+
+impl ZoneStats {
+    pub const fn new() -> Self {
+        Self {
+            time_ms: [0; MAX_BANDS],
+            excursions: [0; MAX_BANDS],
+        }
+    }
+
+    /// Time covered by all zones together.
+    pub fn total_ms(&self) -> u32 { self.time_ms.iter().fold(0, |sum, &t| sum.saturating_add(t)) }
+
+    /// Fraction of the total time spent in `band`, 0 before any time is in.
+    pub fn share(
+        &self,
+        band: usize,
+    ) -> f32 {
+        match self.total_ms() {
+            0 => 0.0,
+            total => self.time_ms[band] as f32 / total as f32,
+        }
+    }
+}
+
+/// Running `ZoneStats` of one sensor.
+#[derive(Clone, Copy, Debug)]
+pub struct ZoneTracker {
+    stats: ZoneStats,
+    band: BandClassifier<usize>,
+    /// Zone of the last update and when it was.
+    last: Option<(usize, u64)>,
+}
+
+impl ZoneTracker {
+    pub const fn new() -> Self {
+        Self {
+            stats: ZoneStats::new(),
+            band: BandClassifier::new(),
+            last: None,
+        }
+    }
+
+    /// Credit the time since the last update to the zone the reading was in, then
+    /// classify `value`. Entering a different zone counts as an excursion into it.
+    pub fn update(
+        &mut self,
+        value: f32,
+        now_ms: u64,
+        thresholds: &[Threshold],
+    ) {
+        let band = self
+            .band
+            .classify(value, now_ms, thresholds, |v| band_index(thresholds, v))
+            .min(MAX_BANDS - 1);
+        if let Some((previous, since)) = self.last {
+            let elapsed = u32::try_from(now_ms.saturating_sub(since)).unwrap_or(u32::MAX);
+            self.stats.time_ms[previous] = self.stats.time_ms[previous].saturating_add(elapsed);
+            if band != previous {
+                self.stats.excursions[band] = self.stats.excursions[band].saturating_add(1);
+            }
+        }
+        self.last = Some((band, now_ms));
+    }
+
+    pub const fn stats(&self) -> &ZoneStats { &self.stats }
+
+    /// Zone of the last reading, if there was one since the last reset.
+    pub fn current(&self) -> Option<usize> { self.last.map(|(band, _)| band) }
+
+    /// Stop crediting time until the next update, e.g. while the reading is faulty.
+    /// The zone the reading is in when it resumes is not an excursion.
+    pub const fn pause(&mut self) { self.last = None; }
+
+    /// Start over; the zone the reading is in when counting resumes is not an excursion.
+    pub const fn reset(&mut self) {
+        self.stats = ZoneStats::new();
+        self.last = None;
+    }
+}
+
+impl Default for ZoneTracker {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_clock::FakeClock;
+
+    const EDGES: &[Threshold] = &[Threshold::new(90.0, 1.0, 0), Threshold::new(100.0, 1.0, 0)];
+
+    #[test]
+    fn band_index_counts_edges_below() {
+        assert_eq!(band_index(EDGES, 80.0), 0);
+        assert_eq!(band_index(EDGES, 90.0), 0);
+        assert_eq!(band_index(EDGES, 95.0), 1);
+        assert_eq!(band_index(EDGES, 120.0), 2);
+        assert_eq!(band_index(&[], 120.0), 0);
+    }
+
+    #[test]
+    fn time_is_credited_per_zone_at_any_frame_rate() {
+        for fps in [20, 50, 100] {
+            let mut tracker = ZoneTracker::new();
+            let mut clock = FakeClock::new();
+            clock.run(1_000 / fps, 10_000, |now| tracker.update(80.0, now, EDGES));
+            clock.run(1_000 / fps, 5_000, |now| tracker.update(105.0, now, EDGES));
+            clock.run(1_000 / fps, 1_000, |now| tracker.update(95.0, now, EDGES));
+            tracker.update(95.0, clock.now_ms(), EDGES);
+
+            let stats = tracker.stats();
+            assert_eq!(stats.time_ms, [10_000, 1_000, 5_000, 0, 0], "{fps} fps");
+            assert_eq!(stats.total_ms(), 16_000);
+            assert!((stats.share(2) - 0.3125).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn excursions_count_entries_not_frames() {
+        let mut tracker = ZoneTracker::new();
+        let mut clock = FakeClock::new();
+        clock.run(20, 1_000, |now| tracker.update(95.0, now, EDGES));
+        for _ in 0..3 {
+            clock.run(20, 1_000, |now| tracker.update(105.0, now, EDGES));
+            clock.run(20, 1_000, |now| tracker.update(95.0, now, EDGES));
+        }
+        // Hovering at the edge stays inside the hysteresis
+        for value in [100.5, 99.5, 100.8, 99.2] {
+            clock.run(20, 200, |now| tracker.update(value, now, EDGES));
+        }
+        assert_eq!(tracker.stats().excursions, [0, 3, 3, 0, 0]);
+    }
+
+    #[test]
+    fn reset_starts_counting_afresh() {
+        let mut tracker = ZoneTracker::new();
+        let mut clock = FakeClock::new();
+        clock.run(20, 2_000, |now| tracker.update(105.0, now, EDGES));
+        tracker.reset();
+        assert_eq!(tracker.current(), None);
+        clock.run(20, 1_000, |now| tracker.update(105.0, now, EDGES));
+        tracker.update(105.0, clock.now_ms(), EDGES);
+        assert_eq!(tracker.stats().time_ms[2], 1_000);
+        assert_eq!(tracker.stats().excursions, [0; MAX_BANDS]);
+    }
+}
@@ -5,8 +5,12 @@ use embassy_sync::watch::Watch;
 use embassy_time::{Duration, Instant, Timer};
 
 use crate::log_info;
+use crate::tasks::watchdog::{self, SupervisedTask};
 use crate::tasks::{CORE1_STACK_USED_KB, CORE1_UTIL_PERCENT, core1_stack_hwm_bytes};
 
+/// VIN the demo source reports, standing in for the one read from the engine controller.
+pub const DEMO_VIN: &str = "VSSZZZ5FZKR000000";
+
 #[derive(Clone, Copy, Default)]
 pub struct DemoSensorValues {
     pub boost: f32,
@@ -16,7 +20,10 @@ pub struct DemoSensorValues {
     pub iat_temp: f32,
     pub egt_temp: f32,
     pub batt_voltage: f32,
-    pub afr: f32,
+    pub lambda: f32,
+    /// Lambda the ECU is commanding, if it reports one.
+    pub lambda_target: Option<f32>,
+    pub rpm: f32,
 }
 
 pub static DEMO_VALUES: Watch<CriticalSectionRawMutex, DemoSensorValues, 2> = Watch::new();
@@ -28,6 +35,7 @@ pub async fn demo_values_task(
 ) {
     let sender = DEMO_VALUES.dyn_sender();
     log_info!("Demo task started");
+    watchdog::register(SupervisedTask::Core1Sensors);
 
     let mut last_util_calc = Instant::now();
     let mut total_work_cycles = 0u32;
@@ -38,18 +46,23 @@ pub async fn demo_values_task(
         let elapsed_ms = start_time.elapsed().as_millis() as u32;
         let t = elapsed_ms as f32 / 1000.0;
 
+        let boost = (0.3 + 2.2 * micromath::F32(t * 0.5).sin().0.abs()).min(2.0);
         let values = DemoSensorValues {
-            boost: (0.3 + 2.2 * micromath::F32(t * 0.5).sin().0.abs()).min(2.0),
-            oil_temp: 60.0 + 55.0 * micromath::F32(t * 0.3).sin().0,
+            boost,
+            oil_temp: 85.0 + 30.0 * micromath::F32(t * 0.3).sin().0,
             water_temp: 88.0 + 7.0 * micromath::F32(t * 0.4).sin().0,
             dsg_temp: 75.0 + 40.0 * micromath::F32(t * 0.35).sin().0,
             iat_temp: 30.0 + 40.0 * micromath::F32(t * 0.25).sin().0,
             egt_temp: 200.0 + 1000.0 * micromath::F32(t * 0.04).sin().0.abs(),
             batt_voltage: 12.0 + 2.5 * micromath::F32(t * 0.15).sin().0,
-            afr: 14.0 + 4.0 * micromath::F32(t * 0.45).sin().0,
+            lambda: 0.95 + 0.27 * micromath::F32(t * 0.45).sin().0,
+            // Enrichment under boost, stoichiometric otherwise
+            lambda_target: Some(if boost > 1.2 { 0.82 } else { 1.0 }),
+            rpm: 850.0 + 5900.0 * micromath::F32(t * 0.5).sin().0.abs(),
         };
 
         sender.send(values);
+        watchdog::heartbeat(SupervisedTask::Core1Sensors);
 
         let work_end = crate::profiling::read();
         total_work_cycles = total_work_cycles.wrapping_add(crate::profiling::elapsed(work_start, work_end));
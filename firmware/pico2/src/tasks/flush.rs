@@ -5,7 +5,9 @@ use embassy_sync::signal::Signal;
 use embassy_time::Instant;
 
 use crate::drivers::St7789Flusher;
+use crate::fault::FAULT_ACTIVE;
 use crate::log_info;
+use crate::tasks::watchdog::{self, SupervisedTask};
 
 pub static FLUSH_SIGNAL: Signal<CriticalSectionRawMutex, usize> = Signal::new();
 
@@ -22,9 +24,14 @@ pub static LAST_FLUSH_TIME_US: AtomicU32 = AtomicU32::new(0);
 #[embassy_executor::task]
 pub async fn display_flush_task(flusher: &'static mut St7789Flusher<'static>) {
     log_info!("Flush task started");
+    watchdog::register(SupervisedTask::Flush);
 
     loop {
         let buffer_idx = FLUSH_SIGNAL.wait().await;
+        // The fault handler owns the panel now; starting DMA would overwrite its screen
+        if FAULT_ACTIVE.load(Ordering::Acquire) {
+            continue;
+        }
         FLUSH_BUFFER_IDX.store(buffer_idx, Ordering::Relaxed);
 
         let flush_start = Instant::now();
@@ -42,5 +49,6 @@ pub async fn display_flush_task(flusher: &'static mut St7789Flusher<'static>) {
         LAST_FLUSH_TIME_US.store(flush_start.elapsed().as_micros() as u32, Ordering::Relaxed);
 
         FLUSH_DONE.signal(());
+        watchdog::heartbeat(SupervisedTask::Flush);
     }
 }
@@ -5,8 +5,9 @@ use static_cell::StaticCell;
 
 pub mod demo;
 pub mod flush;
+pub mod watchdog;
 
-pub use demo::{DEMO_VALUES, demo_values_task};
+pub use demo::{DEMO_VALUES, DEMO_VIN, demo_values_task};
 pub use flush::{
     BUFFER_SWAPS,
     BUFFER_WAITS,
@@ -16,6 +17,7 @@ pub use flush::{
     LAST_FLUSH_TIME_US,
     display_flush_task,
 };
+pub use watchdog::{SupervisedTask, watchdog_task};
 
 pub static EXECUTOR_CORE1: StaticCell<Executor> = StaticCell::new();
 // Stack<N> takes N in bytes. CORE1_STACK_WORDS * 4 = 32768 bytes = 32 KB.
@@ -0,0 +1,174 @@
+//! Hardware watchdog supervisor with per-task liveness heartbeats.
+//!
+//! Each supervised task calls [`register`] once it enters its steady-state loop and
+//! [`heartbeat`] on every iteration. The supervisor feeds the RP2350 watchdog only
+//! when every registered task has checked in since the previous check, so a stall in
+//! any one of them (or in the Core 0 executor itself) ends in a watchdog reset.
+//!
+//! The suspected culprit is kept in watchdog scratch register 0, which survives the
+//! reset, and is reported by [`log_reset_reason`] on the next boot.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_rp::watchdog::{ResetReason, Watchdog};
+use embassy_time::{Duration, Timer};
+
+use crate::fault::FAULT_ACTIVE;
+use crate::log_info;
+
+/// Hardware timeout. Must comfortably exceed [`CHECK_INTERVAL`] so one late check does not reset.
+const WATCHDOG_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// How often heartbeats are collected. Every supervised task must check in at least this often.
+const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+const WATCHDOG_BASE: u32 = 0x400D_8000;
+const WATCHDOG_CTRL: *const u32 = WATCHDOG_BASE as *const u32;
+const WATCHDOG_LOAD: *mut u32 = (WATCHDOG_BASE + 0x04) as *mut u32;
+const WATCHDOG_SCRATCH0: *mut u32 = (WATCHDOG_BASE + 0x0C) as *mut u32;
+const WATCHDOG_CTRL_ENABLE: u32 = 1 << 30;
+
+/// Largest LOAD value (24-bit counter, 1 µs ticks ≈ 16.7 s).
+const WATCHDOG_LOAD_MAX: u32 = 0x00FF_FFFF;
+
+/// Upper half of scratch 0 marks the lower half as a valid culprit code.
+const CULPRIT_MAGIC: u32 = 0xB0A7_0000;
+const CULPRIT_MASK: u32 = 0x0000_FFFF;
+
+/// Culprit code written on every feed: if the supervisor itself stops running,
+/// nothing overwrites it and the reset is blamed on the Core 0 executor.
+pub const CULPRIT_EXECUTOR: u32 = 0x00FE;
+pub const CULPRIT_PANIC: u32 = 0x00F0;
+pub const CULPRIT_HARDFAULT: u32 = 0x00F1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum SupervisedTask {
+    MainLoop = 0,
+    Flush = 1,
+    Encoder = 2,
+    Core1Sensors = 3,
+}
+
+impl SupervisedTask {
+    pub const ALL: [Self; 4] = [Self::MainLoop, Self::Flush, Self::Encoder, Self::Core1Sensors];
+
+    #[inline]
+    const fn bit(self) -> u32 { 1 << self as u32 }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::MainLoop => "main loop",
+            Self::Flush => "flush task",
+            Self::Encoder => "encoder task",
+            Self::Core1Sensors => "core1 sensors",
+        }
+    }
+}
+
+/// Tasks that checked in since the supervisor last looked (bit per [`SupervisedTask`]).
+static HEARTBEATS: AtomicU32 = AtomicU32::new(0);
+
+/// Tasks that are currently expected to check in.
+static SUPERVISED: AtomicU32 = AtomicU32::new(0);
+
+/// Start supervising `task`. Call once it enters its steady-state loop,
+/// after any slow init, so startup is not mistaken for a stall.
+pub fn register(task: SupervisedTask) {
+    HEARTBEATS.fetch_or(task.bit(), Ordering::Relaxed);
+    SUPERVISED.fetch_or(task.bit(), Ordering::Relaxed);
+}
+
+#[inline]
+pub fn heartbeat(task: SupervisedTask) { HEARTBEATS.fetch_or(task.bit(), Ordering::Relaxed); }
+
+/// Report why the previous run ended if it was a watchdog reset, then clear the culprit.
+pub fn log_reset_reason(watchdog: &mut Watchdog) {
+    let scratch = watchdog.get_scratch(0);
+    watchdog.set_scratch(0, 0);
+
+    let Some(reason) = watchdog.reset_reason() else {
+        return;
+    };
+
+    let culprit = if scratch & !CULPRIT_MASK == CULPRIT_MAGIC {
+        culprit_name(scratch & CULPRIT_MASK)
+    } else {
+        "unknown"
+    };
+
+    match reason {
+        ResetReason::TimedOut => log_info!("WDT reset: {}", culprit),
+        ResetReason::Forced => log_info!("Forced reset: {}", culprit),
+    }
+}
+
+fn culprit_name(code: u32) -> &'static str {
+    match code {
+        CULPRIT_EXECUTOR => "core0 executor",
+        CULPRIT_PANIC => "panic",
+        CULPRIT_HARDFAULT => "hardfault",
+        _ => {
+            SupervisedTask::ALL
+                .iter()
+                .find(|task| **task as u32 == code)
+                .map_or("unknown", |task| task.name())
+        }
+    }
+}
+
+/// Called from the fault handlers: record the culprit and stretch the running
+/// watchdog to its maximum period so the fault screen stays readable before the reset.
+///
+/// Returns `true` if the watchdog is running, i.e. a reset will follow.
+pub fn hold_for_fault(culprit: u32) -> bool {
+    unsafe {
+        use core::ptr::{read_volatile, write_volatile};
+
+        write_volatile(WATCHDOG_SCRATCH0, CULPRIT_MAGIC | culprit);
+        if read_volatile(WATCHDOG_CTRL) & WATCHDOG_CTRL_ENABLE == 0 {
+            return false;
+        }
+        write_volatile(WATCHDOG_LOAD, WATCHDOG_LOAD_MAX);
+    }
+    true
+}
+
+/// Supervisor task: starts the watchdog and feeds it only while every registered task is alive.
+#[embassy_executor::task]
+pub async fn watchdog_task(mut watchdog: Watchdog) {
+    watchdog.set_scratch(0, CULPRIT_MAGIC | CULPRIT_EXECUTOR);
+    watchdog.start(WATCHDOG_TIMEOUT);
+    log_info!("Watchdog started");
+
+    let mut reported_stall = 0u32;
+
+    loop {
+        Timer::after(CHECK_INTERVAL).await;
+
+        // A fault handler has taken over the watchdog and the culprit register
+        if FAULT_ACTIVE.load(Ordering::Acquire) {
+            return;
+        }
+
+        let alive = HEARTBEATS.swap(0, Ordering::Relaxed);
+        let missing = SUPERVISED.load(Ordering::Relaxed) & !alive;
+
+        if missing == 0 {
+            watchdog.set_scratch(0, CULPRIT_MAGIC | CULPRIT_EXECUTOR);
+            watchdog.feed();
+            reported_stall = 0;
+            continue;
+        }
+
+        // Not feeding: if the task does not recover before the timeout, the board resets
+        // with the first missing task recorded as the culprit.
+        if let Some(task) = SupervisedTask::ALL.iter().find(|task| missing & task.bit() != 0) {
+            watchdog.set_scratch(0, CULPRIT_MAGIC | *task as u32);
+            if reported_stall & task.bit() == 0 {
+                log_info!("Stall: {}", task.name());
+            }
+        }
+        reported_stall = missing;
+    }
+}
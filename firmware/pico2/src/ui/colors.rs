@@ -10,6 +10,8 @@ pub const GREEN: Rgb565 = Rgb565::GREEN;
 
 pub const BLUE: Rgb565 = Rgb565::BLUE;
 
+pub const CYAN: Rgb565 = Rgb565::CYAN;
+
 pub const YELLOW: Rgb565 = Rgb565::YELLOW;
 
 pub const PINK: Rgb565 = Rgb565::MAGENTA;
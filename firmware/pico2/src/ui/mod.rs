@@ -1,20 +1,24 @@
 mod animations;
 mod colors;
 mod styles;
+pub mod theme {
+    pub use dashboard_pico2::theme::*;
+}
 
 pub use animations::ColorTransition;
-pub use colors::{BLACK, BLUE, DARK_TEAL, GRAY, GREEN, ORANGE, PINK, RED, WHITE, YELLOW};
+pub use colors::{BLACK, RED, WHITE, YELLOW};
 pub use styles::{
     CENTERED,
     LABEL_FONT,
-    LABEL_STYLE_BLACK,
-    LABEL_STYLE_ORANGE,
     LABEL_STYLE_WHITE,
     LEFT_ALIGNED,
     RIGHT_ALIGNED,
+    TITLE_FONT,
     TITLE_STYLE_WHITE,
     VALUE_FONT,
     VALUE_FONT_MEDIUM,
-    VALUE_STYLE_BLACK,
-    VALUE_STYLE_WHITE,
+    caption_style,
+    label_style,
+    title_style,
 };
+pub use theme::Status;
@@ -4,7 +4,8 @@ use embedded_graphics::pixelcolor::Rgb565;
 use embedded_graphics::text::{Alignment, TextStyle, TextStyleBuilder};
 use profont::{PROFONT_18_POINT, PROFONT_24_POINT};
 
-use super::colors::{BLACK, ORANGE, WHITE};
+use super::colors::WHITE;
+use super::theme;
 
 pub const CENTERED: TextStyle = TextStyleBuilder::new().alignment(Alignment::Center).build();
 
@@ -16,16 +17,23 @@ pub const LABEL_FONT: &MonoFont = &FONT_6X10;
 
 pub const VALUE_FONT: &MonoFont = &PROFONT_24_POINT;
 
+/// Fixed-colour styles for the screens that do not follow the theme (boot, fault).
 pub const LABEL_STYLE_WHITE: MonoTextStyle<'static, Rgb565> = MonoTextStyle::new(&FONT_6X10, WHITE);
 
-pub const LABEL_STYLE_BLACK: MonoTextStyle<'static, Rgb565> = MonoTextStyle::new(&FONT_6X10, BLACK);
+pub const TITLE_STYLE_WHITE: MonoTextStyle<'static, Rgb565> = MonoTextStyle::new(&FONT_10X20, WHITE);
 
-pub const LABEL_STYLE_ORANGE: MonoTextStyle<'static, Rgb565> = MonoTextStyle::new(&FONT_6X10, ORANGE);
+pub const VALUE_FONT_MEDIUM: &MonoFont = &PROFONT_18_POINT;
 
-pub const TITLE_STYLE_WHITE: MonoTextStyle<'static, Rgb565> = MonoTextStyle::new(&FONT_10X20, WHITE);
+pub const TITLE_FONT: &MonoFont = &FONT_10X20;
 
-pub const VALUE_STYLE_WHITE: MonoTextStyle<'static, Rgb565> = MonoTextStyle::new(&PROFONT_24_POINT, WHITE);
+/// Small text in the active theme's text colour.
+#[inline]
+pub fn label_style() -> MonoTextStyle<'static, Rgb565> { MonoTextStyle::new(LABEL_FONT, theme::active().text) }
 
-pub const VALUE_STYLE_BLACK: MonoTextStyle<'static, Rgb565> = MonoTextStyle::new(&PROFONT_24_POINT, BLACK);
+/// Small text in the active theme's label colour, for captions and hints.
+#[inline]
+pub fn caption_style() -> MonoTextStyle<'static, Rgb565> { MonoTextStyle::new(LABEL_FONT, theme::active().label) }
 
-pub const VALUE_FONT_MEDIUM: &MonoFont = &PROFONT_18_POINT;
+/// Title text in the active theme's text colour.
+#[inline]
+pub fn title_style() -> MonoTextStyle<'static, Rgb565> { MonoTextStyle::new(TITLE_FONT, theme::active().text) }
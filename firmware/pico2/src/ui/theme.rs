@@ -0,0 +1,421 @@
+//! Colour themes.
+//!
+//! Widgets never use raw colours: they ask the active `Theme` for a semantic
+//! role (text, label, highlight, ...) or for the colour of a reading's
+//! `Status`. The active theme is global because nearly every draw call needs
+//! it and it changes at most once per frame, from the render loop.
+//!
+//! The boot, welcome and fault screens keep their fixed colours: they run
+//! before settings are loaded or after the firmware has stopped.
+//!
+//! Colour is never the only signal: every status that needs attention also has
+//! a `Cue`, a border style and icon that read the same without colour vision.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::IntoStorage;
+
+use super::colors::{BLACK, BLUE, CYAN, DARK_TEAL, GRAY, GREEN, ORANGE, PINK, RED, WHITE, YELLOW};
+
+/// Band a reading falls into, from a sensor's status function. Ordered from cold to critical.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    /// Below operating range.
+    Cold,
+    /// Slightly below the ideal range (rich mixture).
+    Cool,
+    /// Unremarkable; drawn on the plain background.
+    Neutral,
+    /// In the ideal range.
+    Normal,
+    /// Above ideal but harmless.
+    Elevated,
+    Warning,
+    Critical,
+}
+
+impl Status {
+    pub const COUNT: usize = 7;
+
+    /// Non-colour marking of this status. In-range readings carry none.
+    pub const fn cue(self) -> Cue {
+        match self {
+            Self::Cold => Cue::new(Border::Dotted, Icon::Low),
+            Self::Cool => Cue::new(Border::None, Icon::Low),
+            Self::Neutral | Self::Normal => Cue::new(Border::None, Icon::None),
+            Self::Elevated => Cue::new(Border::None, Icon::High),
+            Self::Warning => Cue::new(Border::Dashed, Icon::Warning),
+            Self::Critical => Cue::new(Border::Double, Icon::Critical),
+        }
+    }
+}
+
+/// Frame drawn just inside a cell's edge.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Border {
+    None,
+    Dotted,
+    Dashed,
+    /// Two solid lines.
+    Double,
+}
+
+/// Small symbol in a cell's top-right corner.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Icon {
+    None,
+    /// Hollow triangle pointing down: below the ideal range.
+    Low,
+    /// Hollow triangle pointing up: above the ideal range.
+    High,
+    /// Filled triangle.
+    Warning,
+    /// Cross.
+    Critical,
+}
+
+/// Redundant, shape-only marking of a `Status`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cue {
+    pub border: Border,
+    pub icon: Icon,
+}
+
+impl Cue {
+    const fn new(
+        border: Border,
+        icon: Icon,
+    ) -> Self {
+        Self { border, icon }
+    }
+
+    pub const fn is_none(self) -> bool { matches!(self.border, Border::None) && matches!(self.icon, Icon::None) }
+}
+
+/// Palette a theme is built from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Palette {
+    Day,
+    /// Dimmed, low-blue colours for driving in the dark.
+    Night,
+    /// Pure colours and white secondary text for direct sunlight.
+    HighContrast,
+    /// Okabe-Ito colours that stay distinct with deuteranopia.
+    ColorSafe,
+}
+
+impl Palette {
+    pub const COUNT: usize = 4;
+
+    pub const fn from_index(index: u8) -> Self {
+        match index {
+            1 => Self::Night,
+            2 => Self::HighContrast,
+            3 => Self::ColorSafe,
+            _ => Self::Day,
+        }
+    }
+}
+
+/// Theme setting: a fixed palette, or day/night following the backlight.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ThemeMode {
+    Day,
+    Night,
+    HighContrast,
+    /// Night while the backlight is turned down, day otherwise. The board has no
+    /// light sensor or clock, so the driver's brightness choice stands in for both.
+    Auto,
+    ColorSafe,
+}
+
+/// Backlight level at or below which auto mode switches to night.
+pub const AUTO_NIGHT_BELOW: u32 = 30;
+
+/// Backlight level at or above which auto mode switches back to day; in between it keeps the current palette.
+pub const AUTO_DAY_ABOVE: u32 = 45;
+
+const _: () = assert!(AUTO_NIGHT_BELOW < AUTO_DAY_ABOVE);
+
+impl ThemeMode {
+    pub const COUNT: usize = 5;
+
+    pub const fn from_index(index: u8) -> Self {
+        match index {
+            1 => Self::Night,
+            2 => Self::HighContrast,
+            3 => Self::Auto,
+            4 => Self::ColorSafe,
+            _ => Self::Day,
+        }
+    }
+
+    /// Palette to show given the backlight level and the palette currently shown.
+    pub const fn resolve(
+        self,
+        backlight_percent: u32,
+        current: Palette,
+    ) -> Palette {
+        match self {
+            Self::Day => Palette::Day,
+            Self::Night => Palette::Night,
+            Self::HighContrast => Palette::HighContrast,
+            Self::ColorSafe => Palette::ColorSafe,
+            Self::Auto => {
+                if backlight_percent <= AUTO_NIGHT_BELOW {
+                    Palette::Night
+                } else if backlight_percent >= AUTO_DAY_ABOVE {
+                    Palette::Day
+                } else if matches!(current, Palette::Night) {
+                    Palette::Night
+                } else {
+                    Palette::Day
+                }
+            }
+        }
+    }
+}
+
+/// Colours for every semantic role.
+pub struct Theme {
+    /// Page background and neutral cells.
+    pub background: Rgb565,
+    /// Values and primary text.
+    pub text: Rgb565,
+    /// Captions, axes, dividers and hints.
+    pub label: Rgb565,
+    /// Peak hold, selection frame and values being edited.
+    pub highlight: Rgb565,
+    /// Secondary readouts such as min/max/avg.
+    pub accent: Rgb565,
+    /// Selected row in lists.
+    pub selection: Rgb565,
+    /// Header bar and alert popups.
+    pub header: Rgb565,
+    /// Playful accents (boost easter egg).
+    pub special: Rgb565,
+    /// Overlay graph trace colours.
+    pub traces: [Rgb565; 4],
+    /// Indexed by `Status`.
+    status: [Rgb565; Status::COUNT],
+}
+
+impl Theme {
+    /// Background colour of a reading in `status`.
+    #[inline]
+    pub const fn status(
+        &self,
+        status: Status,
+    ) -> Rgb565 {
+        self.status[status as usize]
+    }
+
+    /// Foreground that stays readable on `bg`: text or background colour, whichever contrasts more.
+    #[inline]
+    pub fn text_on(
+        &self,
+        bg: Rgb565,
+    ) -> Rgb565 {
+        let bg_luma = luminance(bg);
+        if luminance(self.text).abs_diff(bg_luma) >= luminance(self.background).abs_diff(bg_luma) {
+            self.text
+        } else {
+            self.background
+        }
+    }
+
+    /// Colour to draw a reading with on the plain background: its status colour,
+    /// or text colour when the status has none.
+    #[inline]
+    pub fn reading(
+        &self,
+        status: Status,
+    ) -> Rgb565 {
+        let color = self.status(status);
+        if color == self.background { self.text } else { color }
+    }
+}
+
+pub const DAY: Theme = Theme {
+    background: BLACK,
+    text: WHITE,
+    label: GRAY,
+    highlight: YELLOW,
+    accent: ORANGE,
+    selection: DARK_TEAL,
+    header: RED,
+    special: PINK,
+    traces: [CYAN, ORANGE, PINK, GREEN],
+    status: [BLUE, DARK_TEAL, BLACK, GREEN, YELLOW, ORANGE, RED],
+};
+
+pub const NIGHT: Theme = Theme {
+    background: BLACK,
+    text: Rgb565::new(20, 36, 10),
+    label: Rgb565::new(10, 18, 4),
+    highlight: Rgb565::new(22, 34, 0),
+    accent: Rgb565::new(20, 22, 0),
+    selection: Rgb565::new(6, 10, 2),
+    header: Rgb565::new(12, 0, 0),
+    special: Rgb565::new(18, 0, 10),
+    traces: [
+        Rgb565::new(0, 28, 10),
+        Rgb565::new(20, 22, 0),
+        Rgb565::new(18, 0, 10),
+        Rgb565::new(0, 30, 0),
+    ],
+    status: [
+        Rgb565::new(0, 10, 10),
+        Rgb565::new(0, 12, 5),
+        BLACK,
+        Rgb565::new(0, 20, 0),
+        Rgb565::new(16, 28, 0),
+        Rgb565::new(18, 14, 0),
+        Rgb565::new(18, 0, 0),
+    ],
+};
+
+pub const HIGH_CONTRAST: Theme = Theme {
+    background: BLACK,
+    text: WHITE,
+    label: WHITE,
+    highlight: YELLOW,
+    accent: YELLOW,
+    selection: BLUE,
+    header: RED,
+    special: PINK,
+    traces: [CYAN, YELLOW, PINK, GREEN],
+    status: [BLUE, BLUE, BLACK, GREEN, YELLOW, ORANGE, RED],
+};
+
+// Okabe-Ito: blue, sky blue, bluish green, yellow, orange, vermillion, reddish purple.
+// Severity also steps in luminance, so neighbouring bands differ without hue.
+const OI_BLUE: Rgb565 = Rgb565::new(0, 28, 22);
+const OI_SKY_BLUE: Rgb565 = Rgb565::new(10, 45, 29);
+const OI_BLUISH_GREEN: Rgb565 = Rgb565::new(0, 39, 14);
+const OI_YELLOW: Rgb565 = Rgb565::new(30, 57, 8);
+const OI_ORANGE: Rgb565 = Rgb565::new(28, 39, 0);
+const OI_VERMILLION: Rgb565 = Rgb565::new(26, 23, 0);
+const OI_REDDISH_PURPLE: Rgb565 = Rgb565::new(25, 30, 20);
+
+pub const COLOR_SAFE: Theme = Theme {
+    background: BLACK,
+    text: WHITE,
+    label: GRAY,
+    highlight: OI_YELLOW,
+    accent: OI_SKY_BLUE,
+    selection: OI_BLUE,
+    header: OI_VERMILLION,
+    special: OI_REDDISH_PURPLE,
+    traces: [OI_SKY_BLUE, OI_ORANGE, OI_REDDISH_PURPLE, OI_YELLOW],
+    status: [
+        OI_BLUE,
+        OI_SKY_BLUE,
+        BLACK,
+        OI_BLUISH_GREEN,
+        OI_YELLOW,
+        OI_ORANGE,
+        OI_VERMILLION,
+    ],
+};
+
+static ACTIVE: AtomicU8 = AtomicU8::new(Palette::Day as u8);
+
+/// Theme widgets draw with.
+#[inline]
+pub fn active() -> &'static Theme { theme(active_palette()) }
+
+#[inline]
+pub fn active_palette() -> Palette { Palette::from_index(ACTIVE.load(Ordering::Relaxed)) }
+
+/// Switch the theme; returns whether it changed, so the caller can redraw everything.
+pub fn set_active(palette: Palette) -> bool { ACTIVE.swap(palette as u8, Ordering::Relaxed) != palette as u8 }
+
+pub const fn theme(palette: Palette) -> &'static Theme {
+    match palette {
+        Palette::Day => &DAY,
+        Palette::Night => &NIGHT,
+        Palette::HighContrast => &HIGH_CONTRAST,
+        Palette::ColorSafe => &COLOR_SAFE,
+    }
+}
+
+/// Perceived brightness, 0-255.
+pub fn luminance(color: Rgb565) -> u32 {
+    let raw = color.into_storage();
+    let r5 = u32::from((raw >> 11) & 0x1F);
+    let g6 = u32::from((raw >> 5) & 0x3F);
+    let b5 = u32::from(raw & 0x1F);
+
+    let r8 = (r5 << 3) | (r5 >> 2);
+    let g8 = (g6 << 2) | (g6 >> 4);
+    let b8 = (b5 << 3) | (b5 >> 2);
+
+    (r8 * 77 + g8 * 150 + b8 * 29) >> 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_mode_keeps_current_palette_between_thresholds() {
+        let mode = ThemeMode::Auto;
+        assert_eq!(mode.resolve(100, Palette::Day), Palette::Day);
+        assert_eq!(mode.resolve(AUTO_NIGHT_BELOW, Palette::Day), Palette::Night);
+        assert_eq!(mode.resolve(AUTO_NIGHT_BELOW + 5, Palette::Night), Palette::Night);
+        assert_eq!(mode.resolve(AUTO_NIGHT_BELOW + 5, Palette::Day), Palette::Day);
+        assert_eq!(mode.resolve(AUTO_DAY_ABOVE, Palette::Night), Palette::Day);
+    }
+
+    #[test]
+    fn fixed_modes_ignore_backlight() {
+        assert_eq!(ThemeMode::Night.resolve(100, Palette::Day), Palette::Night);
+        assert_eq!(ThemeMode::Day.resolve(5, Palette::Night), Palette::Day);
+        assert_eq!(
+            ThemeMode::HighContrast.resolve(5, Palette::Night),
+            Palette::HighContrast
+        );
+    }
+
+    #[test]
+    fn text_stays_readable_on_every_status_colour() {
+        for theme in [&DAY, &NIGHT, &HIGH_CONTRAST, &COLOR_SAFE] {
+            for bg in theme.status {
+                let fg = theme.text_on(bg);
+                assert!(luminance(fg).abs_diff(luminance(bg)) >= 60, "{bg:?} / {fg:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_attention_status_has_its_own_cue() {
+        let all = [
+            Status::Cold,
+            Status::Cool,
+            Status::Neutral,
+            Status::Normal,
+            Status::Elevated,
+            Status::Warning,
+            Status::Critical,
+        ];
+        for (i, a) in all.iter().enumerate() {
+            let in_range = matches!(a, Status::Neutral | Status::Normal);
+            assert_eq!(a.cue().is_none(), in_range, "{a:?}");
+            for b in &all[i + 1..] {
+                if !in_range {
+                    assert_ne!(a.cue(), b.cue(), "{a:?} / {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn color_safe_severities_step_in_brightness() {
+        // Warning and critical must differ in lightness, not only in red-green hue
+        let status = |s| luminance(COLOR_SAFE.status(s));
+        assert!(status(Status::Elevated) > status(Status::Warning));
+        assert!(status(Status::Warning) > status(Status::Critical));
+    }
+}
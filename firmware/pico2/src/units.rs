@@ -0,0 +1,340 @@
+//! Display units.
+//!
+//! Sensor values, thresholds and history are always kept in base units
+//! (°C, bar relative, lambda, volts, rpm). `UnitPrefs` converts them to the
+//! user's preferred unit and formats them at draw time only.
+
+use core::fmt::{self, Write};
+
+use crate::config::dashboard::DEFAULT_UNITS;
+
+const PSI_PER_BAR: f32 = 14.5038;
+
+const KPA_PER_BAR: f32 = 100.0;
+
+/// What a sensor measures; selects the conversion and formatting of its value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Quantity {
+    /// Base unit °C.
+    Temperature,
+    /// Base unit bar.
+    Pressure,
+    /// Air-fuel mixture, base unit lambda.
+    Mixture,
+    /// Always volts.
+    Voltage,
+    /// Always rpm.
+    EngineSpeed,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TempUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+impl TempUnit {
+    pub const COUNT: usize = 2;
+
+    pub const fn from_index(index: u8) -> Self {
+        match index {
+            1 => Self::Fahrenheit,
+            _ => Self::Celsius,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PressureUnit {
+    Bar,
+    Psi,
+    Kpa,
+}
+
+impl PressureUnit {
+    pub const COUNT: usize = 3;
+
+    pub const fn from_index(index: u8) -> Self {
+        match index {
+            1 => Self::Psi,
+            2 => Self::Kpa,
+            _ => Self::Bar,
+        }
+    }
+
+    /// Following unit in `Bar -> Psi -> Kpa` order, wrapping.
+    pub const fn next(self) -> Self { Self::from_index((self as u8 + 1) % Self::COUNT as u8) }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MixtureUnit {
+    Afr,
+    Lambda,
+}
+
+impl MixtureUnit {
+    pub const COUNT: usize = 2;
+
+    pub const fn from_index(index: u8) -> Self {
+        match index {
+            1 => Self::Lambda,
+            _ => Self::Afr,
+        }
+    }
+}
+
+/// Fuel the engine runs on; sets the stoichiometric AFR used to show lambda as AFR.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fuel {
+    /// Pure gasoline.
+    E0,
+    /// Pump gasoline with 10% ethanol.
+    E10,
+    /// 85% ethanol blend.
+    E85,
+}
+
+impl Fuel {
+    pub const COUNT: usize = 3;
+
+    pub const fn from_index(index: u8) -> Self {
+        match index {
+            1 => Self::E10,
+            2 => Self::E85,
+            _ => Self::E0,
+        }
+    }
+
+    /// Mass of air per mass of fuel at lambda 1.
+    pub const fn stoich(self) -> f32 {
+        match self {
+            Self::E0 => 14.7,
+            Self::E10 => 14.1,
+            Self::E85 => 9.8,
+        }
+    }
+}
+
+/// Preferred display unit per quantity. Voltage and engine speed have only one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UnitPrefs {
+    pub temperature: TempUnit,
+    pub pressure: PressureUnit,
+    pub mixture: MixtureUnit,
+    pub fuel: Fuel,
+}
+
+impl UnitPrefs {
+    /// Display in the base units, i.e. the stored values unchanged.
+    pub const BASE: Self = Self {
+        temperature: TempUnit::Celsius,
+        pressure: PressureUnit::Bar,
+        mixture: MixtureUnit::Lambda,
+        fuel: Fuel::E0,
+    };
+
+    /// Defaults of a fresh settings record, from `dashboard.toml`.
+    pub const fn new() -> Self { DEFAULT_UNITS }
+
+    /// Whether `quantity` is displayed in its base unit, i.e. `convert` is the identity.
+    pub const fn is_base(
+        &self,
+        quantity: Quantity,
+    ) -> bool {
+        match quantity {
+            Quantity::Temperature => matches!(self.temperature, TempUnit::Celsius),
+            Quantity::Pressure => matches!(self.pressure, PressureUnit::Bar),
+            Quantity::Mixture => matches!(self.mixture, MixtureUnit::Lambda),
+            Quantity::Voltage | Quantity::EngineSpeed => true,
+        }
+    }
+
+    /// Base-unit `value` in the preferred unit.
+    pub fn convert(
+        &self,
+        quantity: Quantity,
+        value: f32,
+    ) -> f32 {
+        match quantity {
+            Quantity::Temperature => {
+                match self.temperature {
+                    TempUnit::Celsius => value,
+                    TempUnit::Fahrenheit => value * 1.8 + 32.0,
+                }
+            }
+            Quantity::Pressure => {
+                match self.pressure {
+                    PressureUnit::Bar => value,
+                    PressureUnit::Psi => value * PSI_PER_BAR,
+                    PressureUnit::Kpa => value * KPA_PER_BAR,
+                }
+            }
+            Quantity::Mixture => {
+                match self.mixture {
+                    MixtureUnit::Afr => value * self.fuel.stoich(),
+                    MixtureUnit::Lambda => value,
+                }
+            }
+            Quantity::Voltage | Quantity::EngineSpeed => value,
+        }
+    }
+
+    /// Base-unit difference (or rate) `delta` in the preferred unit: scaled like
+    /// `convert`, without its offset.
+    pub fn convert_delta(
+        &self,
+        quantity: Quantity,
+        delta: f32,
+    ) -> f32 {
+        match (quantity, self.temperature) {
+            (Quantity::Temperature, TempUnit::Fahrenheit) => delta * 1.8,
+            (Quantity::Temperature, TempUnit::Celsius) => delta,
+            _ => self.convert(quantity, delta),
+        }
+    }
+
+    /// Signed rate in the preferred unit with its unit and time base, e.g. "+3C/min".
+    pub fn write_rate<W: Write>(
+        &self,
+        quantity: Quantity,
+        rate: f32,
+        per: &str,
+        out: &mut W,
+    ) -> fmt::Result {
+        write!(
+            out,
+            "{:+.*}{}{}",
+            self.precision(quantity),
+            self.convert_delta(quantity, rate),
+            self.symbol(quantity),
+            per
+        )
+    }
+
+    /// Unit name for captions and titles; empty for rpm, which is named by its label.
+    pub const fn symbol(
+        &self,
+        quantity: Quantity,
+    ) -> &'static str {
+        match quantity {
+            Quantity::Temperature => {
+                match self.temperature {
+                    TempUnit::Celsius => "C",
+                    TempUnit::Fahrenheit => "F",
+                }
+            }
+            Quantity::Pressure => {
+                match self.pressure {
+                    PressureUnit::Bar => "BAR",
+                    PressureUnit::Psi => "PSI",
+                    PressureUnit::Kpa => "KPA",
+                }
+            }
+            Quantity::Mixture => {
+                match self.mixture {
+                    MixtureUnit::Afr => "AFR",
+                    MixtureUnit::Lambda => "LAMBDA",
+                }
+            }
+            Quantity::Voltage => "V",
+            Quantity::EngineSpeed => "",
+        }
+    }
+
+    /// Unit written directly after a number ("95C", "14.2V"). Pressure and mixture
+    /// values are too wide for an inline unit and carry it in the caption instead.
+    pub const fn suffix(
+        &self,
+        quantity: Quantity,
+    ) -> &'static str {
+        match quantity {
+            Quantity::Temperature | Quantity::Voltage => self.symbol(quantity),
+            _ => "",
+        }
+    }
+
+    /// Decimal places in the preferred unit.
+    pub const fn precision(
+        &self,
+        quantity: Quantity,
+    ) -> usize {
+        match quantity {
+            Quantity::Temperature | Quantity::EngineSpeed => 0,
+            Quantity::Pressure => {
+                match self.pressure {
+                    PressureUnit::Bar => 2,
+                    PressureUnit::Psi => 1,
+                    PressureUnit::Kpa => 0,
+                }
+            }
+            Quantity::Mixture => {
+                match self.mixture {
+                    MixtureUnit::Afr => 1,
+                    MixtureUnit::Lambda => 2,
+                }
+            }
+            Quantity::Voltage => 1,
+        }
+    }
+
+    /// Characters of the widest normal reading, without suffix ("-40", "2.50", "250", "1.00", "7000").
+    pub const fn width(
+        &self,
+        quantity: Quantity,
+    ) -> usize {
+        match quantity {
+            Quantity::Temperature => 4,
+            Quantity::Pressure => {
+                match self.pressure {
+                    PressureUnit::Bar => 5,
+                    PressureUnit::Psi => 4,
+                    PressureUnit::Kpa => 3,
+                }
+            }
+            Quantity::Mixture => 4,
+            Quantity::Voltage => 4,
+            Quantity::EngineSpeed => 4,
+        }
+    }
+
+    /// Base-unit `value` converted and written with the preferred precision.
+    pub fn write<W: Write>(
+        &self,
+        quantity: Quantity,
+        value: f32,
+        out: &mut W,
+    ) -> fmt::Result {
+        write!(out, "{:.*}", self.precision(quantity), self.convert(quantity, value))
+    }
+
+    /// As `write`, followed by the inline suffix.
+    pub fn write_with_suffix<W: Write>(
+        &self,
+        quantity: Quantity,
+        value: f32,
+        out: &mut W,
+    ) -> fmt::Result {
+        self.write(quantity, value, out)?;
+        out.write_str(self.suffix(quantity))
+    }
+
+    /// As `write`, right-aligned to `width` so columns of readings line up.
+    pub fn write_padded<W: Write>(
+        &self,
+        quantity: Quantity,
+        value: f32,
+        out: &mut W,
+    ) -> fmt::Result {
+        write!(
+            out,
+            "{:>width$.prec$}",
+            self.convert(quantity, value),
+            width = self.width(quantity),
+            prec = self.precision(quantity)
+        )
+    }
+}
+
+impl Default for UnitPrefs {
+    fn default() -> Self { Self::new() }
+}
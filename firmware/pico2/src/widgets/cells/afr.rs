@@ -1,16 +1,45 @@
-use core::fmt::Write;
-
 use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::pixelcolor::Rgb565;
 use embedded_graphics::prelude::*;
 use embedded_graphics::text::Text;
 use heapless::String;
 
-use super::{SensorDisplayData, label_color_for_bg, label_style_for_text};
-use crate::thresholds::{AFR_LEAN_CRITICAL, AFR_OPTIMAL_MAX, AFR_RICH, AFR_RICH_AF, AFR_STOICH};
-use crate::ui::{BLACK, BLUE, CENTERED, DARK_TEAL, GREEN, LABEL_FONT, ORANGE, RED, VALUE_FONT};
+use super::{SensorDisplayData, label_style_for_text};
+use crate::limits::{self, Limit};
+use crate::ui::{CENTERED, LABEL_FONT, Status, VALUE_FONT, theme};
+use crate::units::{MixtureUnit, Quantity, UnitPrefs};
 use crate::widgets::primitives::{draw_cell_background, draw_mini_graph, draw_value_with_outline};
 
+pub fn lambda_status(lambda: f32) -> Status {
+    if lambda < limits::get(Limit::LambdaRichAf) {
+        Status::Cold
+    } else if lambda < limits::get(Limit::LambdaRich) {
+        Status::Cool
+    } else if lambda < limits::get(Limit::LambdaOptimalMax) {
+        Status::Normal
+    } else if lambda <= limits::get(Limit::LambdaLeanCritical) {
+        Status::Warning
+    } else {
+        Status::Critical
+    }
+}
+
+pub fn is_critical_lambda(lambda: f32) -> bool { lambda > limits::get(Limit::LambdaLeanCritical) }
+
+fn mixture_status(lambda: f32) -> &'static str {
+    if lambda < limits::get(Limit::LambdaRichAf) {
+        "RICH AF"
+    } else if lambda < limits::get(Limit::LambdaRich) {
+        "RICH"
+    } else if lambda < limits::get(Limit::LambdaOptimalMax) {
+        "OPTIMAL"
+    } else if lambda <= limits::get(Limit::LambdaLeanCritical) {
+        "LEAN"
+    } else {
+        "LEAN AF"
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn draw_afr_cell<D>(
     display: &mut D,
@@ -18,8 +47,10 @@ pub fn draw_afr_cell<D>(
     y: u32,
     w: u32,
     h: u32,
-    afr: f32,
+    lambda: f32,
+    target: Option<f32>,
     state: &SensorDisplayData<'_>,
+    units: &UnitPrefs,
     blink_on: bool,
     shake_offset: i32,
     bg_override: Option<Rgb565>,
@@ -27,28 +58,20 @@ pub fn draw_afr_cell<D>(
 where
     D: DrawTarget<Color = Rgb565>,
 {
-    let is_critical = afr > AFR_LEAN_CRITICAL;
-    let (mut bg_color, status) = if afr < AFR_RICH_AF {
-        (BLUE, "RICH AF")
-    } else if afr < AFR_RICH {
-        (DARK_TEAL, "RICH")
-    } else if afr < AFR_OPTIMAL_MAX {
-        (GREEN, "OPTIMAL")
-    } else if afr <= AFR_LEAN_CRITICAL {
-        (ORANGE, "LEAN")
-    } else {
-        (RED, "LEAN AF")
-    };
+    let theme = theme::active();
+    let is_critical = is_critical_lambda(lambda);
+    let mut bg_color = theme.status(lambda_status(lambda));
+    let status = mixture_status(lambda);
 
     if let Some(override_color) = bg_override {
         bg_color = override_color;
     }
 
     if is_critical && !blink_on {
-        bg_color = BLACK;
+        bg_color = theme.background;
     }
 
-    let text_color = label_color_for_bg(bg_color);
+    let text_color = theme.text_on(bg_color);
     let label_style = label_style_for_text(text_color);
 
     draw_cell_background(display, x, y, w, h, bg_color);
@@ -62,7 +85,7 @@ where
         .ok();
 
     let mut value_str: String<16> = String::new();
-    let _ = write!(value_str, "{afr:.1}");
+    let _ = units.write(Quantity::Mixture, lambda, &mut value_str);
 
     draw_value_with_outline(
         display,
@@ -73,11 +96,26 @@ where
         CENTERED,
     );
 
-    let lambda = afr / AFR_STOICH;
-    let mut lambda_str: String<16> = String::new();
-    let _ = write!(lambda_str, "L {lambda:.2}");
-    let lambda_style = MonoTextStyle::new(LABEL_FONT, text_color);
-    Text::with_text_style(&lambda_str, Point::new(center_x, center_y + 4), lambda_style, CENTERED)
+    // Second line: the ECU's commanded value next to the actual one above it,
+    // or the actual value in the other mixture unit when there is no target
+    let mut second_str: String<16> = String::new();
+    if let Some(target) = target {
+        let _ = second_str.push_str("CMD ");
+        let _ = units.write(Quantity::Mixture, target, &mut second_str);
+    } else {
+        let (alternate_unit, prefix) = match units.mixture {
+            MixtureUnit::Afr => (MixtureUnit::Lambda, "L "),
+            MixtureUnit::Lambda => (MixtureUnit::Afr, "AFR "),
+        };
+        let alternate = UnitPrefs {
+            mixture: alternate_unit,
+            ..*units
+        };
+        let _ = second_str.push_str(prefix);
+        let _ = alternate.write(Quantity::Mixture, lambda, &mut second_str);
+    }
+    let second_style = MonoTextStyle::new(LABEL_FONT, text_color);
+    Text::with_text_style(&second_str, Point::new(center_x, center_y + 4), second_style, CENTERED)
         .draw(display)
         .ok();
 
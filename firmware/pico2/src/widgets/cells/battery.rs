@@ -1,16 +1,27 @@
-use core::fmt::Write;
-
 use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::pixelcolor::Rgb565;
 use embedded_graphics::prelude::*;
 use embedded_graphics::text::Text;
 use heapless::String;
 
-use super::{SensorDisplayData, label_color_for_bg, label_style_for_text, peak_highlight_for_text};
-use crate::thresholds::{BATT_CRITICAL, BATT_WARNING};
-use crate::ui::{BLACK, CENTERED, LABEL_FONT, ORANGE, RED, VALUE_FONT_MEDIUM, WHITE};
+use super::{SensorDisplayData, label_style_for_text, peak_highlight_for_text};
+use crate::limits::{self, Limit};
+use crate::ui::{CENTERED, LABEL_FONT, Status, VALUE_FONT_MEDIUM, theme};
+use crate::units::{Quantity, UnitPrefs};
 use crate::widgets::primitives::{draw_cell_background, draw_mini_graph, draw_trend_arrow, draw_value_with_outline};
 
+pub fn batt_status(voltage: f32) -> Status {
+    if voltage < limits::get(Limit::BattCritical) {
+        Status::Critical
+    } else if voltage < limits::get(Limit::BattWarning) {
+        Status::Warning
+    } else {
+        Status::Neutral
+    }
+}
+
+pub fn is_critical_batt(voltage: f32) -> bool { voltage < limits::get(Limit::BattCritical) }
+
 #[allow(clippy::too_many_arguments)]
 pub fn draw_batt_cell<D>(
     display: &mut D,
@@ -22,6 +33,7 @@ pub fn draw_batt_cell<D>(
     min_voltage: f32,
     max_voltage: f32,
     state: &SensorDisplayData<'_>,
+    units: &UnitPrefs,
     blink_on: bool,
     shake_offset: i32,
     bg_override: Option<Rgb565>,
@@ -29,26 +41,21 @@ pub fn draw_batt_cell<D>(
 where
     D: DrawTarget<Color = Rgb565>,
 {
-    let is_critical = voltage < BATT_CRITICAL;
-    let mut bg_color = if voltage < BATT_CRITICAL {
-        RED
-    } else if voltage < BATT_WARNING {
-        ORANGE
-    } else {
-        BLACK
-    };
+    let theme = theme::active();
+    let is_critical = is_critical_batt(voltage);
+    let mut bg_color = theme.status(batt_status(voltage));
 
     if let Some(override_color) = bg_override {
         bg_color = override_color;
     }
 
     if is_critical && !blink_on {
-        bg_color = BLACK;
+        bg_color = theme.background;
     }
 
     draw_cell_background(display, x, y, w, h, bg_color);
 
-    let base_text = label_color_for_bg(bg_color);
+    let base_text = theme.text_on(bg_color);
     let label_style = label_style_for_text(base_text);
     let peak_color = peak_highlight_for_text(base_text);
 
@@ -65,7 +72,7 @@ where
     }
 
     let mut value_str: String<16> = String::new();
-    let _ = write!(value_str, "{voltage:.1}V");
+    let _ = units.write_with_suffix(Quantity::Voltage, voltage, &mut value_str);
     let value_color = if state.is_new_peak { peak_color } else { base_text };
 
     draw_value_with_outline(
@@ -98,17 +105,18 @@ where
         |_| graph_line_color,
     );
 
-    let minmax_color = if base_text == BLACK {
-        BLACK
+    let minmax_color = if base_text == theme.background {
+        theme.background
     } else if is_critical {
-        WHITE
+        theme.text
     } else {
-        ORANGE
+        theme.accent
     };
     let minmax_style = MonoTextStyle::new(LABEL_FONT, minmax_color);
 
     let mut min_str: String<16> = String::new();
-    let _ = write!(min_str, "MIN {min_voltage:.1}V");
+    let _ = min_str.push_str("MIN ");
+    let _ = units.write_with_suffix(Quantity::Voltage, min_voltage, &mut min_str);
     Text::with_text_style(
         &min_str,
         Point::new(center_x, (y + h) as i32 - 18),
@@ -119,7 +127,8 @@ where
     .ok();
 
     let mut max_str: String<16> = String::new();
-    let _ = write!(max_str, "MAX {max_voltage:.1}V");
+    let _ = max_str.push_str("MAX ");
+    let _ = units.write_with_suffix(Quantity::Voltage, max_voltage, &mut max_str);
     Text::with_text_style(
         &max_str,
         Point::new(center_x, (y + h) as i32 - 8),
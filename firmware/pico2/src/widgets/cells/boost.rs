@@ -7,10 +7,24 @@ use embedded_graphics::text::Text;
 use heapless::String;
 
 use super::value_style_for_color;
-use crate::thresholds::BAR_TO_PSI;
-use crate::ui::{BLACK, CENTERED, LABEL_FONT, LABEL_STYLE_ORANGE, LABEL_STYLE_WHITE, PINK, WHITE};
+use crate::limits::{self, Limit};
+use crate::ui::{CENTERED, LABEL_FONT, Status, label_style, theme};
+use crate::units::{PressureUnit, Quantity, UnitPrefs};
 use crate::widgets::primitives::draw_cell_background;
 
+/// Boost bands for gauge arcs and the focus view. The boost cell itself stays on the background.
+pub fn boost_status(boost_bar: f32) -> Status {
+    if boost_bar >= limits::get(Limit::BoostCritical) {
+        Status::Critical
+    } else if boost_bar >= limits::get(Limit::BoostHigh) {
+        Status::Warning
+    } else {
+        Status::Neutral
+    }
+}
+
+pub fn is_critical_boost(boost_bar: f32) -> bool { boost_bar >= limits::get(Limit::BoostCritical) }
+
 #[allow(clippy::too_many_arguments)]
 pub fn draw_boost_cell<D>(
     display: &mut D,
@@ -20,14 +34,15 @@ pub fn draw_boost_cell<D>(
     h: u32,
     boost_bar: f32,
     max_boost: f32,
-    show_psi: bool,
+    units: &UnitPrefs,
     show_easter_egg: bool,
     blink_on: bool,
     shake_offset: i32,
 ) where
     D: DrawTarget<Color = Rgb565>,
 {
-    draw_cell_background(display, x, y, w, h, BLACK);
+    let theme = theme::active();
+    draw_cell_background(display, x, y, w, h, theme.background);
 
     let center_x = (x + w / 2) as i32;
     let center_y = (y + h / 2) as i32;
@@ -36,57 +51,52 @@ pub fn draw_boost_cell<D>(
     Text::with_text_style(
         "BOOST REL",
         Point::new(center_x, y as i32 + 14),
-        LABEL_STYLE_WHITE,
+        label_style(),
         CENTERED,
     )
     .draw(display)
     .ok();
 
-    let boost_psi = boost_bar * BAR_TO_PSI;
+    // Second line shows the reading in PSI, or in bar when PSI/kPa is the main unit
+    let alternate = UnitPrefs {
+        pressure: if units.pressure == PressureUnit::Bar {
+            PressureUnit::Psi
+        } else {
+            PressureUnit::Bar
+        },
+        ..*units
+    };
 
     let mut value_str: String<16> = String::new();
-    if show_psi {
-        let _ = write!(value_str, "{boost_psi:.1}");
+    let _ = units.write(Quantity::Pressure, boost_bar, &mut value_str);
+    let value_color = if show_easter_egg && blink_on {
+        theme.special
     } else {
-        let _ = write!(value_str, "{boost_bar:.2}");
-    }
-    let value_color = if show_easter_egg {
-        if blink_on { PINK } else { WHITE }
-    } else {
-        WHITE
+        theme.text
     };
     let value_style = value_style_for_color(value_color);
     Text::with_text_style(&value_str, Point::new(value_x, center_y - 8), value_style, CENTERED)
         .draw(display)
         .ok();
 
-    let unit_label = if show_psi { "PSI" } else { "BAR" };
     Text::with_text_style(
-        unit_label,
+        units.symbol(Quantity::Pressure),
         Point::new(center_x, center_y + 10),
-        LABEL_STYLE_WHITE,
+        label_style(),
         CENTERED,
     )
     .draw(display)
     .ok();
 
     let mut conv_str: String<16> = String::new();
-    if show_psi {
-        let _ = write!(conv_str, "{boost_bar:.2} BAR");
-    } else {
-        let _ = write!(conv_str, "{boost_psi:.1} PSI");
-    }
-    Text::with_text_style(
-        &conv_str,
-        Point::new(center_x, center_y + 22),
-        LABEL_STYLE_WHITE,
-        CENTERED,
-    )
-    .draw(display)
-    .ok();
+    let _ = alternate.write(Quantity::Pressure, boost_bar, &mut conv_str);
+    let _ = write!(conv_str, " {}", alternate.symbol(Quantity::Pressure));
+    Text::with_text_style(&conv_str, Point::new(center_x, center_y + 22), label_style(), CENTERED)
+        .draw(display)
+        .ok();
 
     if show_easter_egg {
-        let easter_color = if blink_on { WHITE } else { PINK };
+        let easter_color = if blink_on { theme.text } else { theme.special };
         let easter_style = MonoTextStyle::new(LABEL_FONT, easter_color);
         Text::with_text_style(
             "Fast AF Boi!",
@@ -98,15 +108,12 @@ pub fn draw_boost_cell<D>(
         .ok();
     } else {
         let mut max_str: String<16> = String::new();
-        if show_psi {
-            let _ = write!(max_str, "MAX {max_boost:.1}");
-        } else {
-            let _ = write!(max_str, "MAX {max_boost:.2}");
-        }
+        let _ = max_str.push_str("MAX ");
+        let _ = units.write(Quantity::Pressure, max_boost, &mut max_str);
         Text::with_text_style(
             &max_str,
             Point::new(center_x, (y + h) as i32 - 8),
-            LABEL_STYLE_ORANGE,
+            MonoTextStyle::new(LABEL_FONT, theme.accent),
             CENTERED,
         )
         .draw(display)
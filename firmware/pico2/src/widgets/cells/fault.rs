@@ -0,0 +1,61 @@
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::Text;
+
+use super::label_style_for_text;
+use crate::state::Fault;
+use crate::ui::{CENTERED, Status, TITLE_FONT, theme};
+use crate::widgets::primitives::draw_cell_background;
+
+/// A sensor whose readings failed a plausibility check: no value, so a broken sender
+/// is never mistaken for a real reading. Returns the background it was drawn on.
+pub fn draw_fault_cell<D>(
+    display: &mut D,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    label: &str,
+    fault: Fault,
+) -> Rgb565
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let theme = theme::active();
+    let bg_color = theme.background;
+    draw_cell_background(display, x, y, w, h, bg_color);
+    if w >= 8 && h >= 8 {
+        Rectangle::new(Point::new(x as i32 + 3, y as i32 + 3), Size::new(w - 6, h - 6))
+            .into_styled(PrimitiveStyle::with_stroke(theme.status(Status::Warning), 2))
+            .draw(display)
+            .ok();
+    }
+
+    let label_style = label_style_for_text(theme.text);
+    let center_x = (x + w / 2) as i32;
+    let center_y = (y + h / 2) as i32;
+
+    Text::with_text_style(label, Point::new(center_x, y as i32 + 14), label_style, CENTERED)
+        .draw(display)
+        .ok();
+    Text::with_text_style(
+        "FAULT",
+        Point::new(center_x, center_y + 6),
+        MonoTextStyle::new(TITLE_FONT, theme.status(Status::Warning)),
+        CENTERED,
+    )
+    .draw(display)
+    .ok();
+    Text::with_text_style(
+        fault.label(),
+        Point::new(center_x, y as i32 + h as i32 - 8),
+        label_style,
+        CENTERED,
+    )
+    .draw(display)
+    .ok();
+
+    bg_color
+}
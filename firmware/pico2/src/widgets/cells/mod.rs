@@ -1,37 +1,39 @@
 mod afr;
 mod battery;
 mod boost;
+mod fault;
+mod rpm;
 mod temp;
 
-pub use afr::draw_afr_cell;
-pub use battery::draw_batt_cell;
-pub use boost::draw_boost_cell;
+pub use afr::{draw_afr_cell, is_critical_lambda, lambda_status};
+pub use battery::{batt_status, draw_batt_cell, is_critical_batt};
+pub use boost::{boost_status, draw_boost_cell, is_critical_boost};
 use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::pixelcolor::Rgb565;
-use embedded_graphics::prelude::IntoStorage;
+use embedded_graphics::prelude::DrawTarget;
+use fault::draw_fault_cell;
+pub use rpm::{is_critical_rpm, rpm_status};
 pub use temp::{
     draw_temp_cell,
+    is_critical_dsg,
     is_critical_egt,
     is_critical_iat,
-    is_critical_oil_dsg,
+    is_critical_oil,
     is_critical_water,
     is_low_temp_oil,
-    temp_color_egt,
-    temp_color_iat,
-    temp_color_oil_dsg,
-    temp_color_water,
+    temp_status_dsg,
+    temp_status_egt,
+    temp_status_iat,
+    temp_status_oil,
+    temp_status_water,
 };
 
-use crate::ui::{
-    BLACK,
-    LABEL_STYLE_BLACK,
-    LABEL_STYLE_WHITE,
-    VALUE_FONT,
-    VALUE_STYLE_BLACK,
-    VALUE_STYLE_WHITE,
-    WHITE,
-    YELLOW,
-};
+use crate::state::{GRAPH_HISTORY_SIZE, SensorChannel, SensorDescriptor, Widget};
+use crate::thresholds::BOOST_EASTER_EGG_BAR;
+use crate::ui::{LABEL_FONT, VALUE_FONT, theme};
+use crate::units::UnitPrefs;
+use crate::widgets::cue::draw_status_cue;
+use crate::widgets::gauge::draw_gauge_cell;
 
 pub struct SensorDisplayData<'a> {
     pub trend: Option<bool>,
@@ -45,45 +47,176 @@ pub struct SensorDisplayData<'a> {
     pub average: Option<f32>,
 }
 
-impl<'a> SensorDisplayData<'a> {}
-
-pub fn label_color_for_bg(bg_color: Rgb565) -> Rgb565 {
-    let luma = calculate_luminance(bg_color);
-    if luma < 128 { WHITE } else { BLACK }
+impl<'a> SensorDisplayData<'a> {
+    pub fn from_channel(channel: &'a SensorChannel) -> Self {
+        let state = &channel.state;
+        let (buffer, start_idx, count, min, max) = state.get_graph_data();
+        Self {
+            trend: channel.trend,
+            is_new_peak: state.is_new_peak,
+            graph_buffer: buffer,
+            graph_buffer_size: GRAPH_HISTORY_SIZE,
+            graph_start_idx: start_idx,
+            graph_count: count,
+            graph_min: min,
+            graph_max: max,
+            average: state.get_average(),
+        }
+    }
 }
 
-#[inline]
-pub(crate) fn peak_highlight_for_text(base_text: Rgb565) -> Rgb565 { if base_text == WHITE { YELLOW } else { BLACK } }
+/// Draw one sensor into a layout slot with the slot's widget, with its status cue on top if `cues` is set.
+/// A faulty sensor gets the fault cell instead, whatever the slot's widget.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_sensor_cell<D>(
+    display: &mut D,
+    desc: &SensorDescriptor,
+    channel: &SensorChannel,
+    widget: Widget,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    units: &UnitPrefs,
+    cues: bool,
+    blink_on: bool,
+    bg_override: Option<Rgb565>,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    if let Some(fault) = channel.fault {
+        draw_fault_cell(display, x, y, w, h, desc.label, fault);
+        return;
+    }
 
-#[inline]
-pub(crate) fn calculate_luminance(color: Rgb565) -> u32 {
-    let raw = color.into_storage();
-    let r5 = u32::from((raw >> 11) & 0x1F);
-    let g6 = u32::from((raw >> 5) & 0x3F);
-    let b5 = u32::from(raw & 0x1F);
+    // The cells test the raw value for critical; only let them blink once the debounced flag is set
+    let blink_on = blink_on || !channel.critical;
+    let bg_color = draw_widget(display, desc, channel, widget, x, y, w, h, units, blink_on, bg_override);
+    if cues {
+        draw_status_cue(display, x, y, w, h, channel.status, theme::active().text_on(bg_color));
+    }
+}
 
-    let r8 = (r5 << 3) | (r5 >> 2);
-    let g8 = (g6 << 2) | (g6 >> 4);
-    let b8 = (b5 << 3) | (b5 >> 2);
+/// Returns the background the widget was drawn on.
+#[allow(clippy::too_many_arguments)]
+fn draw_widget<D>(
+    display: &mut D,
+    desc: &SensorDescriptor,
+    channel: &SensorChannel,
+    widget: Widget,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    units: &UnitPrefs,
+    blink_on: bool,
+    bg_override: Option<Rgb565>,
+) -> Rgb565
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    if widget == Widget::Gauge
+        && let Some(scale) = &desc.gauge
+    {
+        draw_gauge_cell(display, x, y, w, h, desc, channel, scale, units, blink_on);
+        return theme::active().background;
+    }
 
-    (r8 * 77 + g8 * 150 + b8 * 29) >> 8
+    let data = SensorDisplayData::from_channel(channel);
+    match widget {
+        Widget::Boost => {
+            let show_easter_egg = channel.value >= BOOST_EASTER_EGG_BAR;
+            draw_boost_cell(
+                display,
+                x,
+                y,
+                w,
+                h,
+                channel.value,
+                channel.max,
+                units,
+                show_easter_egg,
+                blink_on,
+                0,
+            );
+            theme::active().background
+        }
+        Widget::Afr => {
+            draw_afr_cell(
+                display,
+                x,
+                y,
+                w,
+                h,
+                channel.value,
+                channel.target,
+                &data,
+                units,
+                blink_on,
+                0,
+                bg_override,
+            )
+        }
+        Widget::Battery => {
+            draw_batt_cell(
+                display,
+                x,
+                y,
+                w,
+                h,
+                channel.value,
+                channel.min,
+                channel.max,
+                &data,
+                units,
+                blink_on,
+                0,
+                bg_override,
+            )
+        }
+        // A gauge slot for a sensor without a gauge scale falls back to the generic cell
+        Widget::Temp | Widget::Gauge => {
+            draw_temp_cell(
+                display,
+                x,
+                y,
+                w,
+                h,
+                desc.label,
+                desc.quantity,
+                units,
+                channel.value,
+                channel.max,
+                &data,
+                desc.status_fn,
+                desc.critical_fn,
+                desc.low_fn,
+                blink_on,
+                0,
+                bg_override,
+            )
+        }
+    }
 }
 
+/// Peak values stand out in the highlight colour, unless the cell's text is drawn
+/// in the background colour on a bright status fill.
 #[inline]
-pub(crate) fn label_style_for_text(base_text: Rgb565) -> MonoTextStyle<'static, Rgb565> {
-    if base_text == WHITE {
-        LABEL_STYLE_WHITE
+pub(crate) fn peak_highlight_for_text(base_text: Rgb565) -> Rgb565 {
+    let theme = theme::active();
+    if base_text == theme.text {
+        theme.highlight
     } else {
-        LABEL_STYLE_BLACK
+        base_text
     }
 }
 
+#[inline]
+pub(crate) fn label_style_for_text(base_text: Rgb565) -> MonoTextStyle<'static, Rgb565> {
+    MonoTextStyle::new(LABEL_FONT, base_text)
+}
+
+#[inline]
 pub(crate) fn value_style_for_color(color: Rgb565) -> MonoTextStyle<'static, Rgb565> {
-    if color == WHITE {
-        VALUE_STYLE_WHITE
-    } else if color == BLACK {
-        VALUE_STYLE_BLACK
-    } else {
-        MonoTextStyle::new(VALUE_FONT, color)
-    }
+    MonoTextStyle::new(VALUE_FONT, color)
 }
@@ -0,0 +1,14 @@
+use crate::limits::{self, Limit};
+use crate::ui::Status;
+
+pub fn rpm_status(rpm: f32) -> Status {
+    if rpm >= limits::get(Limit::RpmRedline) {
+        Status::Critical
+    } else if rpm >= limits::get(Limit::RpmShift) {
+        Status::Elevated
+    } else {
+        Status::Neutral
+    }
+}
+
+pub fn is_critical_rpm(rpm: f32) -> bool { rpm >= limits::get(Limit::RpmRedline) }
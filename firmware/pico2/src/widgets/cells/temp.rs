@@ -1,5 +1,3 @@
-use core::fmt::Write;
-
 use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::pixelcolor::Rgb565;
 use embedded_graphics::prelude::*;
@@ -7,27 +5,13 @@ use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
 use embedded_graphics::text::Text;
 use heapless::String;
 
-use super::{SensorDisplayData, label_color_for_bg, label_style_for_text, peak_highlight_for_text};
-use crate::thresholds::{
-    COOLANT_COLD_MAX,
-    COOLANT_CRITICAL,
-    EGT_COLD_MAX,
-    EGT_CRITICAL,
-    EGT_HIGH_LOAD,
-    EGT_SPIRITED,
-    IAT_COLD,
-    IAT_CRITICAL,
-    IAT_EXTREME_COLD,
-    IAT_HOT,
-    IAT_WARM,
-    OIL_DSG_CRITICAL,
-    OIL_DSG_ELEVATED,
-    OIL_DSG_HIGH,
-    OIL_LOW_TEMP,
-};
-use crate::ui::{BLACK, BLUE, CENTERED, GREEN, LABEL_FONT, ORANGE, RED, VALUE_FONT, VALUE_FONT_MEDIUM, WHITE, YELLOW};
+use super::{SensorDisplayData, label_style_for_text, peak_highlight_for_text};
+use crate::limits::{self, Limit};
+use crate::ui::{CENTERED, LABEL_FONT, Status, VALUE_FONT, VALUE_FONT_MEDIUM, theme};
+use crate::units::{Quantity, UnitPrefs};
 use crate::widgets::primitives::{draw_cell_background, draw_mini_graph, draw_trend_arrow, draw_value_with_outline};
 
+/// Displayed values from here on need four digits and switch to the medium font.
 const TEMP_LARGE_VALUE_THRESHOLD: f32 = 999.5;
 
 const TEMP_VALUE_Y_LARGE: i32 = -12;
@@ -38,65 +22,81 @@ const LOW_BADGE_MARGIN: u32 = 9;
 
 const LOW_LABEL_SHIFT: i32 = 12;
 
-pub fn temp_color_oil_dsg(temp: f32) -> (Rgb565, Rgb565) {
-    if temp >= OIL_DSG_CRITICAL {
-        (RED, WHITE)
-    } else if temp >= OIL_DSG_HIGH {
-        (ORANGE, BLACK)
-    } else if temp >= OIL_DSG_ELEVATED {
-        (YELLOW, BLACK)
+pub fn temp_status_oil(temp: f32) -> Status {
+    if temp >= limits::get(Limit::OilCritical) {
+        Status::Critical
+    } else if temp >= limits::get(Limit::OilHigh) {
+        Status::Warning
+    } else if temp >= limits::get(Limit::OilElevated) {
+        Status::Elevated
+    } else {
+        Status::Neutral
+    }
+}
+
+pub fn temp_status_dsg(temp: f32) -> Status {
+    if temp >= limits::get(Limit::DsgCritical) {
+        Status::Critical
+    } else if temp >= limits::get(Limit::DsgHigh) {
+        Status::Warning
+    } else if temp >= limits::get(Limit::DsgElevated) {
+        Status::Elevated
     } else {
-        (BLACK, WHITE)
+        Status::Neutral
     }
 }
 
-pub fn temp_color_water(temp: f32) -> (Rgb565, Rgb565) {
-    if temp > COOLANT_CRITICAL {
-        (RED, WHITE)
-    } else if temp >= COOLANT_COLD_MAX {
-        (GREEN, BLACK)
+pub fn temp_status_water(temp: f32) -> Status {
+    if temp > limits::get(Limit::CoolantCritical) {
+        Status::Critical
+    } else if temp >= limits::get(Limit::CoolantColdMax) {
+        Status::Normal
     } else {
-        (ORANGE, BLACK)
+        Status::Warning
     }
 }
 
-pub fn is_critical_oil_dsg(temp: f32) -> bool { temp >= OIL_DSG_CRITICAL }
+pub fn is_critical_oil(temp: f32) -> bool { temp >= limits::get(Limit::OilCritical) }
 
-pub fn is_critical_water(temp: f32) -> bool { temp > COOLANT_CRITICAL }
+pub fn is_critical_dsg(temp: f32) -> bool { temp >= limits::get(Limit::DsgCritical) }
 
-pub fn temp_color_iat(temp: f32) -> (Rgb565, Rgb565) {
-    if temp >= IAT_CRITICAL {
-        (RED, WHITE)
-    } else if temp >= IAT_HOT {
-        (ORANGE, BLACK)
-    } else if temp >= IAT_WARM {
-        (YELLOW, BLACK)
-    } else if temp >= IAT_COLD {
-        (GREEN, BLACK)
+pub fn is_critical_water(temp: f32) -> bool { temp > limits::get(Limit::CoolantCritical) }
+
+pub fn temp_status_iat(temp: f32) -> Status {
+    if temp >= limits::get(Limit::IatCritical) {
+        Status::Critical
+    } else if temp >= limits::get(Limit::IatHot) {
+        Status::Warning
+    } else if temp >= limits::get(Limit::IatWarm) {
+        Status::Elevated
+    } else if temp >= limits::get(Limit::IatCold) {
+        Status::Normal
     } else {
-        (BLUE, WHITE)
+        Status::Cold
     }
 }
 
-pub fn is_critical_iat(temp: f32) -> bool { temp >= IAT_CRITICAL || temp <= IAT_EXTREME_COLD }
-
-pub fn temp_color_egt(temp: f32) -> (Rgb565, Rgb565) {
-    if temp >= EGT_CRITICAL {
-        (RED, WHITE)
-    } else if temp >= EGT_HIGH_LOAD {
-        (ORANGE, BLACK)
-    } else if temp >= EGT_SPIRITED {
-        (YELLOW, BLACK)
-    } else if temp >= EGT_COLD_MAX {
-        (GREEN, BLACK)
+pub fn is_critical_iat(temp: f32) -> bool {
+    temp >= limits::get(Limit::IatCritical) || temp <= limits::get(Limit::IatExtremeCold)
+}
+
+pub fn temp_status_egt(temp: f32) -> Status {
+    if temp >= limits::get(Limit::EgtCritical) {
+        Status::Critical
+    } else if temp >= limits::get(Limit::EgtHighLoad) {
+        Status::Warning
+    } else if temp >= limits::get(Limit::EgtSpirited) {
+        Status::Elevated
+    } else if temp >= limits::get(Limit::EgtColdMax) {
+        Status::Normal
     } else {
-        (BLUE, WHITE)
+        Status::Cold
     }
 }
 
-pub fn is_critical_egt(temp: f32) -> bool { temp >= EGT_CRITICAL }
+pub fn is_critical_egt(temp: f32) -> bool { temp >= limits::get(Limit::EgtCritical) }
 
-pub fn is_low_temp_oil(temp: f32) -> bool { temp < OIL_LOW_TEMP }
+pub fn is_low_temp_oil(temp: f32) -> bool { temp < limits::get(Limit::OilLowTemp) }
 
 fn draw_low_warning_badge<D>(
     display: &mut D,
@@ -111,13 +111,19 @@ fn draw_low_warning_badge<D>(
     let badge_x = (x + LOW_BADGE_MARGIN) as i32;
     let badge_y = (y + 4) as i32;
 
-    let (bg_color, text_color) = if blink_on { (RED, WHITE) } else { (WHITE, BLACK) };
+    let theme = theme::active();
+    let (bg_color, text_color) = if blink_on {
+        let critical = theme.status(Status::Critical);
+        (critical, theme.text_on(critical))
+    } else {
+        (theme.text, theme.background)
+    };
 
     Rectangle::new(
         Point::new(badge_x - 1, badge_y - 1),
         Size::new(badge_w + 2, badge_h + 2),
     )
-    .into_styled(PrimitiveStyle::with_fill(BLACK))
+    .into_styled(PrimitiveStyle::with_fill(theme.background))
     .draw(display)
     .ok();
 
@@ -138,17 +144,19 @@ fn draw_low_warning_badge<D>(
 }
 
 #[allow(clippy::too_many_arguments)]
-pub fn draw_temp_cell<D, F, C, L>(
+pub fn draw_temp_cell<D, S, C, L>(
     display: &mut D,
     x: u32,
     y: u32,
     w: u32,
     h: u32,
     label: &str,
+    quantity: Quantity,
+    units: &UnitPrefs,
     temp: f32,
     max_temp: f32,
     state: &SensorDisplayData<'_>,
-    color_fn: F,
+    status_fn: S,
     critical_fn: C,
     low_fn: Option<L>,
     blink_on: bool,
@@ -157,11 +165,12 @@ pub fn draw_temp_cell<D, F, C, L>(
 ) -> Rgb565
 where
     D: DrawTarget<Color = Rgb565>,
-    F: Fn(f32) -> (Rgb565, Rgb565),
+    S: Fn(f32) -> Status,
     C: Fn(f32) -> bool,
     L: Fn(f32) -> bool,
 {
-    let (mut bg_color, _) = color_fn(temp);
+    let theme = theme::active();
+    let mut bg_color = theme.status(status_fn(temp));
     let is_critical = critical_fn(temp);
     let max_is_critical = critical_fn(max_temp);
     let is_low = low_fn.as_ref().is_some_and(|f| f(temp));
@@ -171,7 +180,7 @@ where
     }
 
     if is_critical && !blink_on {
-        bg_color = BLACK;
+        bg_color = theme.background;
     }
 
     draw_cell_background(display, x, y, w, h, bg_color);
@@ -180,7 +189,7 @@ where
         draw_low_warning_badge(display, x, y, blink_on);
     }
 
-    let base_text = label_color_for_bg(bg_color);
+    let base_text = theme.text_on(bg_color);
     let label_style = label_style_for_text(base_text);
     let peak_color = peak_highlight_for_text(base_text);
 
@@ -200,10 +209,10 @@ where
     }
 
     let mut value_str: String<16> = String::new();
-    let _ = write!(value_str, "{temp:.0}C");
+    let _ = units.write_with_suffix(quantity, temp, &mut value_str);
     let value_color = if state.is_new_peak { peak_color } else { base_text };
 
-    let (value_font, value_y_offset) = if temp >= TEMP_LARGE_VALUE_THRESHOLD {
+    let (value_font, value_y_offset) = if units.convert(quantity, temp) >= TEMP_LARGE_VALUE_THRESHOLD {
         (VALUE_FONT_MEDIUM, TEMP_VALUE_Y_MEDIUM)
     } else {
         (VALUE_FONT, TEMP_VALUE_Y_LARGE)
@@ -239,37 +248,43 @@ where
         |_| graph_line_color,
     );
 
-    let avg_color = if base_text == BLACK {
-        BLACK
+    let avg_color = if base_text == theme.background {
+        theme.background
     } else if is_critical {
-        WHITE
+        theme.text
     } else {
-        ORANGE
+        theme.accent
     };
     let avg_style = MonoTextStyle::new(LABEL_FONT, avg_color);
 
     let max_warning = max_is_critical && !is_critical;
     let max_color = if max_is_critical {
-        if is_critical { WHITE } else { RED }
-    } else if base_text == BLACK {
-        BLACK
+        if is_critical {
+            theme.text
+        } else {
+            theme.reading(Status::Critical)
+        }
+    } else if base_text == theme.background {
+        theme.background
     } else if is_critical {
-        WHITE
+        theme.text
     } else {
-        ORANGE
+        theme.accent
     };
     let max_style = MonoTextStyle::new(LABEL_FONT, max_color);
 
     if let Some(avg) = state.average {
         let mut avg_str: String<16> = String::new();
-        let _ = write!(avg_str, "AVG {avg:.0}C");
+        let _ = avg_str.push_str("AVG ");
+        let _ = units.write_with_suffix(quantity, avg, &mut avg_str);
         Text::with_text_style(&avg_str, Point::new(center_x, (y + h) as i32 - 22), avg_style, CENTERED)
             .draw(display)
             .ok();
     }
 
     let mut max_str: String<16> = String::new();
-    let _ = write!(max_str, "MAX {max_temp:.0}C");
+    let _ = max_str.push_str("MAX ");
+    let _ = units.write_with_suffix(quantity, max_temp, &mut max_str);
     let max_pos = Point::new(center_x, (y + h) as i32 - 6);
     let max_text = Text::with_text_style(&max_str, max_pos, max_style, CENTERED);
 
@@ -284,7 +299,7 @@ where
         let badge_width = (badge_right - badge_left).max(0) as u32;
         let badge_size = Size::new(badge_width, bb.size.height + (pad as u32 * 2));
         Rectangle::new(badge_pos, badge_size)
-            .into_styled(PrimitiveStyle::with_fill(BLACK))
+            .into_styled(PrimitiveStyle::with_fill(theme.background))
             .draw(display)
             .ok();
     }
@@ -0,0 +1,146 @@
+//! Shape-only status marking (border and corner icon), so a reading's severity
+//! can be told without relying on its colour.
+
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle, Triangle};
+
+use crate::ui::Status;
+use crate::ui::theme::{Border, Icon};
+
+/// Width and height of a cue icon.
+pub const ICON_SIZE: u32 = 9;
+
+/// Inset of the outer border line from the cell edge; matches the cell background inset.
+const BORDER_INSET: u32 = 2;
+
+/// Gap between the lines of a double border.
+const DOUBLE_GAP: u32 = 2;
+
+/// On/off run lengths of the broken border styles, in pixels.
+const DOT_PERIOD: i32 = 3;
+const DASH_ON: i32 = 6;
+const DASH_PERIOD: i32 = 10;
+
+/// Mark a cell with the cue of `status`: border just inside its edge, icon in its top-right corner.
+pub fn draw_status_cue<D>(
+    display: &mut D,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    status: Status,
+    color: Rgb565,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let cue = status.cue();
+    if cue.is_none() || w < 4 * ICON_SIZE || h < 2 * ICON_SIZE {
+        return;
+    }
+
+    let outer = Rectangle::new(
+        Point::new((x + BORDER_INSET) as i32, (y + BORDER_INSET) as i32),
+        Size::new(w - 2 * BORDER_INSET, h - 2 * BORDER_INSET),
+    );
+    match cue.border {
+        Border::None => {}
+        Border::Dotted => draw_broken_frame(display, &outer, 1, DOT_PERIOD, color),
+        Border::Dashed => draw_broken_frame(display, &outer, DASH_ON, DASH_PERIOD, color),
+        Border::Double => {
+            let style = PrimitiveStyle::with_stroke(color, 1);
+            outer.into_styled(style).draw(display).ok();
+            outer
+                .offset(-((DOUBLE_GAP + 1) as i32))
+                .into_styled(style)
+                .draw(display)
+                .ok();
+        }
+    }
+
+    let icon_origin = Point::new(
+        (x + w - BORDER_INSET - 4 - ICON_SIZE) as i32,
+        (y + BORDER_INSET + 4) as i32,
+    );
+    draw_cue_icon(display, icon_origin, cue.icon, color);
+}
+
+/// Draw `icon` in the `ICON_SIZE` square at `top_left`.
+pub fn draw_cue_icon<D>(
+    display: &mut D,
+    top_left: Point,
+    icon: Icon,
+    color: Rgb565,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let last = ICON_SIZE as i32 - 1;
+    let mid = last / 2;
+    let at = |dx: i32, dy: i32| top_left + Point::new(dx, dy);
+
+    match icon {
+        Icon::None => {}
+        Icon::Low => {
+            Triangle::new(at(0, 1), at(last, 1), at(mid, last))
+                .into_styled(PrimitiveStyle::with_stroke(color, 1))
+                .draw(display)
+                .ok();
+        }
+        Icon::High => {
+            Triangle::new(at(0, last - 1), at(last, last - 1), at(mid, 0))
+                .into_styled(PrimitiveStyle::with_stroke(color, 1))
+                .draw(display)
+                .ok();
+        }
+        Icon::Warning => {
+            Triangle::new(at(0, last), at(last, last), at(mid, 0))
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(display)
+                .ok();
+        }
+        Icon::Critical => {
+            let style = PrimitiveStyle::with_stroke(color, 2);
+            Line::new(at(0, 0), at(last, last))
+                .into_styled(style)
+                .draw(display)
+                .ok();
+            Line::new(at(last, 0), at(0, last))
+                .into_styled(style)
+                .draw(display)
+                .ok();
+        }
+    }
+}
+
+/// Rectangle outline drawn as `on` pixels out of every `period`, following the edge clockwise.
+fn draw_broken_frame<D>(
+    display: &mut D,
+    rect: &Rectangle,
+    on: i32,
+    period: i32,
+    color: Rgb565,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let Some(bottom_right) = rect.bottom_right() else {
+        return;
+    };
+    let (left, top) = (rect.top_left.x, rect.top_left.y);
+    let (right, bottom) = (bottom_right.x, bottom_right.y);
+    let width = right - left;
+    let height = bottom - top;
+
+    let perimeter = (0..2 * (width + height)).filter(|i| i % period < on).map(|i| {
+        let point = if i < width {
+            Point::new(left + i, top)
+        } else if i < width + height {
+            Point::new(right, top + i - width)
+        } else if i < 2 * width + height {
+            Point::new(right - (i - width - height), bottom)
+        } else {
+            Point::new(left, bottom - (i - 2 * width - height))
+        };
+        Pixel(point, color)
+    });
+    display.draw_iter(perimeter).ok();
+}
@@ -0,0 +1,415 @@
+//! Analog needle gauge: 240° arc scale with ticks and labels, coloured warning
+//! arcs, an anti-aliased needle and a peak-hold marker.
+//!
+//! All angles come from a trig table built at compile time, so a frame costs no
+//! `micromath` calls; the needle is interpolated between whole-degree entries.
+
+use core::f64::consts::PI;
+use core::fmt::Write;
+
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, Line, PrimitiveStyle, Triangle};
+use embedded_graphics::text::Text;
+use heapless::String;
+
+use crate::state::{SensorChannel, SensorDescriptor};
+use crate::ui::{CENTERED, Status, VALUE_FONT_MEDIUM, caption_style, label_style, theme};
+use crate::units::{Quantity, UnitPrefs};
+use crate::widgets::primitives::draw_cell_background;
+
+/// Scale of a gauge, in the sensor's base unit.
+#[derive(Clone, Copy, Debug)]
+pub struct GaugeScale {
+    pub min: f32,
+    pub max: f32,
+    /// Distance between labelled major ticks.
+    pub major_step: f32,
+    /// Minor ticks per major interval (1 = none).
+    pub minor_divisions: u32,
+    /// Labels show `value / label_divisor`, e.g. 1000 for RPM.
+    pub label_divisor: f32,
+    /// Label decimals in the base unit; converted labels are shown whole.
+    pub label_precision: usize,
+}
+
+impl GaugeScale {
+    pub const fn is_valid(&self) -> bool { self.max > self.min && self.major_step > 0.0 && self.minor_divisions > 0 }
+}
+
+/// Sweep of the scale in whole degrees, clockwise from lower left (7 o'clock) to lower right (5 o'clock).
+const SWEEP_DEG: usize = 240;
+const START_DEG: f64 = 210.0;
+
+/// Screen-space unit vectors (x right, y down) for every degree along the sweep.
+static DIRECTIONS: [(f32, f32); SWEEP_DEG + 1] = build_direction_table();
+
+/// Degrees between the corners of the quads that make up a warning arc.
+const ARC_STEP_DEG: usize = 2;
+const ARC_WIDTH: f32 = 4.0;
+
+const MAJOR_TICK_LEN: f32 = 7.0;
+const MINOR_TICK_LEN: f32 = 3.0;
+const LABEL_INSET: f32 = 15.0;
+/// Below this radius only every other major tick is labelled.
+const FULL_LABELS_MIN_RADIUS: f32 = 44.0;
+
+const NEEDLE_TAIL: f32 = 6.0;
+const NEEDLE_TIP_INSET: f32 = 5.0;
+const NEEDLE_BASE_HALF_WIDTH: f32 = 2.2;
+const NEEDLE_TIP_HALF_WIDTH: f32 = 0.7;
+const HUB_DIAMETER: u32 = 8;
+
+const PEAK_MARKER_DEPTH: f32 = 7.0;
+const PEAK_MARKER_HALF_WIDTH: f32 = 3.0;
+
+const LABEL_HEIGHT: u32 = 18;
+
+const fn build_direction_table() -> [(f32, f32); SWEEP_DEG + 1] {
+    let mut table = [(0.0, 0.0); SWEEP_DEG + 1];
+    let mut i = 0;
+    while i <= SWEEP_DEG {
+        let rad = (START_DEG - i as f64) * PI / 180.0;
+        table[i] = (const_sin(rad + PI / 2.0) as f32, -const_sin(rad) as f32);
+        i += 1;
+    }
+    table
+}
+
+/// Taylor series sine for table generation only.
+const fn const_sin(x: f64) -> f64 {
+    let mut x = x;
+    while x > PI {
+        x -= 2.0 * PI;
+    }
+    while x < -PI {
+        x += 2.0 * PI;
+    }
+    let x2 = x * x;
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 1;
+    while n < 12 {
+        term *= -x2 / ((2 * n) * (2 * n + 1)) as f64;
+        sum += term;
+        n += 1;
+    }
+    sum
+}
+
+/// Unit vector for a position along the sweep, linearly interpolated between table entries.
+fn direction(deg: f32) -> (f32, f32) {
+    let deg = deg.clamp(0.0, SWEEP_DEG as f32);
+    let i = (deg as usize).min(SWEEP_DEG - 1);
+    let t = deg - i as f32;
+    let (x0, y0) = DIRECTIONS[i];
+    let (x1, y1) = DIRECTIONS[i + 1];
+    (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+}
+
+#[inline]
+fn round(v: f32) -> i32 { if v >= 0.0 { (v + 0.5) as i32 } else { (v - 0.5) as i32 } }
+
+struct Dial {
+    cx: f32,
+    cy: f32,
+    radius: f32,
+    scale: GaugeScale,
+}
+
+impl Dial {
+    fn value_to_deg(
+        &self,
+        value: f32,
+    ) -> f32 {
+        let frac = ((value - self.scale.min) / (self.scale.max - self.scale.min)).clamp(0.0, 1.0);
+        frac * SWEEP_DEG as f32
+    }
+
+    fn point(
+        &self,
+        dir: (f32, f32),
+        radius: f32,
+    ) -> Point {
+        Point::new(round(self.cx + dir.0 * radius), round(self.cy + dir.1 * radius))
+    }
+}
+
+/// Draw a gauge cell for `channel`, with the needle at its value and the peak marker at its max.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_gauge_cell<D>(
+    display: &mut D,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    desc: &SensorDescriptor,
+    channel: &SensorChannel,
+    scale: &GaugeScale,
+    units: &UnitPrefs,
+    blink_on: bool,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let theme = theme::active();
+    draw_cell_background(display, x, y, w, h, theme.background);
+
+    let center_x = (x + w / 2) as i32;
+    Text::with_text_style(desc.label, Point::new(center_x, y as i32 + 14), label_style(), CENTERED)
+        .draw(display)
+        .ok();
+
+    // The arc spans radius above the centre and radius * sin(30°) below it
+    let radius = ((w as f32 - 10.0) / 2.0).min((h - LABEL_HEIGHT) as f32 / 1.5 - 4.0);
+    let dial = Dial {
+        cx: center_x as f32,
+        cy: (y + LABEL_HEIGHT) as f32 + 2.0 + radius,
+        radius,
+        scale: *scale,
+    };
+
+    draw_warning_arcs(display, &dial, desc);
+    draw_ticks(display, &dial, desc.quantity, units);
+
+    let mut s: String<16> = String::new();
+    if scale.label_divisor == 1.0 {
+        let _ = s.push_str(units.symbol(desc.quantity));
+    } else {
+        let _ = write!(s, "x{:.0}", scale.label_divisor);
+    }
+    Text::with_text_style(
+        &s,
+        Point::new(center_x, round(dial.cy - radius * 0.35)),
+        caption_style(),
+        CENTERED,
+    )
+    .draw(display)
+    .ok();
+
+    if channel.max > scale.min {
+        draw_peak_marker(display, &dial, channel.max);
+    }
+
+    let needle_color = if channel.critical && blink_on {
+        theme.reading(Status::Critical)
+    } else {
+        theme.text
+    };
+    draw_needle(display, &dial, channel.value, needle_color, theme.background);
+
+    Circle::with_center(Point::new(center_x, round(dial.cy)), HUB_DIAMETER)
+        .into_styled(PrimitiveStyle::with_fill(theme.label))
+        .draw(display)
+        .ok();
+
+    let value_color = theme.reading(channel.status);
+    s.clear();
+    let _ = units.write(desc.quantity, channel.value, &mut s);
+    let value_y = (round(dial.cy + radius * 0.5) + 10).min((y + h) as i32 - 6);
+    Text::with_text_style(
+        &s,
+        Point::new(center_x, value_y),
+        MonoTextStyle::new(VALUE_FONT_MEDIUM, value_color),
+        CENTERED,
+    )
+    .draw(display)
+    .ok();
+}
+
+/// Colour every non-neutral band of the sensor along the outer edge of the scale.
+fn draw_warning_arcs<D>(
+    display: &mut D,
+    dial: &Dial,
+    desc: &SensorDescriptor,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let edges = desc.thresholds();
+    let (lo, hi) = (dial.scale.min, dial.scale.max);
+
+    for band in 0..=edges.len() {
+        let lower = if band == 0 { lo } else { edges[band - 1].value.max(lo) };
+        let upper = if band == edges.len() {
+            hi
+        } else {
+            edges[band].value.min(hi)
+        };
+        if upper <= lower {
+            continue;
+        }
+        let status = (desc.status_fn)((lower + upper) / 2.0);
+        if status == Status::Neutral {
+            continue;
+        }
+
+        let style = PrimitiveStyle::with_fill(theme::active().reading(status));
+        let outer = dial.radius;
+        let inner = dial.radius - ARC_WIDTH;
+        let from = dial.value_to_deg(lower);
+        let to = dial.value_to_deg(upper);
+
+        let mut deg = from;
+        while deg < to {
+            let next = (deg + ARC_STEP_DEG as f32).min(to);
+            let (d0, d1) = (direction(deg), direction(next));
+            let (o0, o1) = (dial.point(d0, outer), dial.point(d1, outer));
+            let (i0, i1) = (dial.point(d0, inner), dial.point(d1, inner));
+            Triangle::new(o0, o1, i1).into_styled(style).draw(display).ok();
+            Triangle::new(o0, i1, i0).into_styled(style).draw(display).ok();
+            deg = next;
+        }
+    }
+}
+
+fn draw_ticks<D>(
+    display: &mut D,
+    dial: &Dial,
+    quantity: Quantity,
+    units: &UnitPrefs,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let scale = &dial.scale;
+    let theme = theme::active();
+    let major_style = PrimitiveStyle::with_stroke(theme.text, 2);
+    let minor_style = PrimitiveStyle::with_stroke(theme.label, 1);
+    let full_labels = dial.radius >= FULL_LABELS_MIN_RADIUS;
+
+    let majors = ((scale.max - scale.min) / scale.major_step + 0.001) as u32;
+    let minor_step = scale.major_step / scale.minor_divisions as f32;
+    let label_precision = if units.is_base(quantity) {
+        scale.label_precision
+    } else {
+        0
+    };
+    let mut s: String<8> = String::new();
+
+    for major in 0..=majors {
+        let value = scale.min + major as f32 * scale.major_step;
+        let dir = direction(dial.value_to_deg(value));
+        Line::new(
+            dial.point(dir, dial.radius),
+            dial.point(dir, dial.radius - MAJOR_TICK_LEN),
+        )
+        .into_styled(major_style)
+        .draw(display)
+        .ok();
+
+        if full_labels || major % 2 == 0 || major == majors {
+            s.clear();
+            let label = units.convert(quantity, value) / scale.label_divisor;
+            let _ = write!(s, "{label:.label_precision$}");
+            let pos = dial.point(dir, dial.radius - LABEL_INSET);
+            Text::with_text_style(&s, Point::new(pos.x, pos.y + 4), label_style(), CENTERED)
+                .draw(display)
+                .ok();
+        }
+
+        if major == majors {
+            break;
+        }
+        for minor in 1..scale.minor_divisions {
+            let dir = direction(dial.value_to_deg(value + minor as f32 * minor_step));
+            Line::new(
+                dial.point(dir, dial.radius),
+                dial.point(dir, dial.radius - MINOR_TICK_LEN),
+            )
+            .into_styled(minor_style)
+            .draw(display)
+            .ok();
+        }
+    }
+}
+
+/// Small inward-pointing triangle on the scale edge at the peak value.
+fn draw_peak_marker<D>(
+    display: &mut D,
+    dial: &Dial,
+    peak: f32,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let dir = direction(dial.value_to_deg(peak));
+    let perp = (-dir.1, dir.0);
+    let base = (dial.cx + dir.0 * dial.radius, dial.cy + dir.1 * dial.radius);
+    let corner = |sign: f32| {
+        Point::new(
+            round(base.0 + perp.0 * PEAK_MARKER_HALF_WIDTH * sign),
+            round(base.1 + perp.1 * PEAK_MARKER_HALF_WIDTH * sign),
+        )
+    };
+    Triangle::new(
+        corner(1.0),
+        corner(-1.0),
+        dial.point(dir, dial.radius - PEAK_MARKER_DEPTH),
+    )
+    .into_styled(PrimitiveStyle::with_fill(theme::active().highlight))
+    .draw(display)
+    .ok();
+}
+
+/// Tapered needle rendered as a capsule distance field with one pixel of edge coverage,
+/// blended against the known face colour `bg`.
+fn draw_needle<D>(
+    display: &mut D,
+    dial: &Dial,
+    value: f32,
+    color: Rgb565,
+    bg: Rgb565,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let (dx, dy) = direction(dial.value_to_deg(value));
+    let length = NEEDLE_TAIL + dial.radius - NEEDLE_TIP_INSET;
+    let (ax, ay) = (dial.cx - dx * NEEDLE_TAIL, dial.cy - dy * NEEDLE_TAIL);
+    let (bx, by) = (ax + dx * length, ay + dy * length);
+
+    let pad = NEEDLE_BASE_HALF_WIDTH + 1.0;
+    let x0 = round(ax.min(bx) - pad);
+    let x1 = round(ax.max(bx) + pad);
+    let y0 = round(ay.min(by) - pad);
+    let y1 = round(ay.max(by) + pad);
+
+    let pixels = (y0..=y1).flat_map(move |py| {
+        (x0..=x1).filter_map(move |px| {
+            let (rx, ry) = (px as f32 - ax, py as f32 - ay);
+            let along = rx * dx + ry * dy;
+            let across = ry * dx - rx * dy;
+            let overshoot = if along < 0.0 {
+                -along
+            } else if along > length {
+                along - length
+            } else {
+                0.0
+            };
+            let dist_sq = across * across + overshoot * overshoot;
+
+            let t = (along / length).clamp(0.0, 1.0);
+            let half = NEEDLE_BASE_HALF_WIDTH + (NEEDLE_TIP_HALF_WIDTH - NEEDLE_BASE_HALF_WIDTH) * t;
+            let outer_sq = (half + 0.5) * (half + 0.5);
+            if dist_sq >= outer_sq {
+                return None;
+            }
+            let inner = (half - 0.5).max(0.0);
+            let inner_sq = inner * inner;
+            let coverage = if dist_sq <= inner_sq {
+                256
+            } else {
+                ((outer_sq - dist_sq) / (outer_sq - inner_sq) * 256.0) as u32
+            };
+            Some(Pixel(Point::new(px, py), blend(color, bg, coverage)))
+        })
+    });
+    display.draw_iter(pixels).ok();
+}
+
+/// Mix `fg` over `bg` with `alpha` in 0..=256.
+fn blend(
+    fg: Rgb565,
+    bg: Rgb565,
+    alpha: u32,
+) -> Rgb565 {
+    let mix = |f: u8, b: u8| ((u32::from(f) * alpha + u32::from(b) * (256 - alpha)) >> 8) as u8;
+    Rgb565::new(mix(fg.r(), bg.r()), mix(fg.g(), bg.g()), mix(fg.b(), bg.b()))
+}
@@ -1,32 +1,22 @@
 use core::fmt::Write;
 
+use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::pixelcolor::Rgb565;
 use embedded_graphics::prelude::*;
-use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+use embedded_graphics::primitives::{Line, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, StrokeAlignment};
 use embedded_graphics::text::Text;
 use heapless::String;
 
-use crate::config::{COL_WIDTH, HEADER_HEIGHT, ROW_HEIGHT, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::config::{HEADER_HEIGHT, SCREEN_HEIGHT, SCREEN_WIDTH};
 use crate::render::FpsMode;
-use crate::ui::{CENTERED, GRAY, LABEL_STYLE_WHITE, RED, RIGHT_ALIGNED, TITLE_STYLE_WHITE};
+use crate::state::Grid;
+use crate::ui::{CENTERED, LABEL_FONT, RIGHT_ALIGNED, TITLE_FONT, theme};
 
 const HEADER_TITLE_POS: Point = Point::new(160, 19);
 const HEADER_FPS_POS: Point = Point::new((SCREEN_WIDTH - 5) as i32, 17);
 const HEADER_RECT_POS: Point = Point::new(0, 0);
 const HEADER_RECT_SIZE: Size = Size::new(SCREEN_WIDTH, 26);
 
-const DIV_V1_START: Point = Point::new(COL_WIDTH as i32, HEADER_HEIGHT as i32);
-const DIV_V1_END: Point = Point::new(COL_WIDTH as i32, (SCREEN_HEIGHT - 1) as i32);
-const DIV_V2_START: Point = Point::new((COL_WIDTH * 2) as i32, HEADER_HEIGHT as i32);
-const DIV_V2_END: Point = Point::new((COL_WIDTH * 2) as i32, (SCREEN_HEIGHT - 1) as i32);
-const DIV_V3_START: Point = Point::new((COL_WIDTH * 3) as i32, HEADER_HEIGHT as i32);
-const DIV_V3_END: Point = Point::new((COL_WIDTH * 3) as i32, (SCREEN_HEIGHT - 1) as i32);
-const DIV_H_START: Point = Point::new(0, (HEADER_HEIGHT + ROW_HEIGHT) as i32);
-const DIV_H_END: Point = Point::new((SCREEN_WIDTH - 1) as i32, (HEADER_HEIGHT + ROW_HEIGHT) as i32);
-
-const DIVIDER_STYLE: PrimitiveStyle<Rgb565> = PrimitiveStyle::with_stroke(GRAY, 1);
-const HEADER_FILL_STYLE: PrimitiveStyle<Rgb565> = PrimitiveStyle::with_fill(RED);
-
 pub fn draw_header<D>(
     display: &mut D,
     fps_mode: FpsMode,
@@ -35,14 +25,21 @@ pub fn draw_header<D>(
 ) where
     D: DrawTarget<Color = Rgb565>,
 {
+    let theme = theme::active();
+    let text_color = theme.text_on(theme.header);
     Rectangle::new(HEADER_RECT_POS, HEADER_RECT_SIZE)
-        .into_styled(HEADER_FILL_STYLE)
+        .into_styled(PrimitiveStyle::with_fill(theme.header))
         .draw(display)
         .ok();
 
-    Text::with_text_style("OBD Sim", HEADER_TITLE_POS, TITLE_STYLE_WHITE, CENTERED)
-        .draw(display)
-        .ok();
+    Text::with_text_style(
+        "OBD Sim",
+        HEADER_TITLE_POS,
+        MonoTextStyle::new(TITLE_FONT, text_color),
+        CENTERED,
+    )
+    .draw(display)
+    .ok();
 
     if fps_mode.is_visible() {
         let mut fps_str: String<16> = String::new();
@@ -58,33 +55,64 @@ pub fn draw_header<D>(
                 let _ = write!(fps_str, "{:.0}/{:.0}{}", fps_instant, fps_average, fps_mode.suffix());
             }
         }
-        Text::with_text_style(&fps_str, HEADER_FPS_POS, LABEL_STYLE_WHITE, RIGHT_ALIGNED)
-            .draw(display)
-            .ok();
+        Text::with_text_style(
+            &fps_str,
+            HEADER_FPS_POS,
+            MonoTextStyle::new(LABEL_FONT, text_color),
+            RIGHT_ALIGNED,
+        )
+        .draw(display)
+        .ok();
     }
 }
 
-pub fn draw_dividers<D>(display: &mut D)
-where
+pub fn draw_dividers<D>(
+    display: &mut D,
+    grid: Grid,
+) where
     D: DrawTarget<Color = Rgb565>,
 {
-    Line::new(DIV_V1_START, DIV_V1_END)
-        .into_styled(DIVIDER_STYLE)
-        .draw(display)
-        .ok();
-
-    Line::new(DIV_V2_START, DIV_V2_END)
-        .into_styled(DIVIDER_STYLE)
+    let divider_style = PrimitiveStyle::with_stroke(theme::active().label, 1);
+    for col in 1..grid.columns() {
+        let x = (col * grid.cell_width()) as i32;
+        Line::new(
+            Point::new(x, HEADER_HEIGHT as i32),
+            Point::new(x, (SCREEN_HEIGHT - 1) as i32),
+        )
+        .into_styled(divider_style)
         .draw(display)
         .ok();
+    }
 
-    Line::new(DIV_V3_START, DIV_V3_END)
-        .into_styled(DIVIDER_STYLE)
-        .draw(display)
-        .ok();
+    for row in 1..Grid::ROWS {
+        let y = (HEADER_HEIGHT + row * grid.cell_height()) as i32;
+        Line::new(Point::new(0, y), Point::new((SCREEN_WIDTH - 1) as i32, y))
+            .into_styled(divider_style)
+            .draw(display)
+            .ok();
+    }
+}
 
-    Line::new(DIV_H_START, DIV_H_END)
-        .into_styled(DIVIDER_STYLE)
-        .draw(display)
-        .ok();
+/// Highlight the encoder-selected cell with a 2 px frame on its edges.
+pub fn draw_selection_frame<D>(
+    display: &mut D,
+    grid: Grid,
+    slot: usize,
+) where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let (x, y) = grid.slot_origin(slot);
+    Rectangle::new(
+        Point::new(x as i32, y as i32),
+        Size::new(grid.cell_width(), grid.cell_height()),
+    )
+    .into_styled(
+        PrimitiveStyleBuilder::new()
+            .stroke_color(theme::active().highlight)
+            .stroke_width(2)
+            .stroke_alignment(StrokeAlignment::Inside)
+            .build(),
+    )
+    .draw(display)
+    .ok();
 }
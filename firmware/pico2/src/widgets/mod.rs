@@ -1,29 +1,33 @@
 mod cells;
+mod cue;
+mod gauge;
 mod header;
 mod popups;
 mod primitives;
 
 pub use cells::{
-    SensorDisplayData,
-    draw_afr_cell,
-    draw_batt_cell,
-    draw_boost_cell,
-    draw_temp_cell,
+    batt_status,
+    boost_status,
+    draw_sensor_cell,
+    is_critical_batt,
+    is_critical_boost,
+    is_critical_dsg,
     is_critical_egt,
     is_critical_iat,
-    is_critical_oil_dsg,
+    is_critical_lambda,
+    is_critical_oil,
+    is_critical_rpm,
     is_critical_water,
     is_low_temp_oil,
-    temp_color_egt,
-    temp_color_iat,
-    temp_color_oil_dsg,
-    temp_color_water,
-};
-pub use header::{draw_dividers, draw_header};
-pub use popups::{
-    draw_boost_unit_popup,
-    draw_brightness_popup,
-    draw_danger_manifold_popup,
-    draw_fps_toggle_popup,
-    draw_reset_popup,
+    lambda_status,
+    rpm_status,
+    temp_status_dsg,
+    temp_status_egt,
+    temp_status_iat,
+    temp_status_oil,
+    temp_status_water,
 };
+pub use cue::{ICON_SIZE, draw_cue_icon};
+pub use gauge::GaugeScale;
+pub use header::{draw_dividers, draw_header, draw_selection_frame};
+pub use popups::PopupContext;
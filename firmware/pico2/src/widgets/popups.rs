@@ -1,7 +1,6 @@
 use core::fmt::Write;
 
 use embedded_graphics::mono_font::MonoTextStyle;
-use embedded_graphics::mono_font::ascii::FONT_10X20;
 use embedded_graphics::pixelcolor::Rgb565;
 use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
@@ -10,9 +9,21 @@ use heapless::String;
 
 use crate::config::{CENTER_X, CENTER_Y, SCREEN_HEIGHT, SCREEN_WIDTH};
 use crate::render::FpsMode;
-use crate::ui::{CENTERED, RED, TITLE_STYLE_WHITE, WHITE};
-
-const TITLE_STYLE_RED: MonoTextStyle<'static, Rgb565> = MonoTextStyle::new(&FONT_10X20, RED);
+use crate::state::{
+    AlertManager,
+    AlertRule,
+    Overlay,
+    OverlaySpec,
+    POPUP_DURATION_MS,
+    POPUP_DURATION_SHORT_MS,
+    Popup,
+    Priority,
+    RULES,
+    Severity,
+};
+use crate::ui::{CENTERED, LABEL_FONT, TITLE_FONT, theme};
+use crate::units::{Quantity, UnitPrefs};
+use crate::widgets::{ICON_SIZE, draw_cue_icon};
 
 const RESET_POPUP_WIDTH: u32 = 180;
 const RESET_POPUP_HEIGHT: u32 = 60;
@@ -28,15 +39,13 @@ const RESET_TEXT1_POS: Point = Point::new(CENTER_X, CENTER_Y - 5);
 const RESET_TEXT2_POS: Point = Point::new(CENTER_X, CENTER_Y + 15);
 const FPS_TEXT_POS: Point = Point::new(CENTER_X, CENTER_Y + 5);
 
-const DANGER_POPUP_WIDTH: u32 = 210;
-const DANGER_POPUP_HEIGHT: u32 = 70;
-const DANGER_POPUP_X: i32 = (SCREEN_WIDTH - DANGER_POPUP_WIDTH) as i32 / 2;
-const DANGER_POPUP_Y: i32 = (SCREEN_HEIGHT - DANGER_POPUP_HEIGHT) as i32 / 2;
-const DANGER_TEXT1_POS: Point = Point::new(CENTER_X, CENTER_Y - 8);
-const DANGER_TEXT2_POS: Point = Point::new(CENTER_X, CENTER_Y + 15);
-
-const WHITE_FILL: PrimitiveStyle<Rgb565> = PrimitiveStyle::with_fill(WHITE);
-const RED_FILL: PrimitiveStyle<Rgb565> = PrimitiveStyle::with_fill(RED);
+const ALERT_POPUP_WIDTH: u32 = 210;
+const ALERT_POPUP_HEIGHT: u32 = 84;
+const ALERT_POPUP_X: i32 = (SCREEN_WIDTH - ALERT_POPUP_WIDTH) as i32 / 2;
+const ALERT_POPUP_Y: i32 = (SCREEN_HEIGHT - ALERT_POPUP_HEIGHT) as i32 / 2;
+const ALERT_TITLE_POS: Point = Point::new(CENTER_X, CENTER_Y - 15);
+const ALERT_MESSAGE_POS: Point = Point::new(CENTER_X, CENTER_Y + 8);
+const ALERT_HINT_POS: Point = Point::new(CENTER_X, CENTER_Y + 30);
 
 const RESET_BORDER_POS: Point = Point::new(RESET_POPUP_X - 3, RESET_POPUP_Y - 3);
 const RESET_BORDER_SIZE: Size = Size::new(RESET_POPUP_WIDTH + 6, RESET_POPUP_HEIGHT + 6);
@@ -48,122 +57,207 @@ const FPS_BORDER_SIZE: Size = Size::new(FPS_POPUP_WIDTH + 6, FPS_POPUP_HEIGHT +
 const FPS_BG_POS: Point = Point::new(FPS_POPUP_X, FPS_POPUP_Y);
 const FPS_BG_SIZE: Size = Size::new(FPS_POPUP_WIDTH, FPS_POPUP_HEIGHT);
 
-const DANGER_BORDER_POS: Point = Point::new(DANGER_POPUP_X - 3, DANGER_POPUP_Y - 3);
-const DANGER_BORDER_SIZE: Size = Size::new(DANGER_POPUP_WIDTH + 6, DANGER_POPUP_HEIGHT + 6);
-const DANGER_BG_POS: Point = Point::new(DANGER_POPUP_X, DANGER_POPUP_Y);
-const DANGER_BG_SIZE: Size = Size::new(DANGER_POPUP_WIDTH, DANGER_POPUP_HEIGHT);
+const ALERT_BORDER_POS: Point = Point::new(ALERT_POPUP_X - 3, ALERT_POPUP_Y - 3);
+const ALERT_BORDER_SIZE: Size = Size::new(ALERT_POPUP_WIDTH + 6, ALERT_POPUP_HEIGHT + 6);
+const ALERT_BG_POS: Point = Point::new(ALERT_POPUP_X, ALERT_POPUP_Y);
+const ALERT_BG_SIZE: Size = Size::new(ALERT_POPUP_WIDTH, ALERT_POPUP_HEIGHT);
+
+const RESET_REGION: Rectangle = Rectangle::new(RESET_BORDER_POS, RESET_BORDER_SIZE);
+const FPS_REGION: Rectangle = Rectangle::new(FPS_BORDER_POS, FPS_BORDER_SIZE);
+const ALERT_REGION: Rectangle = Rectangle::new(ALERT_BORDER_POS, ALERT_BORDER_SIZE);
+
+/// What popups read when drawn besides their own content.
+pub struct PopupContext<'a> {
+    pub units: &'a UnitPrefs,
+    pub alerts: &'a AlertManager,
+    pub blink_on: bool,
+}
 
-pub fn draw_reset_popup<D>(display: &mut D)
+impl Overlay for Popup {
+    type Context<'a> = PopupContext<'a>;
+
+    fn spec(&self) -> OverlaySpec {
+        let feedback = |region, timeout_ms| {
+            OverlaySpec {
+                priority: Priority::Feedback,
+                region,
+                timeout_ms: Some(timeout_ms),
+                page_scoped: true,
+            }
+        };
+        match self {
+            Self::Reset => feedback(RESET_REGION, POPUP_DURATION_MS),
+            Self::Fps(_) | Self::BoostUnit => feedback(FPS_REGION, POPUP_DURATION_MS),
+            Self::Brightness(_) => feedback(FPS_REGION, POPUP_DURATION_SHORT_MS),
+            // Shown while the alert heads the queue on the dashboard, which main keeps in sync
+            Self::Alert(_) => {
+                OverlaySpec {
+                    priority: Priority::Alert,
+                    region: ALERT_REGION,
+                    timeout_ms: None,
+                    page_scoped: true,
+                }
+            }
+        }
+    }
+
+    fn draw<D>(
+        &self,
+        display: &mut D,
+        context: &PopupContext<'_>,
+    ) where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        match *self {
+            Self::Reset => draw_reset_popup(display),
+            Self::Fps(fps_mode) => draw_fps_toggle_popup(display, fps_mode),
+            Self::BoostUnit => draw_boost_unit_popup(display, context.units),
+            Self::Brightness(brightness_percent) => draw_brightness_popup(display, brightness_percent),
+            Self::Alert(rule) => {
+                let queued_behind = context.alerts.queue().len().saturating_sub(1);
+                draw_alert_popup(display, &RULES[rule], queued_behind, context.blink_on);
+            }
+        }
+    }
+}
+
+fn draw_reset_popup<D>(display: &mut D)
 where
     D: DrawTarget<Color = Rgb565>,
 {
+    let (border, fill, text_style) = info_styles();
     Rectangle::new(RESET_BORDER_POS, RESET_BORDER_SIZE)
-        .into_styled(WHITE_FILL)
+        .into_styled(border)
         .draw(display)
         .ok();
 
     Rectangle::new(RESET_BG_POS, RESET_BG_SIZE)
-        .into_styled(RED_FILL)
+        .into_styled(fill)
         .draw(display)
         .ok();
 
-    Text::with_text_style("MIN/AVG/MAX", RESET_TEXT1_POS, TITLE_STYLE_WHITE, CENTERED)
+    Text::with_text_style("MIN/AVG/MAX", RESET_TEXT1_POS, text_style, CENTERED)
         .draw(display)
         .ok();
-    Text::with_text_style("RESET", RESET_TEXT2_POS, TITLE_STYLE_WHITE, CENTERED)
+    Text::with_text_style("RESET", RESET_TEXT2_POS, text_style, CENTERED)
         .draw(display)
         .ok();
 }
 
-pub fn draw_fps_toggle_popup<D>(
+fn draw_fps_toggle_popup<D>(
     display: &mut D,
     fps_mode: FpsMode,
 ) where
     D: DrawTarget<Color = Rgb565>,
 {
+    let (border, fill, text_style) = info_styles();
     Rectangle::new(FPS_BORDER_POS, FPS_BORDER_SIZE)
-        .into_styled(WHITE_FILL)
+        .into_styled(border)
         .draw(display)
         .ok();
 
     Rectangle::new(FPS_BG_POS, FPS_BG_SIZE)
-        .into_styled(RED_FILL)
+        .into_styled(fill)
         .draw(display)
         .ok();
 
-    Text::with_text_style(fps_mode.label(), FPS_TEXT_POS, TITLE_STYLE_WHITE, CENTERED)
+    Text::with_text_style(fps_mode.label(), FPS_TEXT_POS, text_style, CENTERED)
         .draw(display)
         .ok();
 }
 
-pub fn draw_boost_unit_popup<D>(
+fn draw_boost_unit_popup<D>(
     display: &mut D,
-    show_psi: bool,
+    units: &UnitPrefs,
 ) where
     D: DrawTarget<Color = Rgb565>,
 {
+    let (border, fill, text_style) = info_styles();
     Rectangle::new(FPS_BORDER_POS, FPS_BORDER_SIZE)
-        .into_styled(WHITE_FILL)
+        .into_styled(border)
         .draw(display)
         .ok();
 
     Rectangle::new(FPS_BG_POS, FPS_BG_SIZE)
-        .into_styled(RED_FILL)
+        .into_styled(fill)
         .draw(display)
         .ok();
 
-    let unit = if show_psi { "BOOST: PSI" } else { "BOOST: BAR" };
-    Text::with_text_style(unit, FPS_TEXT_POS, TITLE_STYLE_WHITE, CENTERED)
+    let mut text: String<16> = String::new();
+    let _ = write!(text, "BOOST: {}", units.symbol(Quantity::Pressure));
+    Text::with_text_style(&text, FPS_TEXT_POS, text_style, CENTERED)
         .draw(display)
         .ok();
 }
 
-pub fn draw_danger_manifold_popup<D>(
+/// The alert at the head of the queue, with a hint to acknowledge it and the count of
+/// alerts waiting behind it. Critical alerts flash with `blink_on`.
+fn draw_alert_popup<D>(
     display: &mut D,
+    rule: &AlertRule,
+    queued_behind: usize,
     blink_on: bool,
 ) where
     D: DrawTarget<Color = Rgb565>,
 {
-    let (bg_style, text_style) = if blink_on {
-        (RED_FILL, TITLE_STYLE_WHITE)
+    let theme = theme::active();
+    let status = rule.severity.status();
+    let color = theme.status(status);
+    let (bg, fg) = if blink_on || rule.severity < Severity::Critical {
+        (color, theme.text_on(color))
     } else {
-        (WHITE_FILL, TITLE_STYLE_RED)
+        (theme.text, color)
     };
+    let text_style = MonoTextStyle::new(TITLE_FONT, fg);
 
-    let border_style = if blink_on { WHITE_FILL } else { RED_FILL };
-
-    Rectangle::new(DANGER_BORDER_POS, DANGER_BORDER_SIZE)
-        .into_styled(border_style)
+    Rectangle::new(ALERT_BORDER_POS, ALERT_BORDER_SIZE)
+        .into_styled(PrimitiveStyle::with_fill(fg))
         .draw(display)
         .ok();
 
-    Rectangle::new(DANGER_BG_POS, DANGER_BG_SIZE)
-        .into_styled(bg_style)
+    Rectangle::new(ALERT_BG_POS, ALERT_BG_SIZE)
+        .into_styled(PrimitiveStyle::with_fill(bg))
         .draw(display)
         .ok();
 
-    Text::with_text_style("WARNING", DANGER_TEXT1_POS, text_style, CENTERED)
+    let title = Text::with_text_style(rule.severity.label(), ALERT_TITLE_POS, text_style, CENTERED);
+    let title_box = title.bounding_box();
+    title.draw(display).ok();
+    let icon_origin = Point::new(
+        title_box.top_left.x - ICON_SIZE as i32 - 6,
+        title_box.center().y - ICON_SIZE as i32 / 2,
+    );
+    draw_cue_icon(display, icon_origin, status.cue().icon, fg);
+
+    Text::with_text_style(rule.message, ALERT_MESSAGE_POS, text_style, CENTERED)
         .draw(display)
         .ok();
-    Text::with_text_style("DANGER TO MANIFOLD", DANGER_TEXT2_POS, text_style, CENTERED)
+
+    let mut hint: String<24> = String::new();
+    let _ = write!(hint, "PRESS: ACK");
+    if queued_behind > 0 {
+        let _ = write!(hint, "  +{} MORE", queued_behind);
+    }
+    Text::with_text_style(&hint, ALERT_HINT_POS, MonoTextStyle::new(LABEL_FONT, fg), CENTERED)
         .draw(display)
         .ok();
 }
 
-pub fn draw_brightness_popup<D>(
+fn draw_brightness_popup<D>(
     display: &mut D,
     brightness_percent: u32,
 ) where
     D: DrawTarget<Color = Rgb565>,
 {
+    let (border, fill, text_style) = info_styles();
     // Re-use the FPS popup size — compact centered box
     Rectangle::new(FPS_BORDER_POS, FPS_BORDER_SIZE)
-        .into_styled(WHITE_FILL)
+        .into_styled(border)
         .draw(display)
         .ok();
 
     Rectangle::new(FPS_BG_POS, FPS_BG_SIZE)
-        .into_styled(RED_FILL)
+        .into_styled(fill)
         .draw(display)
         .ok();
 
@@ -173,7 +267,21 @@ pub fn draw_brightness_popup<D>(
     } else {
         let _ = write!(label, "BL: {}%", brightness_percent);
     }
-    Text::with_text_style(label.as_str(), FPS_TEXT_POS, TITLE_STYLE_WHITE, CENTERED)
+    Text::with_text_style(label.as_str(), FPS_TEXT_POS, text_style, CENTERED)
         .draw(display)
         .ok();
 }
+
+/// Info popups: a text-coloured frame around a box in the header colour.
+fn info_styles() -> (
+    PrimitiveStyle<Rgb565>,
+    PrimitiveStyle<Rgb565>,
+    MonoTextStyle<'static, Rgb565>,
+) {
+    let theme = theme::active();
+    (
+        PrimitiveStyle::with_fill(theme.text),
+        PrimitiveStyle::with_fill(theme.header),
+        MonoTextStyle::new(TITLE_FONT, theme.text_on(theme.header)),
+    )
+}